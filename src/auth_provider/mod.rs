@@ -0,0 +1,278 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{fmt, io, time::Duration};
+
+use bcrypt::verify;
+use ldap3::{ldap_escape, LdapConnAsync, LdapConnSettings, Scope, SearchEntry};
+use sea_orm::*;
+
+use crate::{
+    controllers::auth::Role,
+    entities::{prelude::*, user},
+    Config,
+};
+
+/// A sign-in accepted by a provider.
+pub enum Verified {
+    /// A local account whose password matched.
+    Account(Box<user::Model>),
+    /// A directory entry whose password matched.
+    Directory(DirectoryUser),
+}
+
+/// A directory entry, with the role its groups map to.
+#[derive(Debug)]
+pub struct DirectoryUser {
+    pub email: String,
+    pub firstname: String,
+    pub lastname: String,
+    pub role: Role,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Database(DbErr),
+    /// The directory could not be reached or answered unexpectedly.
+    Directory(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Database(err) => write!(f, "{}", err),
+            Error::Directory(message) => write!(f, "Directory error: {}", message),
+        }
+    }
+}
+
+impl From<DbErr> for Error {
+    fn from(err: DbErr) -> Self {
+        Error::Database(err)
+    }
+}
+
+impl From<ldap3::LdapError> for Error {
+    fn from(err: ldap3::LdapError) -> Self {
+        Error::Directory(err.to_string())
+    }
+}
+
+/// Checks sign-in credentials.
+#[rocket::async_trait]
+pub trait AuthProvider: Send + Sync {
+    /// The verified user, or `None` if this provider does not accept the
+    /// credentials.
+    async fn authenticate(
+        &self,
+        db: &DatabaseConnection,
+        email: &str,
+        password: &str,
+    ) -> Result<Option<Verified>, Error>;
+}
+
+/// The configured providers, tried in order until one accepts.
+pub type AuthProviders = Vec<Box<dyn AuthProvider>>;
+
+pub fn make(config: &Config) -> io::Result<AuthProviders> {
+    config
+        .auth_providers
+        .iter()
+        .map(|name| -> io::Result<Box<dyn AuthProvider>> {
+            match name.as_str() {
+                "local" => Ok(Box::new(Local)),
+                "ldap" => Ok(Box::new(Ldap::new(config))),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown auth provider: {}", name),
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Accounts with a bcrypt password hash in the database.
+pub struct Local;
+
+#[rocket::async_trait]
+impl AuthProvider for Local {
+    async fn authenticate(
+        &self,
+        db: &DatabaseConnection,
+        email: &str,
+        password: &str,
+    ) -> Result<Option<Verified>, Error> {
+        let u = match User::find()
+            .filter(user::Column::Email.eq(email))
+            .one(db)
+            .await?
+        {
+            Some(u) => u,
+            None => return Ok(None),
+        };
+
+        if !verify(password, &u.password).unwrap_or(false) {
+            return Ok(None);
+        }
+
+        Ok(Some(Verified::Account(Box::new(u))))
+    }
+}
+
+/// Binds to an LDAP directory as the user. The user's entry is found with a
+/// search, optionally bound as a service account, and their groups are read
+/// from `memberOf` and, if a group base is set, from group entries listing
+/// them as a `member` or `uniqueMember`.
+pub struct Ldap {
+    url: String,
+    bind_dn: String,
+    bind_password: String,
+    base_dn: String,
+    user_filter: String,
+    group_base_dn: String,
+    admin_groups: Vec<String>,
+    user_groups: Vec<String>,
+}
+
+impl Ldap {
+    pub fn new(config: &Config) -> Ldap {
+        Ldap {
+            url: config.ldap_url.to_owned(),
+            bind_dn: config.ldap_bind_dn.to_owned(),
+            bind_password: config.ldap_bind_password.to_owned(),
+            base_dn: config.ldap_base_dn.to_owned(),
+            user_filter: config.ldap_user_filter.to_owned(),
+            group_base_dn: config.ldap_group_base_dn.to_owned(),
+            admin_groups: config.ldap_admin_groups.to_owned(),
+            user_groups: config.ldap_user_groups.to_owned(),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl AuthProvider for Ldap {
+    async fn authenticate(
+        &self,
+        _db: &DatabaseConnection,
+        email: &str,
+        password: &str,
+    ) -> Result<Option<Verified>, Error> {
+        // Most directories treat a bind without a password as anonymous.
+        if password.is_empty() {
+            return Ok(None);
+        }
+
+        let settings = LdapConnSettings::new().set_conn_timeout(Duration::from_secs(10));
+        let (conn, mut ldap) = LdapConnAsync::with_settings(settings, &self.url).await?;
+        ldap3::drive!(conn);
+
+        if !self.bind_dn.is_empty() {
+            ldap.simple_bind(&self.bind_dn, &self.bind_password)
+                .await?
+                .success()?;
+        }
+
+        let (entries, _) = ldap
+            .search(
+                &self.base_dn,
+                Scope::Subtree,
+                &user_filter(&self.user_filter, email),
+                vec!["mail", "givenName", "sn", "cn", "memberOf"],
+            )
+            .await?
+            .success()?;
+
+        // No entry, or an ambiguous filter.
+        if entries.len() != 1 {
+            ldap.unbind().await?;
+            return Ok(None);
+        }
+
+        let entry = SearchEntry::construct(entries.into_iter().next().unwrap());
+
+        if ldap.simple_bind(&entry.dn, password).await?.rc != 0 {
+            ldap.unbind().await?;
+            return Ok(None);
+        }
+
+        let mut groups = attribute_values(&entry, "memberOf");
+
+        if !self.group_base_dn.is_empty() {
+            let dn = ldap_escape(entry.dn.as_str());
+            let (group_entries, _) = ldap
+                .search(
+                    &self.group_base_dn,
+                    Scope::Subtree,
+                    &format!("(|(member={})(uniqueMember={}))", dn, dn),
+                    vec!["1.1"],
+                )
+                .await?
+                .success()?;
+
+            groups.extend(
+                group_entries
+                    .into_iter()
+                    .map(|g| SearchEntry::construct(g).dn),
+            );
+        }
+
+        ldap.unbind().await?;
+
+        let role = match map_role(&groups, &self.admin_groups, &self.user_groups) {
+            Some(r) => r,
+            None => return Ok(None),
+        };
+
+        let first = |name: &str| attribute_values(&entry, name).into_iter().next();
+
+        Ok(Some(Verified::Directory(DirectoryUser {
+            email: first("mail").unwrap_or(email.to_string()),
+            firstname: first("givenName")
+                .or_else(|| first("cn"))
+                .unwrap_or(email.to_string()),
+            lastname: first("sn").unwrap_or_default(),
+            role,
+        })))
+    }
+}
+
+/// The user search filter, with each `{email}` replaced by the escaped email.
+pub fn user_filter(template: &str, email: &str) -> String {
+    template.replace("{email}", &ldap_escape(email))
+}
+
+/// The role for a user in `groups`: admin for any of the admin groups, else
+/// user, if in one of the user groups or none are configured. Group DNs
+/// compare case-insensitively.
+pub fn map_role(
+    groups: &[String],
+    admin_groups: &[String],
+    user_groups: &[String],
+) -> Option<Role> {
+    let member_of = |wanted: &[String]| {
+        wanted
+            .iter()
+            .any(|w| groups.iter().any(|g| g.eq_ignore_ascii_case(w)))
+    };
+
+    if member_of(admin_groups) {
+        Some(Role::Admin)
+    } else if user_groups.is_empty() || member_of(user_groups) {
+        Some(Role::User)
+    } else {
+        None
+    }
+}
+
+fn attribute_values(entry: &SearchEntry, name: &str) -> Vec<String> {
+    entry
+        .attrs
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.to_owned())
+        .unwrap_or_default()
+}
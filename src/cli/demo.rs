@@ -0,0 +1,72 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm::{prelude::Decimal, DatabaseConnection};
+
+use super::{
+    transfer::{self, Export, ExportCategory, ExportItem, ExportSupplier},
+    Result,
+};
+
+fn category(uuid: &str, parent_uuid: Option<&str>, name: &str) -> ExportCategory {
+    ExportCategory {
+        uuid: uuid.to_string(),
+        parent_uuid: parent_uuid.map(str::to_string),
+        name: name.to_string(),
+        description: None,
+    }
+}
+
+fn item(
+    category_uuid: &str,
+    name: &str,
+    quantity: i64,
+    unit: &str,
+    cost: i64,
+    location: &str,
+) -> ExportItem {
+    ExportItem {
+        category_uuid: Some(category_uuid.to_string()),
+        name: name.to_string(),
+        description: None,
+        quantity: Decimal::from(quantity),
+        unit: unit.to_string(),
+        unit_cost: Some(Decimal::new(cost, 2)),
+        currency: None,
+        mode: "bulk".to_string(),
+        location: Some(location.to_string()),
+    }
+}
+
+/// The demo data goes through the import, so it is checked the same way.
+pub(super) async fn seed(db: &DatabaseConnection, admin_email: &str) -> Result {
+    let export = Export {
+        organization: "Demo Warehouse".to_string(),
+        categories: vec![
+            category("office", None, "Office"),
+            category("stationery", Some("office"), "Stationery"),
+            category("electronics", None, "Electronics"),
+            category("cleaning", None, "Cleaning"),
+        ],
+        suppliers: vec![ExportSupplier {
+            name: "Acme Supplies".to_string(),
+            email: Some("orders@acme.example.com".to_string()),
+            phone: None,
+            address: None,
+        }],
+        items: vec![
+            item("stationery", "A4 paper", 40, "pcs", 499, "Store room"),
+            item("stationery", "Ballpoint pens", 12, "dozen", 350, "Store room"),
+            item("office", "Desk chair", 8, "pcs", 12900, "Warehouse"),
+            item("electronics", "USB-C cable", 25, "pcs", 899, "IT cupboard"),
+            item("electronics", "Network cable", 150, "m", 45, "IT cupboard"),
+            item("cleaning", "Floor cleaner", 20, "l", 325, "Warehouse"),
+        ],
+    };
+
+    transfer::create(db, admin_email, &export).await
+}
@@ -0,0 +1,48 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm::DatabaseConnection;
+use sea_orm_migration::prelude::*;
+
+use super::{MigrateAction, Result};
+use crate::migrator::Migrator;
+
+pub(super) async fn run(db: &DatabaseConnection, action: MigrateAction) -> Result {
+    let applied = Migrator::get_applied_migrations(db).await?.len();
+
+    match action {
+        MigrateAction::Up { steps } => Migrator::up(db, steps).await?,
+        MigrateAction::Down { steps } => Migrator::down(db, Some(steps)).await?,
+        MigrateAction::Status => return status(db).await,
+    }
+
+    let now = Migrator::get_applied_migrations(db).await?.len();
+    if now >= applied {
+        println!("Applied {} migrations", now - applied);
+    } else {
+        println!("Rolled back {} migrations", applied - now);
+    }
+
+    Ok(())
+}
+
+async fn status(db: &DatabaseConnection) -> Result {
+    let (migrations, unknown) = Migrator::state(db).await?;
+
+    for migration in migrations {
+        match migration.applied_at {
+            Some(at) => println!("Applied  {}  {}", migration.name, at.to_rfc3339()),
+            None => println!("Pending  {}", migration.name),
+        }
+    }
+
+    for name in unknown {
+        println!("Unknown  {}", name);
+    }
+
+    Ok(())
+}
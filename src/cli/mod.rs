@@ -0,0 +1,155 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{error, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+
+use crate::{auth_provider, db, jwt, mailer, storage, Config};
+
+mod demo;
+mod migrate;
+mod transfer;
+mod users;
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn error::Error>>;
+
+#[derive(Debug, Parser)]
+#[clap(
+    name = "iron-guard-server",
+    version,
+    about = "Iron Guard inventory server"
+)]
+pub struct Cli {
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the HTTP server (the default)
+    Serve,
+    /// Apply, roll back or list database migrations
+    Migrate {
+        #[clap(subcommand)]
+        action: MigrateAction,
+    },
+    /// Create an administrator
+    CreateAdmin {
+        #[clap(long)]
+        email: String,
+        #[clap(long, default_value = "Admin")]
+        firstname: String,
+        #[clap(long, default_value = "User")]
+        lastname: String,
+        /// Generated and printed when omitted
+        #[clap(long)]
+        password: Option<String>,
+        /// UUID of the organization to administer. Defaults to the first one,
+        /// which is created if there is none.
+        #[clap(long)]
+        organization: Option<String>,
+    },
+    /// Set a user's password and sign them out everywhere
+    ResetPassword {
+        #[clap(long)]
+        email: String,
+        /// Generated and printed when omitted
+        #[clap(long)]
+        password: Option<String>,
+    },
+    /// Create a demo organization with sample categories, suppliers and items
+    SeedDemo {
+        /// Email of the user who administers the demo organization
+        #[clap(long)]
+        admin: String,
+    },
+    /// Write an organization's categories, suppliers and items as JSON
+    Export {
+        /// UUID of the organization
+        #[clap(long)]
+        organization: String,
+        /// Written to standard output when omitted
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Create an organization from an export
+    Import {
+        /// Email of the user who administers the new organization
+        #[clap(long)]
+        admin: String,
+        file: PathBuf,
+    },
+    /// Validate the configuration without starting the server
+    CheckConfig,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateAction {
+    /// Apply pending migrations
+    Up {
+        /// Apply this many at most. Defaults to all.
+        #[clap(long)]
+        steps: Option<u32>,
+    },
+    /// Roll back applied migrations
+    Down {
+        #[clap(long, default_value = "1")]
+        steps: u32,
+    },
+    /// List migrations and whether they are applied
+    Status,
+}
+
+/// Runs any command other than `serve`.
+pub async fn run(command: Command) -> Result {
+    let profile = rocket::Config::figment().profile().clone();
+    let config = Config::load(profile.as_str().as_str())?;
+
+    if let Command::CheckConfig = command {
+        jwt::Keys::make(&config, profile == rocket::Config::DEBUG_PROFILE)?;
+        storage::make(&config)?;
+        mailer::make(&config)?;
+        auth_provider::make(&config)?;
+
+        println!("The configuration for the {} profile is valid.", profile);
+        return Ok(());
+    }
+
+    let db = db::connect(&config).await?;
+
+    match command {
+        Command::Migrate { action } => migrate::run(&db, action).await,
+        Command::CreateAdmin {
+            email,
+            firstname,
+            lastname,
+            password,
+            organization,
+        } => {
+            users::create_admin(
+                &db,
+                &email,
+                &firstname,
+                &lastname,
+                password,
+                organization.as_deref(),
+            )
+            .await
+        }
+        Command::ResetPassword { email, password } => {
+            users::reset_password(&db, &email, password).await
+        }
+        Command::SeedDemo { admin } => demo::seed(&db, &admin).await,
+        Command::Export {
+            organization,
+            output,
+        } => transfer::export(&db, &organization, output).await,
+        Command::Import { admin, file } => transfer::import(&db, &admin, &file).await,
+        Command::Serve | Command::CheckConfig => unreachable!(),
+    }
+}
@@ -0,0 +1,279 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{collections::HashMap, fs, path::Path, path::PathBuf};
+
+use rocket::serde::{Deserialize, Serialize};
+use sea_orm::{prelude::Decimal, *};
+use uuid::Uuid;
+
+use super::{users, Result};
+use crate::{
+    controllers::items::ItemMode,
+    entities::{category, item, location, organization, prelude::*, supplier},
+};
+
+/// An organization's categories, suppliers and items. Their uuids only link
+/// them within the file; imported records get new ones. Items name their
+/// location, and each name becomes one location on import.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Export {
+    pub organization: String,
+    pub categories: Vec<ExportCategory>,
+    pub suppliers: Vec<ExportSupplier>,
+    pub items: Vec<ExportItem>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ExportCategory {
+    pub uuid: String,
+    pub parent_uuid: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ExportSupplier {
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ExportItem {
+    pub category_uuid: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub quantity: Decimal,
+    pub unit: String,
+    pub unit_cost: Option<Decimal>,
+    pub currency: Option<String>,
+    pub mode: String,
+    #[serde(default)]
+    pub location: Option<String>,
+}
+
+pub(super) async fn export(
+    db: &DatabaseConnection,
+    organization_uuid: &str,
+    output: Option<PathBuf>,
+) -> Result {
+    let organization = match Organization::find()
+        .filter(organization::Column::Uuid.eq(organization_uuid))
+        .one(db)
+        .await?
+    {
+        Some(o) => o,
+        None => return Err(format!("There is no organization {}", organization_uuid).into()),
+    };
+
+    let categories = Category::find()
+        .filter(category::Column::OrganizationId.eq(organization.id))
+        .order_by_asc(category::Column::Id)
+        .all(db)
+        .await?;
+
+    let uuids = categories
+        .iter()
+        .map(|c| (c.id, c.uuid.to_owned()))
+        .collect::<HashMap<_, _>>();
+
+    let locations = Location::find()
+        .filter(location::Column::OrganizationId.eq(organization.id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|l| (l.id, l.name))
+        .collect::<HashMap<_, _>>();
+
+    let export = Export {
+        organization: organization.name,
+        categories: categories
+            .iter()
+            .map(|c| ExportCategory {
+                uuid: c.uuid.to_owned(),
+                parent_uuid: c.parent_id.and_then(|id| uuids.get(&id).cloned()),
+                name: c.name.to_owned(),
+                description: c.description.to_owned(),
+            })
+            .collect(),
+        suppliers: Supplier::find()
+            .filter(supplier::Column::OrganizationId.eq(organization.id))
+            .order_by_asc(supplier::Column::Id)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| ExportSupplier {
+                name: s.name,
+                email: s.email,
+                phone: s.phone,
+                address: s.address,
+            })
+            .collect(),
+        items: Item::find()
+            .filter(item::Column::OrganizationId.eq(organization.id))
+            .order_by_asc(item::Column::Id)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|i| ExportItem {
+                category_uuid: i.category_id.and_then(|id| uuids.get(&id).cloned()),
+                name: i.name,
+                description: i.description,
+                quantity: i.quantity,
+                unit: i.unit,
+                unit_cost: i.unit_cost,
+                currency: i.currency,
+                mode: i.mode,
+                location: i.location_id.and_then(|id| locations.get(&id).cloned()),
+            })
+            .collect(),
+    };
+
+    let json = serde_json::to_string_pretty(&export)?;
+
+    match output {
+        Some(path) => fs::write(path, json)?,
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+pub(super) async fn import(db: &DatabaseConnection, admin_email: &str, file: &Path) -> Result {
+    let export: Export = serde_json::from_str(&fs::read_to_string(file)?)?;
+
+    create(db, admin_email, &export).await
+}
+
+/// Creates a new organization holding the records of `export`, administered
+/// by the user with `admin_email`.
+pub(super) async fn create(db: &DatabaseConnection, admin_email: &str, export: &Export) -> Result {
+    let admin = users::find_user(db, admin_email).await?;
+
+    let txn = db.begin().await?;
+
+    let organization = users::create_organization(&txn, &export.organization, admin.id).await?;
+
+    let mut categories = HashMap::new();
+    for c in &export.categories {
+        let parent_id = match &c.parent_uuid {
+            Some(uuid) => match categories.get(uuid) {
+                Some(id) => Some(*id),
+                None => return Err(format!("Category {} comes before its parent", c.name).into()),
+            },
+            None => None,
+        };
+
+        let id = Category::insert(category::ActiveModel {
+            uuid: Set(Uuid::new_v4().to_string()),
+            organization_id: Set(organization.id),
+            user_id: Set(admin.id),
+            parent_id: Set(parent_id),
+            name: Set(c.name.to_owned()),
+            description: Set(c.description.to_owned()),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?
+        .last_insert_id;
+
+        categories.insert(c.uuid.to_owned(), id);
+    }
+
+    for s in &export.suppliers {
+        Supplier::insert(supplier::ActiveModel {
+            uuid: Set(Uuid::new_v4().to_string()),
+            organization_id: Set(organization.id),
+            user_id: Set(admin.id),
+            name: Set(s.name.to_owned()),
+            email: Set(s.email.to_owned()),
+            phone: Set(s.phone.to_owned()),
+            address: Set(s.address.to_owned()),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?;
+    }
+
+    let mut locations = HashMap::new();
+    for i in &export.items {
+        // Serialized items are counted by their assets, which are not exported.
+        let mode = ItemMode::from_str(&i.mode);
+        let quantity = match mode {
+            ItemMode::Bulk => i.quantity,
+            ItemMode::Serialized => Decimal::ZERO,
+        };
+
+        if Item::validate_quantity(mode, Some(&i.unit), quantity).is_err() {
+            return Err(format!("Item {}: {} is not a valid quantity", i.name, i.unit).into());
+        }
+
+        let location_id = match &i.location {
+            Some(name) => match locations.get(name) {
+                Some(id) => Some(*id),
+                None => {
+                    let id = Location::insert(location::ActiveModel {
+                        uuid: Set(Uuid::new_v4().to_string()),
+                        organization_id: Set(organization.id),
+                        name: Set(name.to_owned()),
+                        ..Default::default()
+                    })
+                    .exec(&txn)
+                    .await?
+                    .last_insert_id;
+
+                    locations.insert(name.to_owned(), id);
+                    Some(id)
+                }
+            },
+            None => None,
+        };
+
+        let item_id = Item::insert(item::ActiveModel {
+            uuid: Set(Uuid::new_v4().to_string()),
+            organization_id: Set(organization.id),
+            user_id: Set(admin.id),
+            category_id: Set(i
+                .category_uuid
+                .as_ref()
+                .and_then(|uuid| categories.get(uuid).copied())),
+            name: Set(i.name.to_owned()),
+            description: Set(i.description.to_owned()),
+            quantity: Set(quantity),
+            unit: Set(i.unit.to_owned()),
+            unit_cost: Set(i.unit_cost),
+            currency: Set(i.currency.to_owned()),
+            mode: Set(mode.as_str().to_string()),
+            location_id: Set(location_id),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?
+        .last_insert_id;
+
+        StockMovement::record(&txn, item_id, admin.id, quantity, i.unit_cost, "initial").await?;
+    }
+
+    txn.commit().await?;
+
+    println!(
+        "Created {} ({}) with {} categories, {} suppliers and {} items",
+        organization.name,
+        organization.uuid,
+        export.categories.len(),
+        export.suppliers.len(),
+        export.items.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use bcrypt::{hash, DEFAULT_COST};
+use sea_orm::*;
+use uuid::Uuid;
+
+use super::Result;
+use crate::{
+    controllers::passwords::random_token,
+    entities::{organization, organization_user, prelude::*, user},
+};
+
+pub(super) async fn find_user(db: &DatabaseConnection, email: &str) -> Result<user::Model> {
+    match User::find()
+        .filter(user::Column::Email.eq(email))
+        .one(db)
+        .await?
+    {
+        Some(u) => Ok(u),
+        None => Err(format!("There is no user with the email {}", email).into()),
+    }
+}
+
+/// Creates an organization administered by `user_id`.
+pub(super) async fn create_organization<C: ConnectionTrait>(
+    db: &C,
+    name: &str,
+    user_id: i32,
+) -> Result<organization::Model> {
+    let organization = organization::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        name: Set(name.to_owned()),
+        require_admin_two_factor: Set(false),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    add_admin(db, organization.id, user_id).await?;
+
+    Ok(organization)
+}
+
+async fn add_admin<C: ConnectionTrait>(db: &C, organization_id: i32, user_id: i32) -> Result {
+    OrganizationUser::insert(organization_user::ActiveModel {
+        organization_id: Set(organization_id),
+        user_id: Set(user_id),
+        role: Set("admin".to_string()),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    Ok(())
+}
+
+/// The given password, or a generated one that is printed.
+fn password_or_random(password: Option<String>) -> String {
+    match password {
+        Some(p) => p,
+        None => {
+            let password = random_token()[..16].to_string();
+            println!("Generated password: {}", password);
+            password
+        }
+    }
+}
+
+pub(super) async fn create_admin(
+    db: &DatabaseConnection,
+    email: &str,
+    firstname: &str,
+    lastname: &str,
+    password: Option<String>,
+    organization_uuid: Option<&str>,
+) -> Result {
+    if User::find()
+        .filter(user::Column::Email.eq(email))
+        .one(db)
+        .await?
+        .is_some()
+    {
+        return Err(format!("A user with the email {} already exists", email).into());
+    }
+
+    let organization = match organization_uuid {
+        Some(uuid) => match Organization::find()
+            .filter(organization::Column::Uuid.eq(uuid))
+            .one(db)
+            .await?
+        {
+            Some(o) => Some(o),
+            None => return Err(format!("There is no organization {}", uuid).into()),
+        },
+        None => {
+            Organization::find()
+                .order_by_asc(organization::Column::Id)
+                .one(db)
+                .await?
+        }
+    };
+
+    let password = password_or_random(password);
+
+    let txn = db.begin().await?;
+
+    let admin = user::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        role: Set("admin".to_string()),
+        firstname: Set(firstname.to_owned()),
+        lastname: Set(lastname.to_owned()),
+        email: Set(email.to_owned()),
+        password: Set(hash(password, DEFAULT_COST)?),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+
+    let organization = match organization {
+        Some(o) => {
+            add_admin(&txn, o.id, admin.id).await?;
+            o
+        }
+        None => create_organization(&txn, "Iron Guard", admin.id).await?,
+    };
+
+    txn.commit().await?;
+
+    println!(
+        "Created {} as an administrator of {} ({})",
+        email, organization.name, organization.uuid
+    );
+
+    Ok(())
+}
+
+pub(super) async fn reset_password(
+    db: &DatabaseConnection,
+    email: &str,
+    password: Option<String>,
+) -> Result {
+    let member = find_user(db, email).await?;
+    let user_id = member.id;
+
+    let mut member: user::ActiveModel = member.into();
+    member.password = Set(hash(password_or_random(password), DEFAULT_COST)?);
+    member.update(db).await?;
+
+    Session::revoke_all(db, user_id).await?;
+
+    println!(
+        "Set the password of {} and signed them out everywhere",
+        email
+    );
+
+    Ok(())
+}
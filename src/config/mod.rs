@@ -0,0 +1,450 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{
+    collections::{HashMap, HashSet},
+    env, fmt, fs, io,
+    str::FromStr,
+};
+
+use crate::{jwt, logging::LogFormat, throttle::Window, Config};
+
+/// Read when `IRON_GUARD_CONFIG` is not set, if it exists.
+const DEFAULT_PATH: &str = "iron-guard.toml";
+
+/// Everything wrong with the configuration, one problem per entry.
+#[derive(Debug)]
+pub struct Error(pub Vec<String>);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Invalid configuration:")?;
+        for problem in &self.0 {
+            writeln!(f, "  - {}", problem)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Config {
+    /// Reads the configuration for the Rocket profile from the environment
+    /// and the config file at `IRON_GUARD_CONFIG`, or `iron-guard.toml` if it
+    /// exists.
+    pub fn load(profile: &str) -> Result<Config, Error> {
+        Config::load_with(profile, &|name| env::var(name).ok())
+    }
+
+    /// Like `load`, with the `IRON_GUARD_*` variables read from `env`.
+    pub fn load_with(profile: &str, env: &dyn Fn(&str) -> Option<String>) -> Result<Config, Error> {
+        let (path, required) = match env("IRON_GUARD_CONFIG") {
+            Some(path) => (path, true),
+            None => (DEFAULT_PATH.to_string(), false),
+        };
+
+        let file = match fs::read_to_string(&path) {
+            Ok(text) => Some(text),
+            Err(err) if !required && err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(Error(vec![format!("{}: {}", path, err)])),
+        };
+
+        Config::parse(
+            profile,
+            file.as_deref().map(|text| (path.as_str(), text)),
+            env,
+        )
+    }
+
+    /// Each setting comes from its `IRON_GUARD_*` variable in `env`, else the
+    /// `(path, text)` config file, else its default. Top-level file settings
+    /// apply to every profile. A table named after the profile, e.g.
+    /// `[release]`, overrides them.
+    pub fn parse(
+        profile: &str,
+        file: Option<(&str, &str)>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Config, Error> {
+        let mut s = Source::new(profile, file, env)?;
+
+        let config = Config {
+            secret: s.string("SECRET", jwt::DEFAULT_SECRET),
+            jwt_algorithm: s.string("JWT_ALGORITHM", "HS256"),
+            jwt_private_key: s.string("JWT_PRIVATE_KEY", ""),
+            jwt_public_keys: s.list("JWT_PUBLIC_KEYS", "", ';'),
+            token_ttl: s.number("TOKEN_TTL", 4 * 60),
+            database_url: s.string("DATABASE_URL", ""),
+            db_type: s.string("DB_TYPE", "mysql"),
+            db_host: s.string("DB_HOST", "localhost"),
+            db_port: s.string("DB_PORT", "3306"),
+            db_username: s.string("DB_USERNAME", "root"),
+            db_password: s.string("DB_PASSWORD", ""),
+            db_database: s.string("DB_DATABASE", "iron_guard"),
+            db_max_connections: s.number("DB_MAX_CONNECTIONS", 10),
+            db_min_connections: s.number("DB_MIN_CONNECTIONS", 1),
+            db_connect_timeout: s.number("DB_CONNECT_TIMEOUT", 8),
+            db_idle_timeout: s.number("DB_IDLE_TIMEOUT", 600),
+            auto_migrate: s.boolean("AUTO_MIGRATE", true),
+            storage_type: s.string("STORAGE_TYPE", "local"),
+            storage_path: s.string("STORAGE_PATH", "storage"),
+            upload_max_size: s.number("UPLOAD_MAX_SIZE", 10 * 1024 * 1024),
+            upload_allowed_types: s
+                .list(
+                    "UPLOAD_ALLOWED_TYPES",
+                    "image/png,image/jpeg,image/gif,image/webp,application/pdf,text/plain",
+                    ',',
+                )
+                .iter()
+                .map(|t| t.to_lowercase())
+                .collect(),
+            currency: s.string("CURRENCY", "USD"),
+            expiry_warning_days: s.number("EXPIRY_WARNING_DAYS", 30),
+            mail_type: s.string("MAIL_TYPE", "log"),
+            mail_path: s.string("MAIL_PATH", "mail"),
+            mail_from: s.string("MAIL_FROM", "Iron Guard <noreply@localhost>"),
+            app_url: s.string("APP_URL", "http://localhost:3000"),
+            cors_origins: s.list("CORS_ORIGINS", "*", ','),
+            cors_methods: s
+                .list("CORS_METHODS", "GET,POST,PUT,PATCH,DELETE", ',')
+                .iter()
+                .map(|m| m.to_uppercase())
+                .collect(),
+            cors_headers: s.list(
+                "CORS_HEADERS",
+                "Content-Type,token,X-API-Key,If-Match,If-None-Match",
+                ',',
+            ),
+            cors_max_age: s.number("CORS_MAX_AGE", 24 * 60 * 60),
+            cors_credentials: s.boolean("CORS_CREDENTIALS", false),
+            password_reset_ttl: s.number("PASSWORD_RESET_TTL", 60),
+            invitation_ttl: s.number("INVITATION_TTL", 72),
+            lockout_threshold: s.number("LOCKOUT_THRESHOLD", 5),
+            lockout_ip_threshold: s.number("LOCKOUT_IP_THRESHOLD", 20),
+            lockout_base: s.number("LOCKOUT_BASE", 30),
+            lockout_max: s.number("LOCKOUT_MAX", 3600),
+            rate_limit_auth: s.window("RATE_LIMIT_AUTH", "20/60"),
+            rate_limit_api: s.window("RATE_LIMIT_API", "600/60"),
+            trust_proxy: s.boolean("TRUST_PROXY", false),
+            password_sign_in: s.boolean("PASSWORD_SIGN_IN", true),
+            api_keys: s.boolean("API_KEYS", true),
+            attachments: s.boolean("ATTACHMENTS", true),
+            metrics_token: s.string("METRICS_TOKEN", ""),
+            low_stock_threshold: s.number("LOW_STOCK_THRESHOLD", 5),
+            log_format: s.string("LOG_FORMAT", "json"),
+            log_queries: s.boolean("LOG_QUERIES", false),
+            otel_endpoint: s.string("OTEL_ENDPOINT", ""),
+            otel_service_name: s.string("OTEL_SERVICE_NAME", "iron-guard-server"),
+            oidc_issuer: s.string("OIDC_ISSUER", ""),
+            oidc_client_id: s.string("OIDC_CLIENT_ID", ""),
+            oidc_client_secret: s.string("OIDC_CLIENT_SECRET", ""),
+            oidc_redirect_url: s.string(
+                "OIDC_REDIRECT_URL",
+                "http://localhost:8000/auth/oidc/callback",
+            ),
+            oidc_scopes: s.string("OIDC_SCOPES", "openid email profile"),
+            oidc_organization: s.string("OIDC_ORGANIZATION", ""),
+            oidc_role_claim: s.string("OIDC_ROLE_CLAIM", "roles"),
+            oidc_admin_role: s.string("OIDC_ADMIN_ROLE", "admin"),
+            auth_providers: s
+                .list("AUTH_PROVIDERS", "local", ',')
+                .iter()
+                .map(|p| p.to_lowercase())
+                .collect(),
+            ldap_url: s.string("LDAP_URL", "ldap://localhost:389"),
+            ldap_bind_dn: s.string("LDAP_BIND_DN", ""),
+            ldap_bind_password: s.string("LDAP_BIND_PASSWORD", ""),
+            ldap_base_dn: s.string("LDAP_BASE_DN", ""),
+            ldap_user_filter: s.string("LDAP_USER_FILTER", "(mail={email})"),
+            ldap_group_base_dn: s.string("LDAP_GROUP_BASE_DN", ""),
+            // Group DNs contain commas.
+            ldap_admin_groups: s.list("LDAP_ADMIN_GROUPS", "", ';'),
+            ldap_user_groups: s.list("LDAP_USER_GROUPS", "", ';'),
+            ldap_organization: s.string("LDAP_ORGANIZATION", ""),
+        };
+
+        let mut problems = s.finish();
+        problems.extend(config.validate());
+
+        if !problems.is_empty() {
+            return Err(Error(problems));
+        }
+
+        Ok(config)
+    }
+
+    /// Problems with settings that parsed but do not make sense.
+    fn validate(&self) -> Vec<String> {
+        let mut problems = vec![];
+        let mut check = |ok: bool, problem: &str| {
+            if !ok {
+                problems.push(problem.to_string());
+            }
+        };
+
+        if self.database_url.is_empty() {
+            check(
+                self.db_type == "mysql",
+                "IRON_GUARD_DB_TYPE: the only supported database type is mysql",
+            );
+        } else {
+            check(
+                self.database_url.starts_with("mysql://"),
+                "IRON_GUARD_DATABASE_URL: must be a mysql:// URL",
+            );
+        }
+
+        check(
+            self.db_max_connections > 0,
+            "IRON_GUARD_DB_MAX_CONNECTIONS: must be at least 1",
+        );
+        check(
+            self.db_min_connections <= self.db_max_connections,
+            "IRON_GUARD_DB_MIN_CONNECTIONS: must not exceed IRON_GUARD_DB_MAX_CONNECTIONS",
+        );
+        check(
+            self.token_ttl > 0,
+            "IRON_GUARD_TOKEN_TTL: must be at least 1",
+        );
+        check(
+            self.upload_max_size > 0,
+            "IRON_GUARD_UPLOAD_MAX_SIZE: must be at least 1",
+        );
+        check(
+            self.lockout_base <= self.lockout_max,
+            "IRON_GUARD_LOCKOUT_BASE: must not exceed IRON_GUARD_LOCKOUT_MAX",
+        );
+        check(
+            is_url(&self.app_url),
+            "IRON_GUARD_APP_URL: must be an http:// or https:// URL",
+        );
+        check(
+            self.cors_origins == ["*"]
+                || self
+                    .cors_origins
+                    .iter()
+                    .all(|o| is_url(o) && !o.ends_with('/')),
+            "IRON_GUARD_CORS_ORIGINS: must be * or origins like https://example.com",
+        );
+        check(
+            !self.cors_credentials
+                || !self
+                    .cors_origins
+                    .iter()
+                    .chain(&self.cors_headers)
+                    .any(|v| v == "*"),
+            "IRON_GUARD_CORS_CREDENTIALS: browsers refuse credentials with * origins or headers",
+        );
+        check(
+            LogFormat::from_str(&self.log_format).is_some(),
+            "IRON_GUARD_LOG_FORMAT: must be json, text or off",
+        );
+        check(
+            self.otel_endpoint.is_empty() || is_url(&self.otel_endpoint),
+            "IRON_GUARD_OTEL_ENDPOINT: must be an http:// or https:// URL",
+        );
+        check(
+            ["HS256", "RS256", "EdDSA"].contains(&self.jwt_algorithm.as_str()),
+            "IRON_GUARD_JWT_ALGORITHM: must be HS256, RS256 or EdDSA",
+        );
+        check(
+            self.jwt_algorithm == "HS256" || !self.jwt_private_key.is_empty(),
+            "IRON_GUARD_JWT_PRIVATE_KEY: is required for RS256 and EdDSA",
+        );
+        check(
+            self.oidc_issuer.is_empty() || !self.oidc_client_id.is_empty(),
+            "IRON_GUARD_OIDC_CLIENT_ID: is required with IRON_GUARD_OIDC_ISSUER",
+        );
+        check(
+            !self.auth_providers.contains(&"ldap".to_string()) || !self.ldap_base_dn.is_empty(),
+            "IRON_GUARD_LDAP_BASE_DN: is required with the ldap auth provider",
+        );
+
+        problems
+    }
+}
+
+fn is_url(value: &str) -> bool {
+    value.starts_with("http://") || value.starts_with("https://")
+}
+
+/// A setting as written: text from the environment or a file string or
+/// number, or a file array.
+enum Setting {
+    Text(String),
+    List(Vec<String>),
+}
+
+/// Looks settings up and collects the problems found along the way, so they
+/// can all be reported at once.
+struct Source<'a> {
+    env: &'a dyn Fn(&str) -> Option<String>,
+    file: HashMap<String, toml::Value>,
+    read: HashSet<String>,
+    problems: Vec<String>,
+}
+
+impl<'a> Source<'a> {
+    fn new(
+        profile: &str,
+        file: Option<(&str, &str)>,
+        env: &'a dyn Fn(&str) -> Option<String>,
+    ) -> Result<Source<'a>, Error> {
+        let mut source = Source {
+            env,
+            file: HashMap::new(),
+            read: HashSet::new(),
+            problems: vec![],
+        };
+
+        let (path, text) = match file {
+            Some(f) => f,
+            None => return Ok(source),
+        };
+
+        let table = match toml::from_str::<toml::value::Table>(text) {
+            Ok(table) => table,
+            Err(err) => return Err(Error(vec![format!("{}: {}", path, err)])),
+        };
+
+        for (key, value) in &table {
+            if !value.is_table() {
+                source.file.insert(key.to_owned(), value.to_owned());
+            }
+        }
+
+        if let Some(toml::Value::Table(overrides)) = table.get(profile) {
+            for (key, value) in overrides {
+                source.file.insert(key.to_owned(), value.to_owned());
+            }
+        }
+
+        Ok(source)
+    }
+
+    /// The problems found, including file settings that were never read,
+    /// which are most likely misspelled.
+    fn finish(mut self) -> Vec<String> {
+        let mut unknown = self
+            .file
+            .keys()
+            .filter(|k| !self.read.contains(*k))
+            .map(|k| format!("{}: unknown setting", k))
+            .collect::<Vec<_>>();
+        unknown.sort();
+
+        self.problems.extend(unknown);
+        self.problems
+    }
+
+    fn problem(&mut self, key: &str, message: String) {
+        self.problems.push(format!(
+            "IRON_GUARD_{} ({}): {}",
+            key,
+            key.to_lowercase(),
+            message
+        ));
+    }
+
+    fn lookup(&mut self, key: &str) -> Option<Setting> {
+        let name = key.to_lowercase();
+        self.read.insert(name.to_owned());
+
+        if let Some(value) = (self.env)(&format!("IRON_GUARD_{}", key)) {
+            return Some(Setting::Text(value));
+        }
+
+        Some(match self.file.get(&name)? {
+            toml::Value::String(s) => Setting::Text(s.to_owned()),
+            toml::Value::Array(values) => Setting::List(
+                values
+                    .iter()
+                    .map(|v| match v {
+                        toml::Value::String(s) => s.to_owned(),
+                        other => other.to_string(),
+                    })
+                    .collect(),
+            ),
+            other => Setting::Text(other.to_string()),
+        })
+    }
+
+    fn text(&mut self, key: &str) -> Option<String> {
+        match self.lookup(key)? {
+            Setting::Text(s) => Some(s),
+            Setting::List(_) => {
+                self.problem(key, "expected a single value, got a list".to_string());
+                None
+            }
+        }
+    }
+
+    fn string(&mut self, key: &str, default: &str) -> String {
+        self.text(key).unwrap_or(default.to_string())
+    }
+
+    fn number<T: FromStr>(&mut self, key: &str, default: T) -> T {
+        let value = match self.text(key) {
+            Some(v) => v,
+            None => return default,
+        };
+
+        match value.trim().parse() {
+            Ok(n) => n,
+            Err(_) => {
+                self.problem(key, format!("expected a number, got \"{}\"", value));
+                default
+            }
+        }
+    }
+
+    fn boolean(&mut self, key: &str, default: bool) -> bool {
+        match self.text(key).as_deref().map(str::trim) {
+            None => default,
+            Some("true") => true,
+            Some("false") => false,
+            Some(other) => {
+                self.problem(key, format!("expected true or false, got \"{}\"", other));
+                default
+            }
+        }
+    }
+
+    /// A list, from a `separator`-separated string or a file array.
+    fn list(&mut self, key: &str, default: &str, separator: char) -> Vec<String> {
+        let values = match self.lookup(key) {
+            Some(Setting::List(values)) => values,
+            Some(Setting::Text(s)) => s.split(separator).map(|v| v.to_string()).collect(),
+            None => default.split(separator).map(|v| v.to_string()).collect(),
+        };
+
+        values
+            .iter()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+
+    /// A rate limit as `<requests>/<seconds>`, or `off`.
+    fn window(&mut self, key: &str, default: &str) -> Option<Window> {
+        let value = self.string(key, default);
+
+        if value.trim() == "off" {
+            return None;
+        }
+
+        let window = Window::parse(&value);
+        if window.is_none() {
+            self.problem(
+                key,
+                format!("expected <requests>/<seconds> or off, got \"{}\"", value),
+            );
+        }
+
+        window
+    }
+}
@@ -0,0 +1,333 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::{Duration, SystemTime};
+
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{prelude::DateTimeUtc, sea_query::Expr, *};
+use uuid::Uuid;
+
+use super::{
+    admin_required,
+    auth::{acting_role, AuthenticatedUser, Role},
+    error_response, not_found,
+    passwords::{hash_token, random_token},
+    success, ErrorResponder, Response, ResponseList,
+};
+use crate::entities::{api_key, organization_user, prelude::*};
+
+/// Prefix of every key, so leaked keys are easy to recognize.
+const KEY_PREFIX: &str = "igk_";
+
+/// Longest `expires_in_days`. Keys that should last longer never expire.
+const MAX_EXPIRY_DAYS: u64 = 365;
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestApiKey<'r> {
+    name: &'r str,
+    read_only: Option<bool>,
+    expires_in_days: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseApiKey {
+    uuid: String,
+    name: String,
+    prefix: String,
+    read_only: bool,
+    expires_at: Option<String>,
+    last_used_at: Option<String>,
+    created_at: String,
+}
+
+impl From<api_key::Model> for ResponseApiKey {
+    fn from(api_key: api_key::Model) -> ResponseApiKey {
+        ResponseApiKey {
+            uuid: api_key.uuid,
+            name: api_key.name,
+            prefix: api_key.prefix,
+            read_only: api_key.read_only,
+            expires_at: api_key.expires_at.map(|t| t.to_rfc3339()),
+            last_used_at: api_key.last_used_at.map(|t| t.to_rfc3339()),
+            created_at: api_key.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// A new key. `key` is only ever shown here.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseNewApiKey {
+    pub uuid: String,
+    pub key: String,
+}
+
+impl ApiKey {
+    /// The unexpired key and the owner's current membership in the key's
+    /// organization, with the role they act with there. Keys stop working
+    /// when the owner leaves it.
+    pub async fn authenticate(
+        db: &DatabaseConnection,
+        key: &str,
+    ) -> Result<Option<(api_key::Model, organization_user::Model)>, DbErr> {
+        if !key.starts_with(KEY_PREFIX) {
+            return Ok(None);
+        }
+
+        let now = DateTimeUtc::from(SystemTime::now());
+
+        let api_key = match ApiKey::find()
+            .filter(api_key::Column::KeyHash.eq(hash_token(key)))
+            .filter(
+                Condition::any()
+                    .add(api_key::Column::ExpiresAt.is_null())
+                    .add(api_key::Column::ExpiresAt.gt(now)),
+            )
+            .one(db)
+            .await?
+        {
+            Some(k) => k,
+            None => return Ok(None),
+        };
+
+        let mut membership = match OrganizationUser::find()
+            .filter(organization_user::Column::OrganizationId.eq(api_key.organization_id))
+            .filter(organization_user::Column::UserId.eq(api_key.user_id))
+            .one(db)
+            .await?
+        {
+            Some(m) => m,
+            None => return Ok(None),
+        };
+
+        // Keys carry no more rights than a sign-in would.
+        let owner = match User::find_by_id(api_key.user_id).one(db).await? {
+            Some(u) => u,
+            None => return Ok(None),
+        };
+        membership.role = acting_role(db, &owner, &membership)
+            .await?
+            .as_str()
+            .to_string();
+
+        ApiKey::update_many()
+            .col_expr(api_key::Column::LastUsedAt, Expr::value(now))
+            .filter(api_key::Column::Id.eq(api_key.id))
+            .exec(db)
+            .await?;
+
+        Ok(Some((api_key, membership)))
+    }
+
+    /// Revokes every key of the user, in all their organizations, except
+    /// `current`.
+    pub async fn revoke_others(
+        db: &DatabaseConnection,
+        user_id: i32,
+        current: Option<i32>,
+    ) -> Result<(), DbErr> {
+        let mut delete = ApiKey::delete_many().filter(api_key::Column::UserId.eq(user_id));
+        if let Some(id) = current {
+            delete = delete.filter(api_key::Column::Id.ne(id));
+        }
+
+        delete.exec(db).await?;
+
+        Ok(())
+    }
+
+    fn owned_by(organization_id: i32, user_id: i32) -> Select<api_key::Entity> {
+        ApiKey::find()
+            .filter(api_key::Column::OrganizationId.eq(organization_id))
+            .filter(api_key::Column::UserId.eq(user_id))
+    }
+
+    async fn list(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        user_id: i32,
+    ) -> Result<ResponseList<ResponseApiKey>, DbErr> {
+        let keys = ApiKey::owned_by(organization_id, user_id)
+            .order_by_desc(api_key::Column::CreatedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(ResponseApiKey::from)
+            .collect::<Vec<_>>();
+
+        Ok(ResponseList {
+            total: keys.len(),
+            results: keys,
+        })
+    }
+
+    async fn revoke(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        user_id: i32,
+        uuid: &str,
+    ) -> Response {
+        let result = ApiKey::delete_many()
+            .filter(api_key::Column::OrganizationId.eq(organization_id))
+            .filter(api_key::Column::UserId.eq(user_id))
+            .filter(api_key::Column::Uuid.eq(uuid))
+            .exec(db)
+            .await?;
+
+        if result.rows_affected == 0 {
+            return Err(not_found());
+        }
+
+        success(Status::Ok)
+    }
+}
+
+#[get("/api-keys")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<Json<ResponseList<ResponseApiKey>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    Ok(Json(ApiKey::list(db, user.organization_id, user.id).await?))
+}
+
+/// Creates a key acting as the user in the active organization.
+#[post("/api-keys", data = "<req_api_key>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    req_api_key: Json<RequestApiKey<'_>>,
+) -> Result<Json<ResponseNewApiKey>, ErrorResponder> {
+    // Keys cannot mint further keys, so a leaked key cannot outlive its revocation.
+    if user.api_key.is_some() {
+        return Err(error_response(
+            Status::Forbidden,
+            "API keys cannot create API keys".to_string(),
+        ));
+    }
+
+    if req_api_key.name.trim().is_empty() {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            "Name is required".to_string(),
+        ));
+    }
+
+    if req_api_key
+        .expires_in_days
+        .is_some_and(|days| days > MAX_EXPIRY_DAYS)
+    {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            format!("Keys expire within {} days at most", MAX_EXPIRY_DAYS),
+        ));
+    }
+
+    let db = db as &DatabaseConnection;
+
+    // Keys act with the membership's role whenever it is granted, so they
+    // cannot be made while the token withholds it, e.g. until two-factor
+    // authentication is set up.
+    let membership = match OrganizationUser::find()
+        .filter(organization_user::Column::OrganizationId.eq(user.organization_id))
+        .filter(organization_user::Column::UserId.eq(user.id))
+        .one(db)
+        .await?
+    {
+        Some(m) => m,
+        None => return Err(not_found()),
+    };
+
+    if user.role == Role::User && Role::from_str(&membership.role) == Role::Admin {
+        return Err(error_response(
+            Status::Forbidden,
+            "Set up two-factor authentication and sign in again to create API keys".to_string(),
+        ));
+    }
+
+    let key = format!("{}{}", KEY_PREFIX, random_token());
+    let uuid = Uuid::new_v4().to_string();
+
+    ApiKey::insert(api_key::ActiveModel {
+        uuid: Set(uuid.to_owned()),
+        organization_id: Set(user.organization_id),
+        user_id: Set(user.id),
+        name: Set(req_api_key.name.trim().to_string()),
+        prefix: Set(key[..KEY_PREFIX.len() + 8].to_string()),
+        key_hash: Set(hash_token(&key)),
+        read_only: Set(req_api_key.read_only.unwrap_or(false)),
+        expires_at: Set(req_api_key.expires_in_days.map(|days| {
+            DateTimeUtc::from(SystemTime::now() + Duration::from_secs(days * 24 * 60 * 60))
+        })),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    Ok(Json(ResponseNewApiKey { uuid, key }))
+}
+
+#[delete("/api-keys/<uuid>")]
+pub async fn delete(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    ApiKey::revoke(db, user.organization_id, user.id, uuid).await
+}
+
+#[get("/<user_uuid>/api-keys")]
+pub async fn user_index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    user_uuid: &str,
+) -> Result<Json<ResponseList<ResponseApiKey>>, ErrorResponder> {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let member = match User::from_uuid(db, user.organization_id, user_uuid).await? {
+        Some((u, _)) => u,
+        None => return Err(not_found()),
+    };
+
+    Ok(Json(
+        ApiKey::list(db, user.organization_id, member.id).await?,
+    ))
+}
+
+#[delete("/<user_uuid>/api-keys/<uuid>")]
+pub async fn user_delete(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    user_uuid: &str,
+    uuid: &str,
+) -> Response {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let member = match User::from_uuid(db, user.organization_id, user_uuid).await? {
+        Some((u, _)) => u,
+        None => return Err(not_found()),
+    };
+
+    ApiKey::revoke(db, user.organization_id, member.id, uuid).await
+}
@@ -0,0 +1,419 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::SystemTime;
+
+use chrono::NaiveDate;
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{
+    prelude::{Date, DateTimeUtc, Decimal},
+    *,
+};
+use uuid::Uuid;
+
+use super::{
+    auth::AuthenticatedUser,
+    error_response,
+    items::{ItemMode, ResponseItem},
+    not_found, success,
+    users::ResponseUser,
+    ErrorResponder, Response, ResponseList,
+};
+use crate::entities::{asset, asset_event, item, prelude::*};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssetStatus {
+    InStock,
+    Assigned,
+    Repair,
+    Retired,
+}
+
+impl AssetStatus {
+    pub fn from_str(status: &str) -> Option<AssetStatus> {
+        match status {
+            "in_stock" => Some(AssetStatus::InStock),
+            "assigned" => Some(AssetStatus::Assigned),
+            "repair" => Some(AssetStatus::Repair),
+            "retired" => Some(AssetStatus::Retired),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetStatus::InStock => "in_stock",
+            AssetStatus::Assigned => "assigned",
+            AssetStatus::Repair => "repair",
+            AssetStatus::Retired => "retired",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestAsset<'r> {
+    serial_number: &'r str,
+    status: Option<String>,
+    assignee_uuid: Option<String>,
+    purchase_date: Option<String>,
+    notes: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseAssetEvent {
+    status: String,
+    assignee: Option<ResponseUser>,
+    user: Option<ResponseUser>,
+    notes: Option<String>,
+    created_at: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseAsset {
+    uuid: String,
+    item: Option<ResponseItem>,
+    serial_number: String,
+    status: String,
+    assignee: Option<ResponseUser>,
+    purchase_date: Option<String>,
+    notes: Option<String>,
+    history: Option<Vec<ResponseAssetEvent>>,
+}
+
+impl From<&asset::Model> for ResponseAsset {
+    fn from(asset: &asset::Model) -> ResponseAsset {
+        ResponseAsset {
+            uuid: asset.uuid.to_owned(),
+            item: None,
+            serial_number: asset.serial_number.to_owned(),
+            status: asset.status.to_owned(),
+            assignee: None,
+            purchase_date: asset.purchase_date.map(|d| d.to_string()),
+            notes: asset.notes.to_owned(),
+            history: None,
+        }
+    }
+}
+
+impl Asset {
+    pub async fn from_uuid(
+        db: &DatabaseConnection,
+        item: &item::Model,
+        uuid: &str,
+    ) -> Result<Option<asset::Model>, DbErr> {
+        Asset::find()
+            .filter(asset::Column::ItemId.eq(item.id))
+            .filter(asset::Column::Uuid.eq(uuid))
+            .one(db)
+            .await
+    }
+
+    /// Number of units that still count towards the item's quantity.
+    pub async fn active_count<C: ConnectionTrait>(db: &C, item_id: i32) -> Result<u32, DbErr> {
+        Ok(Asset::find()
+            .filter(asset::Column::ItemId.eq(item_id))
+            .filter(asset::Column::Status.ne(AssetStatus::Retired.as_str()))
+            .count(db)
+            .await? as u32)
+    }
+
+    /// Sets a serialized item's quantity to its number of active units,
+    /// recording the difference as a stock movement.
+    pub async fn sync_quantity<C: ConnectionTrait>(
+        db: &C,
+        item: &item::Model,
+        user_id: i32,
+    ) -> Result<(), DbErr> {
+        let quantity = Decimal::from(Asset::active_count(db, item.id).await?);
+
+        StockMovement::record(
+            db,
+            item.id,
+            user_id,
+            quantity - item.quantity,
+            item.unit_cost,
+            "asset",
+        )
+        .await?;
+
+        let mut item: item::ActiveModel = item.clone().into();
+        item.quantity = Set(quantity);
+        item.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+        item.update(db).await?;
+
+        Ok(())
+    }
+}
+
+async fn user_response(
+    db: &DatabaseConnection,
+    id: Option<i32>,
+) -> Result<Option<ResponseUser>, DbErr> {
+    Ok(match id {
+        Some(id) => User::find_by_id(id).one(db).await?.map(ResponseUser::from),
+        None => None,
+    })
+}
+
+/// Validates the requested status and assignee. Assigned units need an
+/// assignee; every other status clears it.
+async fn resolve(
+    db: &DatabaseConnection,
+    organization_id: i32,
+    req_asset: &RequestAsset<'_>,
+) -> Result<(AssetStatus, Option<i32>, Option<Date>), ErrorResponder> {
+    let status = match &req_asset.status {
+        Some(s) => match AssetStatus::from_str(s) {
+            Some(s) => s,
+            None => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    "Status must be one of in_stock, assigned, repair, retired".to_string(),
+                ))
+            }
+        },
+        None => AssetStatus::InStock,
+    };
+
+    let mut assignee: Option<i32> = None;
+    if status == AssetStatus::Assigned {
+        let member = match &req_asset.assignee_uuid {
+            Some(uuid) => User::from_uuid(db, organization_id, uuid).await?,
+            None => None,
+        };
+
+        match member {
+            Some((u, _)) => assignee = Some(u.id),
+            None => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    "Assigned assets need a valid assignee_uuid".to_string(),
+                ))
+            }
+        }
+    }
+
+    let purchase_date = match &req_asset.purchase_date {
+        Some(d) => match NaiveDate::parse_from_str(d, "%Y-%m-%d") {
+            Ok(d) => Some(d),
+            Err(_) => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    "Dates must be formatted as YYYY-MM-DD".to_string(),
+                ))
+            }
+        },
+        None => None,
+    };
+
+    Ok((status, assignee, purchase_date))
+}
+
+async fn serialized_item(
+    db: &DatabaseConnection,
+    organization_id: i32,
+    uuid: &str,
+) -> Result<item::Model, ErrorResponder> {
+    let item = match Item::from_uuid(db, organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    if ItemMode::from_str(&item.mode) != ItemMode::Serialized {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            "Item is not serialized".to_string(),
+        ));
+    }
+
+    Ok(item)
+}
+
+#[get("/<uuid>/assets")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Result<Json<ResponseList<ResponseAsset>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let assets = item
+        .find_related(Asset)
+        .find_also_related(User)
+        .order_by_asc(asset::Column::SerialNumber)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(asset, assignee)| {
+            let mut response = ResponseAsset::from(&asset);
+            response.assignee = assignee.map(ResponseUser::from);
+            response
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: assets.len(),
+        results: assets,
+    }))
+}
+
+#[post("/<uuid>/assets", data = "<req_asset>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_asset: Json<RequestAsset<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let item = serialized_item(db, user.organization_id, uuid).await?;
+    let (status, assignee, purchase_date) = resolve(db, user.organization_id, &req_asset).await?;
+
+    let txn = db.begin().await?;
+
+    let asset_id = Asset::insert(asset::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        item_id: Set(item.id),
+        serial_number: Set(req_asset.serial_number.to_owned()),
+        status: Set(status.as_str().to_string()),
+        assignee_id: Set(assignee),
+        purchase_date: Set(purchase_date),
+        notes: Set(req_asset.notes.to_owned()),
+        ..Default::default()
+    })
+    .exec(&txn)
+    .await?
+    .last_insert_id;
+
+    AssetEvent::insert(asset_event::ActiveModel {
+        asset_id: Set(asset_id),
+        user_id: Set(user.id),
+        status: Set(status.as_str().to_string()),
+        assignee_id: Set(assignee),
+        notes: Set(req_asset.notes.to_owned()),
+        ..Default::default()
+    })
+    .exec(&txn)
+    .await?;
+
+    Asset::sync_quantity(&txn, &item, user.id).await?;
+
+    txn.commit().await?;
+
+    success(Status::Created)
+}
+
+#[get("/<uuid>/assets/<asset_uuid>")]
+pub async fn show(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    asset_uuid: &str,
+) -> Result<Json<ResponseAsset>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let asset = match Asset::from_uuid(db, &item, asset_uuid).await? {
+        Some(a) => a,
+        None => return Err(not_found()),
+    };
+
+    let mut response = ResponseAsset::from(&asset);
+    response.item = Some(ResponseItem::from(&item));
+    response.assignee = user_response(db, asset.assignee_id).await?;
+
+    let mut history = vec![];
+    for event in asset
+        .find_related(AssetEvent)
+        .order_by_desc(asset_event::Column::CreatedAt)
+        .order_by_desc(asset_event::Column::Id)
+        .all(db)
+        .await?
+    {
+        history.push(ResponseAssetEvent {
+            status: event.status,
+            assignee: user_response(db, event.assignee_id).await?,
+            user: user_response(db, Some(event.user_id)).await?,
+            notes: event.notes,
+            created_at: event.created_at.to_rfc3339(),
+        });
+    }
+
+    response.history = Some(history);
+
+    Ok(Json(response))
+}
+
+#[put("/<uuid>/assets/<asset_uuid>", data = "<req_asset>")]
+pub async fn update(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    asset_uuid: &str,
+    req_asset: Json<RequestAsset<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let item = serialized_item(db, user.organization_id, uuid).await?;
+
+    let asset = match Asset::from_uuid(db, &item, asset_uuid).await? {
+        Some(a) => a,
+        None => return Err(not_found()),
+    };
+
+    let (status, assignee, purchase_date) = resolve(db, user.organization_id, &req_asset).await?;
+    let changed = asset.status != status.as_str() || asset.assignee_id != assignee;
+
+    let txn = db.begin().await?;
+
+    let mut asset: asset::ActiveModel = asset.into();
+
+    asset.serial_number = Set(req_asset.serial_number.to_owned());
+    asset.status = Set(status.as_str().to_string());
+    asset.assignee_id = Set(assignee);
+    asset.purchase_date = Set(purchase_date);
+    asset.notes = Set(req_asset.notes.to_owned());
+
+    asset.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+
+    let asset = asset.update(&txn).await?;
+
+    if changed {
+        AssetEvent::insert(asset_event::ActiveModel {
+            asset_id: Set(asset.id),
+            user_id: Set(user.id),
+            status: Set(status.as_str().to_string()),
+            assignee_id: Set(assignee),
+            notes: Set(req_asset.notes.to_owned()),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?;
+
+        Asset::sync_quantity(&txn, &item, user.id).await?;
+    }
+
+    txn.commit().await?;
+
+    success(Status::Ok)
+}
@@ -0,0 +1,315 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::io::Cursor;
+
+use image::ImageOutputFormat;
+use rocket::{
+    data::Capped,
+    form::Form,
+    fs::TempFile,
+    http::{ContentType, Status},
+    serde::{json::Json, Serialize},
+    tokio::{fs, task},
+    *,
+};
+use sea_orm::*;
+use uuid::Uuid;
+
+use super::{
+    auth::AuthenticatedUser, error_response, not_found, success, ErrorResponder, Response,
+    ResponseList,
+};
+use crate::{
+    entities::{attachment, item, prelude::*},
+    storage::StorageBackend,
+    Config,
+};
+
+const THUMBNAIL_SIZE: u32 = 256;
+
+#[derive(FromForm)]
+pub struct RequestAttachment<'r> {
+    file: Capped<TempFile<'r>>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseAttachment {
+    uuid: String,
+    name: String,
+    content_type: String,
+    size: u64,
+    thumbnail: bool,
+}
+
+impl From<&attachment::Model> for ResponseAttachment {
+    fn from(attachment: &attachment::Model) -> ResponseAttachment {
+        ResponseAttachment {
+            uuid: attachment.uuid.to_owned(),
+            name: attachment.name.to_owned(),
+            content_type: attachment.content_type.to_owned(),
+            size: attachment.size,
+            thumbnail: attachment.thumbnail_key.is_some(),
+        }
+    }
+}
+
+impl Attachment {
+    pub async fn from_uuid(
+        db: &DatabaseConnection,
+        item: &item::Model,
+        uuid: &str,
+    ) -> Result<Option<attachment::Model>, DbErr> {
+        Attachment::find()
+            .filter(attachment::Column::ItemId.eq(item.id))
+            .filter(attachment::Column::Uuid.eq(uuid))
+            .one(db)
+            .await
+    }
+
+    pub async fn for_item(
+        db: &DatabaseConnection,
+        item: &item::Model,
+    ) -> Result<Vec<ResponseAttachment>, DbErr> {
+        Ok(item
+            .find_related(Attachment)
+            .order_by_asc(attachment::Column::CreatedAt)
+            .all(db)
+            .await?
+            .iter()
+            .map(ResponseAttachment::from)
+            .collect::<Vec<_>>())
+    }
+
+    /// Removes the attachment row along with its stored file and thumbnail.
+    pub async fn remove(
+        db: &DatabaseConnection,
+        storage: &StorageBackend,
+        attachment: attachment::Model,
+    ) -> Result<(), ErrorResponder> {
+        Attachment::remove_files(storage, &attachment).await?;
+
+        attachment.delete(db).await?;
+
+        Ok(())
+    }
+
+    /// Removes the stored file and thumbnail of an attachment whose row is gone.
+    pub async fn remove_files(
+        storage: &StorageBackend,
+        attachment: &attachment::Model,
+    ) -> std::io::Result<()> {
+        storage.delete(&attachment.storage_key).await?;
+
+        if let Some(thumbnail_key) = &attachment.thumbnail_key {
+            storage.delete(thumbnail_key).await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn read_upload(file: &TempFile<'_>) -> std::io::Result<Vec<u8>> {
+    match file {
+        TempFile::Buffered { content } => Ok(content.as_bytes().to_vec()),
+        _ => match file.path() {
+            Some(path) => fs::read(path).await,
+            None => Ok(vec![]),
+        },
+    }
+}
+
+fn make_thumbnail(data: Vec<u8>) -> Option<Vec<u8>> {
+    let thumbnail = image::load_from_memory(&data)
+        .ok()?
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+
+    let mut bytes = Cursor::new(vec![]);
+    thumbnail
+        .write_to(&mut bytes, ImageOutputFormat::Png)
+        .ok()?;
+
+    Some(bytes.into_inner())
+}
+
+#[get("/<uuid>/attachments")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Result<Json<ResponseList<ResponseAttachment>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let attachments = Attachment::for_item(db, &item).await?;
+
+    Ok(Json(ResponseList {
+        total: attachments.len(),
+        results: attachments,
+    }))
+}
+
+#[post("/<uuid>/attachments", data = "<req_attachment>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    storage: &State<StorageBackend>,
+    config: &State<Config>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_attachment: Form<RequestAttachment<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let file = &req_attachment.file;
+
+    if !file.is_complete() || file.len() > config.upload_max_size {
+        return Err(error_response(
+            Status::PayloadTooLarge,
+            format!("Files must not exceed {} bytes", config.upload_max_size),
+        ));
+    }
+
+    let content_type = match file.content_type() {
+        Some(ct) => ct.clone(),
+        None => ContentType::Binary,
+    };
+
+    let media_type = format!("{}/{}", content_type.top(), content_type.sub());
+    if !config.upload_allowed_types.contains(&media_type) {
+        return Err(error_response(
+            Status::UnsupportedMediaType,
+            format!("Files of type {} are not allowed", media_type),
+        ));
+    }
+
+    let data = read_upload(file).await?;
+
+    let attachment_uuid = Uuid::new_v4().to_string();
+    let mut storage_key = format!("items/{}/{}", item.uuid, attachment_uuid);
+    if let Some(ext) = content_type.extension() {
+        storage_key = format!("{}.{}", storage_key, ext);
+    }
+
+    storage.put(&storage_key, &data).await?;
+
+    let mut thumbnail_key: Option<String> = None;
+    if content_type.top() == "image" {
+        if let Ok(Some(thumbnail)) = task::spawn_blocking(move || make_thumbnail(data)).await {
+            let key = format!("items/{}/{}.thumb.png", item.uuid, attachment_uuid);
+            storage.put(&key, &thumbnail).await?;
+            thumbnail_key = Some(key);
+        }
+    }
+
+    let name = file
+        .raw_name()
+        .map(|n| n.dangerous_unsafe_unsanitized_raw().as_str().to_owned())
+        .unwrap_or_else(|| "attachment".to_string());
+
+    Attachment::insert(attachment::ActiveModel {
+        uuid: Set(attachment_uuid),
+        item_id: Set(item.id),
+        user_id: Set(user.id),
+        name: Set(name),
+        content_type: Set(media_type),
+        size: Set(file.len()),
+        storage_key: Set(storage_key),
+        thumbnail_key: Set(thumbnail_key),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    success(Status::Created)
+}
+
+#[get("/<uuid>/attachments/<attachment_uuid>")]
+pub async fn show(
+    db: &State<DatabaseConnection>,
+    storage: &State<StorageBackend>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    attachment_uuid: &str,
+) -> Result<(ContentType, Vec<u8>), ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let attachment = match Attachment::from_uuid(db, &item, attachment_uuid).await? {
+        Some(a) => a,
+        None => return Err(not_found()),
+    };
+
+    let content_type =
+        ContentType::parse_flexible(&attachment.content_type).unwrap_or(ContentType::Binary);
+
+    Ok((content_type, storage.get(&attachment.storage_key).await?))
+}
+
+#[get("/<uuid>/attachments/<attachment_uuid>/thumbnail")]
+pub async fn thumbnail(
+    db: &State<DatabaseConnection>,
+    storage: &State<StorageBackend>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    attachment_uuid: &str,
+) -> Result<(ContentType, Vec<u8>), ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let thumbnail_key = match Attachment::from_uuid(db, &item, attachment_uuid).await? {
+        Some(attachment::Model {
+            thumbnail_key: Some(key),
+            ..
+        }) => key,
+        _ => return Err(not_found()),
+    };
+
+    Ok((ContentType::PNG, storage.get(&thumbnail_key).await?))
+}
+
+#[delete("/<uuid>/attachments/<attachment_uuid>")]
+pub async fn delete(
+    db: &State<DatabaseConnection>,
+    storage: &State<StorageBackend>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    attachment_uuid: &str,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let attachment = match Attachment::from_uuid(db, &item, attachment_uuid).await? {
+        Some(a) => a,
+        None => return Err(not_found()),
+    };
+
+    Attachment::remove(db, storage, attachment).await?;
+
+    success(Status::Ok)
+}
@@ -7,20 +7,28 @@
  */
 use std::time::SystemTime;
 
-use bcrypt::verify;
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use rocket::{
-    http::Status,
+    http::{Method, Status},
     outcome::Outcome,
     request::{self, FromRequest, Request},
-    serde::{json::Json, Deserialize, Serialize},
+    serde::{
+        json::{Json, Value},
+        Deserialize, Serialize,
+    },
     State,
 };
 use sea_orm::*;
 
-use super::{error_response, ErrorResponder};
+use super::{
+    error_response, sessions::ClientInfo, too_many_requests, two_factor::check_code,
+    ErrorResponder,
+};
 use crate::{
-    entities::{prelude::*, user},
+    auth_provider::{self, AuthProviders, DirectoryUser, Verified},
+    entities::{organization, organization_user, prelude::*, session, user},
+    jwt::Keys,
+    logging,
+    throttle::Lockout,
     Config,
 };
 
@@ -37,13 +45,34 @@ impl Role {
             _ => Role::User,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Admin => "admin",
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(crate = "rocket::serde")]
 struct Claims {
     sub: i32,
+    org: i32,
     role: String,
+    /// The session's uuid. Revoking the session revokes the token.
+    sid: String,
+    exp: u64,
+}
+
+/// Proof of a correct password for a user who still has to pass 2FA. It has
+/// no `role`, so it does not decode as [`Claims`] and cannot authenticate.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde")]
+struct ChallengeClaims {
+    sub: i32,
+    org: i32,
+    challenge: bool,
     exp: u64,
 }
 
@@ -52,8 +81,25 @@ struct Claims {
 pub struct RequestSignIn<'r> {
     email: &'r str,
     password: &'r str,
+    organization_uuid: Option<&'r str>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestSwitch<'r> {
+    organization_uuid: &'r str,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestTwoFactor<'r> {
+    token: &'r str,
+    code: &'r str,
 }
 
+/// `status` is `success`, `2fa_required` when `token` is a challenge to pass
+/// to `/auth/2fa`, or `2fa_setup_required` when the token is limited to the
+/// `user` role until the user enables 2FA.
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct ResponseSignIn {
@@ -61,9 +107,16 @@ pub struct ResponseSignIn {
     pub token: String,
 }
 
+/// The signed-in user acting within their active organization. `role` is
+/// the user's role in that organization. `api_key` is set when the request
+/// was authenticated with an API key instead of a JWT, and `session` when it
+/// was authenticated with a JWT.
 pub struct AuthenticatedUser {
     pub role: Role,
     pub id: i32,
+    pub organization_id: i32,
+    pub api_key: Option<i32>,
+    pub session: Option<i32>,
 }
 
 #[rocket::async_trait]
@@ -71,28 +124,70 @@ impl<'r> FromRequest<'r> for AuthenticatedUser {
     type Error = ();
 
     async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
-        if let Some(token) = req.headers().get_one("token") {
-            let config = req.rocket().state::<Config>().unwrap();
-
-            let data = decode::<Claims>(
-                token,
-                &DecodingKey::from_secret(config.secret.as_bytes()),
-                &Validation::new(Algorithm::HS256),
-            );
-
-            let claims = match data {
-                Ok(p) => p.claims,
-                Err(_) => return Outcome::Failure((Status::Unauthorized, ())),
+        let outcome = AuthenticatedUser::authenticate(req).await;
+
+        if let Outcome::Success(user) = &outcome {
+            logging::set_user(req, user.id);
+        }
+
+        outcome
+    }
+}
+
+impl AuthenticatedUser {
+    async fn authenticate(req: &Request<'_>) -> request::Outcome<Self, ()> {
+        if let Some(key) = req.headers().get_one("X-API-Key") {
+            if !req.rocket().state::<Config>().unwrap().api_keys {
+                return Outcome::Failure((Status::Unauthorized, ()));
+            }
+
+            let db = req.rocket().state::<DatabaseConnection>().unwrap();
+
+            let (api_key, membership) = match ApiKey::authenticate(db, key).await {
+                Ok(Some(found)) => found,
+                _ => return Outcome::Failure((Status::Unauthorized, ())),
+            };
+
+            if api_key.read_only && !matches!(req.method(), Method::Get | Method::Head) {
+                return Outcome::Failure((Status::Forbidden, ()));
+            }
+
+            Outcome::Success(AuthenticatedUser {
+                role: Role::from_str(&membership.role),
+                id: membership.user_id,
+                organization_id: membership.organization_id,
+                api_key: Some(api_key.id),
+                session: None,
+            })
+        } else if let Some(token) = req.headers().get_one("token") {
+            let keys = req.rocket().state::<Keys>().unwrap();
+
+            let claims = match keys.decode::<Claims>(token) {
+                Some(c) => c,
+                None => return Outcome::Failure((Status::Unauthorized, ())),
+            };
+
+            let db = req.rocket().state::<DatabaseConnection>().unwrap();
+
+            let session = match Session::authenticate(db, claims.sub, &claims.sid).await {
+                Ok(Some(s)) => s,
+                _ => return Outcome::Failure((Status::Unauthorized, ())),
             };
 
             match Role::from_str(&claims.role) {
                 Role::User => Outcome::Success(AuthenticatedUser {
                     role: Role::User,
                     id: claims.sub,
+                    organization_id: claims.org,
+                    api_key: None,
+                    session: Some(session.id),
                 }),
                 Role::Admin => Outcome::Success(AuthenticatedUser {
                     role: Role::Admin,
                     id: claims.sub,
+                    organization_id: claims.org,
+                    api_key: None,
+                    session: Some(session.id),
                 }),
             }
         } else {
@@ -105,49 +200,330 @@ impl<'r> FromRequest<'r> for AuthenticatedUser {
 pub async fn sign_in(
     db: &State<DatabaseConnection>,
     config: &State<Config>,
+    keys: &State<Keys>,
+    providers: &State<AuthProviders>,
+    lockout: &State<Lockout>,
+    client: ClientInfo,
     req_sign_in: Json<RequestSignIn<'_>>,
 ) -> Result<Json<ResponseSignIn>, ErrorResponder> {
+    if !config.password_sign_in {
+        return Err(password_sign_in_disabled());
+    }
+
+    if let Err(retry_after) = lockout.check(req_sign_in.email, client.ip) {
+        return Err(too_many_requests(retry_after));
+    }
+
     let db = db as &DatabaseConnection;
-    let u: user::Model = match User::find()
-        .filter(user::Column::Email.eq(req_sign_in.email))
-        .one(db)
-        .await?
-    {
-        Some(u) => u,
+
+    let mut verified = None;
+    let mut unavailable = None;
+
+    for provider in providers.iter() {
+        match provider
+            .authenticate(db, req_sign_in.email, req_sign_in.password)
+            .await
+        {
+            Ok(Some(v)) => {
+                verified = Some(v);
+                break;
+            }
+            Ok(None) => {}
+            Err(auth_provider::Error::Database(err)) => return Err(err.into()),
+            // An unreachable directory should not keep the other providers from answering.
+            Err(err) => {
+                log::warn!("{}", err);
+                unavailable = Some(err);
+            }
+        }
+    }
+
+    let u = match verified {
+        Some(Verified::Account(u)) => *u,
+        Some(Verified::Directory(directory_user)) => {
+            match directory_account(db, config, &directory_user).await? {
+                Some(u) => u,
+                None => return Err(no_membership()),
+            }
+        }
         None => {
+            if let Some(err) = unavailable {
+                return Err(error_response(Status::BadGateway, err.to_string()));
+            }
+
+            // Unknown emails count too, so lockouts do not reveal accounts.
+            lockout.fail(req_sign_in.email, client.ip);
             return Err(error_response(
                 Status::Unauthorized,
                 "Invalid credentials".to_string(),
+            ));
+        }
+    };
+
+    lockout.succeed(req_sign_in.email);
+
+    let membership =
+        match OrganizationUser::membership(db, u.id, req_sign_in.organization_uuid).await? {
+            Some(m) => m,
+            None => return Err(no_membership()),
+        };
+
+    Ok(Json(issue(db, keys, &client, &u, membership).await?))
+}
+
+#[post("/2fa", data = "<req_two_factor>")]
+pub async fn two_factor(
+    db: &State<DatabaseConnection>,
+    keys: &State<Keys>,
+    lockout: &State<Lockout>,
+    client: ClientInfo,
+    req_two_factor: Json<RequestTwoFactor<'_>>,
+) -> Result<Json<ResponseSignIn>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let claims = match keys.decode::<ChallengeClaims>(req_two_factor.token) {
+        Some(claims) if claims.challenge => claims,
+        _ => {
+            return Err(error_response(
+                Status::Unauthorized,
+                "Invalid or expired challenge".to_string(),
             ))
         }
     };
 
-    if !verify(req_sign_in.password, &u.password).unwrap() {
+    let u = match User::find_by_id(claims.sub).one(db).await? {
+        Some(u) => u,
+        None => return Err(no_membership()),
+    };
+
+    if let Err(retry_after) = lockout.check(&u.email, client.ip) {
+        return Err(too_many_requests(retry_after));
+    }
+
+    if !check_code(db, &u, req_two_factor.code).await? {
+        lockout.fail(&u.email, client.ip);
         return Err(error_response(
             Status::Unauthorized,
-            "Invalid credentials".to_string(),
+            "Invalid code".to_string(),
         ));
     }
 
+    let membership = match OrganizationUser::find()
+        .filter(organization_user::Column::OrganizationId.eq(claims.org))
+        .filter(organization_user::Column::UserId.eq(u.id))
+        .one(db)
+        .await?
+    {
+        Some(m) => m,
+        None => return Err(no_membership()),
+    };
+
+    let session = Session::start(db, u.id, &client, keys.ttl).await?;
+
+    Ok(Json(finish(db, keys, &u, membership, &session).await?))
+}
+
+#[post("/switch", data = "<req_switch>")]
+pub async fn switch(
+    db: &State<DatabaseConnection>,
+    keys: &State<Keys>,
+    user: AuthenticatedUser,
+    req_switch: Json<RequestSwitch<'_>>,
+) -> Result<Json<ResponseSignIn>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    // API keys are not sessions, so they cannot be traded for a token.
+    let session = match user.session {
+        Some(id) => Session::find_by_id(id).one(db).await?,
+        None => None,
+    };
+
+    let session = match session {
+        Some(s) => s,
+        None => {
+            return Err(error_response(
+                Status::Forbidden,
+                "Only signed-in sessions can switch organizations".to_string(),
+            ))
+        }
+    };
+
+    let membership =
+        match OrganizationUser::membership(db, user.id, Some(req_switch.organization_uuid)).await? {
+            Some(m) => m,
+            None => return Err(no_membership()),
+        };
+
+    // The user already passed 2FA, if enabled, when signing in.
+    let u = User::find_by_id(user.id).one(db).await?.unwrap();
+
+    Session::extend(db, &session, keys.ttl).await?;
+
+    Ok(Json(finish(db, keys, &u, membership, &session).await?))
+}
+
+/// Returned by the password routes when only single sign-on is allowed.
+pub fn password_sign_in_disabled() -> ErrorResponder {
+    error_response(
+        Status::Forbidden,
+        "Password sign-in is disabled".to_string(),
+    )
+}
+
+/// The account for a directory user, created if the directory provisions
+/// users. In the directory's organization, their role follows their groups.
+async fn directory_account(
+    db: &DatabaseConnection,
+    config: &Config,
+    directory_user: &DirectoryUser,
+) -> Result<Option<user::Model>, DbErr> {
+    let organization = match config.ldap_organization.as_str() {
+        "" => None,
+        uuid => {
+            Organization::find()
+                .filter(organization::Column::Uuid.eq(uuid))
+                .one(db)
+                .await?
+        }
+    };
+
+    let existing = User::find()
+        .filter(user::Column::Email.eq(directory_user.email.as_str()))
+        .one(db)
+        .await?;
+
+    let u = match (existing, &organization) {
+        (Some(u), _) => u,
+        (None, Some(_)) => {
+            User::provision(
+                db,
+                &directory_user.email,
+                &directory_user.firstname,
+                &directory_user.lastname,
+                None,
+            )
+            .await?
+        }
+        (None, None) => return Ok(None),
+    };
+
+    if let Some(o) = organization {
+        OrganizationUser::sync(db, o.id, u.id, directory_user.role.to_owned()).await?;
+    }
+
+    Ok(Some(u))
+}
+
+fn no_membership() -> ErrorResponder {
+    error_response(
+        Status::Forbidden,
+        "Not a member of the organization".to_string(),
+    )
+}
+
+/// Signs a user whose password was checked in to the membership's
+/// organization with a new session, or challenges them for a second factor
+/// first.
+pub async fn issue(
+    db: &DatabaseConnection,
+    keys: &Keys,
+    client: &ClientInfo,
+    user: &user::Model,
+    membership: organization_user::Model,
+) -> Result<ResponseSignIn, DbErr> {
+    if user.totp_enabled_at.is_none() {
+        let session = Session::start(db, user.id, client, keys.ttl).await?;
+        return finish(db, keys, user, membership, &session).await;
+    }
+
+    let claims = ChallengeClaims {
+        sub: user.id,
+        org: membership.organization_id,
+        challenge: true,
+        exp: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 5 * 60,
+    };
+
+    Ok(ResponseSignIn {
+        status: "2fa_required".to_string(),
+        token: keys.encode(&claims),
+    })
+}
+
+/// Issues the token for a fully authenticated user. Admins of organizations
+/// that require 2FA act as users until they enable it.
+async fn finish(
+    db: &DatabaseConnection,
+    keys: &Keys,
+    user: &user::Model,
+    mut membership: organization_user::Model,
+    session: &session::Model,
+) -> Result<ResponseSignIn, DbErr> {
+    let mut status = "success";
+
+    let role = acting_role(db, user, &membership).await?;
+    if role != Role::from_str(&membership.role) {
+        membership.role = role.as_str().to_string();
+        status = "2fa_setup_required";
+    }
+
+    Ok(ResponseSignIn {
+        status: status.to_string(),
+        token: make_token(keys, &membership, &session.uuid),
+    })
+}
+
+/// The role a user acts with in a membership's organization. Admins of
+/// organizations that require two-factor authentication act as users until
+/// they have set it up.
+pub async fn acting_role(
+    db: &DatabaseConnection,
+    user: &user::Model,
+    membership: &organization_user::Model,
+) -> Result<Role, DbErr> {
+    let role = Role::from_str(&membership.role);
+
+    if role == Role::Admin && user.totp_enabled_at.is_none() {
+        let organization = Organization::find_by_id(membership.organization_id)
+            .one(db)
+            .await?
+            .unwrap();
+
+        if organization.require_admin_two_factor {
+            return Ok(Role::User);
+        }
+    }
+
+    Ok(role)
+}
+
+/// Issues a token for acting in the membership's organization with its role,
+/// valid while the session is.
+pub fn make_token(
+    keys: &Keys,
+    membership: &organization_user::Model,
+    session_uuid: &str,
+) -> String {
     let claims = Claims {
-        role: u.role,
-        sub: u.id,
+        role: membership.role.to_owned(),
+        sub: membership.user_id,
+        org: membership.organization_id,
+        sid: session_uuid.to_string(),
         exp: SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap()
             .as_secs()
-            + 4 * 60 * 60,
+            + keys.ttl,
     };
 
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(config.secret.as_bytes()),
-    )
-    .unwrap();
+    keys.encode(&claims)
+}
 
-    Ok(Json(ResponseSignIn {
-        status: "success".to_string(),
-        token,
-    }))
+/// The public keys tokens are verified with, for other services to check them.
+#[get("/jwks.json")]
+pub fn jwks(keys: &State<Keys>) -> Json<Value> {
+    Json(keys.jwks())
 }
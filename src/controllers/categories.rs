@@ -15,9 +15,13 @@ use sea_orm::{prelude::DateTimeUtc, *};
 use uuid::Uuid;
 
 use super::{
-    auth::AuthenticatedUser, not_found, success, users::ResponseUser, ErrorResponder, ResponseList, Response,
+    auth::AuthenticatedUser, groups::ResponseGroup, not_found, precondition_failed, success,
+    users::ResponseUser, ErrorResponder, ResponseList, Response,
+};
+use crate::{
+    entities::{category, prelude::*},
+    etag::{etag, Preconditions, Tagged},
 };
-use crate::entities::{category, prelude::*};
 
 #[derive(Deserialize)]
 #[serde(crate = "rocket::serde")]
@@ -25,6 +29,7 @@ pub struct RequestCategory<'r> {
     name: &'r str,
     description: Option<String>,
     parent_uuid: Option<String>,
+    group_uuid: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -36,6 +41,7 @@ pub struct ResponseCategory {
     parent: Option<Box<ResponseCategory>>,
     item_count: Option<usize>,
     user: Option<ResponseUser>,
+    group: Option<ResponseGroup>,
 }
 
 impl From<&category::Model> for ResponseCategory {
@@ -47,6 +53,7 @@ impl From<&category::Model> for ResponseCategory {
             parent: None,
             item_count: None,
             user: None,
+            group: None,
         }
     }
 }
@@ -54,19 +61,48 @@ impl From<&category::Model> for ResponseCategory {
 impl Category {
     pub async fn from_uuid(
         db: &DatabaseConnection,
+        organization_id: i32,
         uuid: &str,
     ) -> Result<Option<category::Model>, DbErr> {
         Category::find()
+            .filter(category::Column::OrganizationId.eq(organization_id))
             .filter(category::Column::Uuid.eq(uuid))
             .one(db)
             .await
     }
 
+    /// Ids of the category and all of its descendants.
+    pub async fn subtree_ids(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        id: i32,
+    ) -> Result<Vec<i32>, DbErr> {
+        let categories = Category::find()
+            .filter(category::Column::OrganizationId.eq(organization_id))
+            .all(db)
+            .await?;
+
+        let mut ids = vec![id];
+        let mut i = 0;
+        while i < ids.len() {
+            for c in &categories {
+                if c.parent_id == Some(ids[i]) && !ids.contains(&c.id) {
+                    ids.push(c.id);
+                }
+            }
+            i += 1;
+        }
+
+        Ok(ids)
+    }
+
     pub async fn latest(
         db: &DatabaseConnection,
+        organization_id: i32,
         count: u64,
     ) -> Result<Vec<ResponseCategory>, DbErr> {
         Ok(Category::find()
+            .filter(category::Column::OrganizationId.eq(organization_id))
             .order_by_desc(category::Column::UpdatedAt)
             .limit(count)
             .all(db)
@@ -80,11 +116,12 @@ impl Category {
 #[get("/")]
 pub async fn index(
     db: &State<DatabaseConnection>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
 ) -> Result<Json<ResponseList<ResponseCategory>>, ErrorResponder> {
     let db = db as &DatabaseConnection;
 
     let categories = Category::find()
+        .filter(category::Column::OrganizationId.eq(user.organization_id))
         .order_by_desc(category::Column::UpdatedAt)
         .all(db)
         .await?
@@ -108,17 +145,23 @@ pub async fn store(
 
     let mut parent: Option<i32> = None;
     if let Some(parent_uuid) = req_category.parent_uuid.to_owned() {
-        if let Some(p) = Category::from_uuid(db, parent_uuid.as_str()).await? {
+        if let Some(p) =
+            Category::from_uuid(db, user.organization_id, parent_uuid.as_str()).await?
+        {
             parent = Some(p.id);
         }
     }
 
+    let group = Group::id_from_uuid(db, user.organization_id, &req_category.group_uuid).await?;
+
     Category::insert(category::ActiveModel {
         uuid: Set(Uuid::new_v4().to_string()),
+        organization_id: Set(user.organization_id),
         user_id: Set(user.id),
         name: Set(req_category.name.to_owned()),
         description: Set(req_category.description.to_owned()),
         parent_id: Set(parent),
+        group_id: Set(group),
         ..Default::default()
     })
     .exec(db)
@@ -130,16 +173,22 @@ pub async fn store(
 #[get("/<uuid>")]
 pub async fn show(
     db: &State<DatabaseConnection>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
+    preconditions: Preconditions,
     uuid: &str,
-) -> Result<Json<ResponseCategory>, ErrorResponder> {
+) -> Result<Tagged<Json<ResponseCategory>>, ErrorResponder> {
     let db = db as &DatabaseConnection;
 
-    let category = match Category::from_uuid(db, uuid).await? {
+    let category = match Category::from_uuid(db, user.organization_id, uuid).await? {
         Some(c) => c,
         None => return Err(not_found()),
     };
 
+    let etag = etag(category.version);
+    if preconditions.not_modified(&etag) {
+        return Ok(Tagged::not_modified(etag));
+    }
+
     let mut response = ResponseCategory::from(&category);
 
     response.user = Some(ResponseUser::from(
@@ -148,43 +197,65 @@ pub async fn show(
 
     response.item_count = Some(category.find_related(Item).count(db).await?);
 
+    response.group = category
+        .find_related(Group)
+        .one(db)
+        .await?
+        .as_ref()
+        .map(ResponseGroup::from);
+
     if let Some(parent_id) = category.parent_id {
         response.parent = Some(Box::new(ResponseCategory::from(
             &Category::find_by_id(parent_id).one(db).await?.unwrap(),
         )));
     }
 
-    Ok(Json(response))
+    Ok(Tagged::new(Json(response), etag))
 }
 
 #[put("/<uuid>", data = "<req_category>")]
 pub async fn update(
     db: &State<DatabaseConnection>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
+    preconditions: Preconditions,
     uuid: &str,
     req_category: Json<RequestCategory<'_>>,
 ) -> Response {
     let db = db as &DatabaseConnection;
 
-    let mut category: category::ActiveModel = match Category::from_uuid(db, uuid).await? {
-        Some(c) => c.into(),
+    let existing = match Category::from_uuid(db, user.organization_id, uuid).await? {
+        Some(c) => c,
         None => return Err(not_found()),
     };
 
+    if !preconditions.allows(&etag(existing.version)) {
+        return Err(precondition_failed());
+    }
+
+    let version = existing.version;
+    let mut category: category::ActiveModel = existing.into();
+
     let mut parent: Option<i32> = None;
     if let Some(parent_uuid) = req_category.parent_uuid.to_owned() {
-        if let Some(p) = Category::from_uuid(db, parent_uuid.as_str()).await? {
+        if let Some(p) =
+            Category::from_uuid(db, user.organization_id, parent_uuid.as_str()).await?
+        {
             parent = Some(p.id);
         }
     }
 
+    let group = Group::id_from_uuid(db, user.organization_id, &req_category.group_uuid).await?;
+
     category.name = Set(req_category.name.to_owned());
     category.description = Set(req_category.description.to_owned());
     category.parent_id = Set(parent);
+    category.group_id = Set(group);
 
     category.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
 
-    category.update(db).await?;
+    preconditions
+        .update(category, category::Column::Version, version, db)
+        .await?;
 
     success(Status::Ok)
 }
@@ -192,17 +263,30 @@ pub async fn update(
 #[delete("/<uuid>")]
 pub async fn delete(
     db: &State<DatabaseConnection>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
+    preconditions: Preconditions,
     uuid: &str,
 ) -> Response {
     let db = db as &DatabaseConnection;
 
-    let category = match Category::from_uuid(db, uuid).await? {
+    let category = match Category::from_uuid(db, user.organization_id, uuid).await? {
         Some(c) => c,
         None => return Err(not_found()),
     };
 
-    category.delete(db).await?;
+    if !preconditions.allows(&etag(category.version)) {
+        return Err(precondition_failed());
+    }
+
+    let version = category.version;
+    preconditions
+        .delete(
+            category.into_active_model(),
+            category::Column::Version,
+            version,
+            db,
+        )
+        .await?;
 
     success(Status::Ok)
 }
@@ -5,23 +5,37 @@
  * @link   https://afaan.dev
  * @link   https://github.com/AfaanBilal/iron-guard-server
  */
+use std::collections::BTreeMap;
+
 use rocket::{
     serde::{json::Json, Serialize},
     *,
 };
-use sea_orm::*;
+use sea_orm::{prelude::Decimal, *};
 
 use super::{
     auth::{AuthenticatedUser, Role},
     categories::ResponseCategory,
     items::ResponseItem,
+    lots::ResponseLot,
     users::ResponseUser,
     ErrorResponder,
 };
-use crate::entities::prelude::*;
+use crate::{
+    entities::{category, item, prelude::*},
+    Config,
+};
 
 const LATEST_COUNT: u64 = 5;
 
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseUnitQuantity {
+    unit: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    quantity: Decimal,
+}
+
 #[derive(Serialize)]
 #[serde(crate = "rocket::serde")]
 pub struct ResponseDashboard {
@@ -29,14 +43,20 @@ pub struct ResponseDashboard {
     count_categories: usize,
     count_items: usize,
 
+    /// Total quantity on hand, one entry per unit of measure.
+    quantities: Vec<ResponseUnitQuantity>,
+
     latest_users: Vec<ResponseUser>,
     latest_categories: Vec<ResponseCategory>,
     latest_items: Vec<ResponseItem>,
+
+    expiring_lots: Vec<ResponseLot>,
 }
 
 #[get("/")]
 pub async fn index(
     db: &State<DatabaseConnection>,
+    config: &State<Config>,
     user: AuthenticatedUser,
 ) -> Result<Json<ResponseDashboard>, ErrorResponder> {
     let db = db as &DatabaseConnection;
@@ -45,17 +65,33 @@ pub async fn index(
     let mut user_count = 0;
 
     if user.role == Role::Admin {
-        users = User::latest(db, LATEST_COUNT).await?;
-        user_count = User::find().count(db).await?;
+        users = User::latest(db, user.organization_id, LATEST_COUNT).await?;
+        user_count = User::members(user.organization_id).count(db).await?;
+    }
+
+    let mut quantities: BTreeMap<String, Decimal> = BTreeMap::new();
+    let items = Item::find().filter(item::Column::OrganizationId.eq(user.organization_id));
+    for item in items.clone().all(db).await? {
+        *quantities.entry(item.unit).or_default() += item.quantity;
     }
 
     Ok(Json(ResponseDashboard {
         count_users: user_count,
-        count_categories: Category::find().count(db).await?,
-        count_items: Item::find().count(db).await?,
+        count_categories: Category::find()
+            .filter(category::Column::OrganizationId.eq(user.organization_id))
+            .count(db)
+            .await?,
+        count_items: items.count(db).await?,
+
+        quantities: quantities
+            .into_iter()
+            .map(|(unit, quantity)| ResponseUnitQuantity { unit, quantity })
+            .collect(),
 
         latest_users: users,
-        latest_categories: Category::latest(db, LATEST_COUNT).await?,
-        latest_items: Item::latest(db, LATEST_COUNT).await?,
+        latest_categories: Category::latest(db, user.organization_id, LATEST_COUNT).await?,
+        latest_items: Item::latest(db, user.organization_id, LATEST_COUNT).await?,
+
+        expiring_lots: Lot::expiring(db, user.organization_id, config.expiry_warning_days).await?,
     }))
 }
@@ -0,0 +1,334 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{collections::HashMap, time::SystemTime};
+
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{prelude::DateTimeUtc, *};
+use uuid::Uuid;
+
+use super::{
+    admin_required,
+    auth::{AuthenticatedUser, Role},
+    error_response, not_found, success,
+    users::ResponseUser,
+    ErrorResponder, Response, ResponseList,
+};
+use crate::entities::{group, group_user, prelude::*};
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestGroup<'r> {
+    name: &'r str,
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestGroupMember<'r> {
+    user_uuid: &'r str,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseGroup {
+    uuid: String,
+    name: String,
+    description: Option<String>,
+    members: Option<Vec<ResponseUser>>,
+}
+
+impl From<&group::Model> for ResponseGroup {
+    fn from(group: &group::Model) -> ResponseGroup {
+        ResponseGroup {
+            uuid: group.uuid.to_owned(),
+            name: group.name.to_owned(),
+            description: group.description.to_owned(),
+            members: None,
+        }
+    }
+}
+
+impl Group {
+    pub async fn from_uuid(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        uuid: &str,
+    ) -> Result<Option<group::Model>, DbErr> {
+        Group::find()
+            .filter(group::Column::OrganizationId.eq(organization_id))
+            .filter(group::Column::Uuid.eq(uuid))
+            .one(db)
+            .await
+    }
+
+    /// Resolves an optional group uuid from a request, ignoring unknown groups.
+    pub async fn id_from_uuid(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        uuid: &Option<String>,
+    ) -> Result<Option<i32>, DbErr> {
+        Ok(match uuid {
+            Some(uuid) => Group::from_uuid(db, organization_id, uuid)
+                .await?
+                .map(|g| g.id),
+            None => None,
+        })
+    }
+
+    /// The groups a user belongs to in the organization.
+    pub async fn for_user(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        user_id: i32,
+    ) -> Result<Vec<ResponseGroup>, DbErr> {
+        Ok(Group::find()
+            .inner_join(GroupUser)
+            .filter(group::Column::OrganizationId.eq(organization_id))
+            .filter(group_user::Column::UserId.eq(user_id))
+            .order_by_asc(group::Column::Name)
+            .all(db)
+            .await?
+            .iter()
+            .map(ResponseGroup::from)
+            .collect::<Vec<_>>())
+    }
+
+    /// The groups of every member of the organization, keyed by user id.
+    pub async fn by_user(
+        db: &DatabaseConnection,
+        organization_id: i32,
+    ) -> Result<HashMap<i32, Vec<ResponseGroup>>, DbErr> {
+        let mut groups: HashMap<i32, Vec<ResponseGroup>> = HashMap::new();
+
+        for (membership, group) in GroupUser::find()
+            .find_also_related(Group)
+            .filter(group::Column::OrganizationId.eq(organization_id))
+            .order_by_asc(group::Column::Name)
+            .all(db)
+            .await?
+        {
+            if let Some(g) = group {
+                groups
+                    .entry(membership.user_id)
+                    .or_default()
+                    .push(ResponseGroup::from(&g));
+            }
+        }
+
+        Ok(groups)
+    }
+}
+
+#[get("/")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<Json<ResponseList<ResponseGroup>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let groups = Group::find()
+        .filter(group::Column::OrganizationId.eq(user.organization_id))
+        .order_by_asc(group::Column::Name)
+        .all(db)
+        .await?
+        .iter()
+        .map(ResponseGroup::from)
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: groups.len(),
+        results: groups,
+    }))
+}
+
+#[post("/", data = "<req_group>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    req_group: Json<RequestGroup<'_>>,
+) -> Response {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    Group::insert(group::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        organization_id: Set(user.organization_id),
+        name: Set(req_group.name.to_owned()),
+        description: Set(req_group.description.to_owned()),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    success(Status::Created)
+}
+
+#[get("/<uuid>")]
+pub async fn show(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Result<Json<ResponseGroup>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let group = match Group::from_uuid(db, user.organization_id, uuid).await? {
+        Some(g) => g,
+        None => return Err(not_found()),
+    };
+
+    let mut response = ResponseGroup::from(&group);
+
+    response.members = Some(
+        group
+            .find_related(User)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(ResponseUser::from)
+            .collect::<Vec<_>>(),
+    );
+
+    Ok(Json(response))
+}
+
+#[put("/<uuid>", data = "<req_group>")]
+pub async fn update(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_group: Json<RequestGroup<'_>>,
+) -> Response {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let mut group: group::ActiveModel =
+        match Group::from_uuid(db, user.organization_id, uuid).await? {
+            Some(g) => g.into(),
+            None => return Err(not_found()),
+        };
+
+    group.name = Set(req_group.name.to_owned());
+    group.description = Set(req_group.description.to_owned());
+
+    group.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+
+    group.update(db).await?;
+
+    success(Status::Ok)
+}
+
+#[delete("/<uuid>")]
+pub async fn delete(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Response {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let group = match Group::from_uuid(db, user.organization_id, uuid).await? {
+        Some(g) => g,
+        None => return Err(not_found()),
+    };
+
+    group.delete(db).await?;
+
+    success(Status::Ok)
+}
+
+#[put("/<uuid>/members", data = "<req_member>")]
+pub async fn add_member(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_member: Json<RequestGroupMember<'_>>,
+) -> Response {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let group = match Group::from_uuid(db, user.organization_id, uuid).await? {
+        Some(g) => g,
+        None => return Err(not_found()),
+    };
+
+    let member = match User::from_uuid(db, user.organization_id, req_member.user_uuid).await? {
+        Some((u, _)) => u,
+        None => {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                format!("Unknown user {}", req_member.user_uuid),
+            ))
+        }
+    };
+
+    let existing = GroupUser::find()
+        .filter(group_user::Column::GroupId.eq(group.id))
+        .filter(group_user::Column::UserId.eq(member.id))
+        .one(db)
+        .await?;
+
+    if existing.is_none() {
+        GroupUser::insert(group_user::ActiveModel {
+            group_id: Set(group.id),
+            user_id: Set(member.id),
+            ..Default::default()
+        })
+        .exec(db)
+        .await?;
+    }
+
+    success(Status::Ok)
+}
+
+#[delete("/<uuid>/members/<user_uuid>")]
+pub async fn remove_member(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    user_uuid: &str,
+) -> Response {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let group = match Group::from_uuid(db, user.organization_id, uuid).await? {
+        Some(g) => g,
+        None => return Err(not_found()),
+    };
+
+    let member = match User::from_uuid(db, user.organization_id, user_uuid).await? {
+        Some((u, _)) => u,
+        None => return Err(not_found()),
+    };
+
+    GroupUser::delete_many()
+        .filter(group_user::Column::GroupId.eq(group.id))
+        .filter(group_user::Column::UserId.eq(member.id))
+        .exec(db)
+        .await?;
+
+    success(Status::Ok)
+}
@@ -0,0 +1,137 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::convert::Infallible;
+
+use rocket::{
+    http::Status,
+    request::{self, FromRequest, Request},
+    serde::json::Json,
+    *,
+};
+use sea_orm::{prelude::Decimal, sea_query::Expr, *};
+use serde_json::{json, Value};
+
+use super::{error_response, ErrorResponder};
+use crate::{
+    entities::{item, prelude::*},
+    metrics::{Inventory, Metrics},
+    migrator::Migrator,
+    Config,
+};
+
+/// The token of an `Authorization: Bearer` header.
+pub struct BearerToken(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for BearerToken {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(BearerToken(
+            req.headers()
+                .get_one("Authorization")
+                .and_then(|h| h.strip_prefix("Bearer "))
+                .map(|t| t.trim().to_string()),
+        ))
+    }
+}
+
+#[derive(FromQueryResult)]
+struct Totals {
+    items: i64,
+    quantity: Option<Decimal>,
+}
+
+/// Liveness: the server is up and answering.
+#[get("/healthz")]
+pub fn healthz() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness: the database answers and its schema matches this version.
+#[get("/readyz")]
+pub async fn readyz(db: &State<DatabaseConnection>) -> (Status, Json<Value>) {
+    let db = db as &DatabaseConnection;
+
+    let database = db
+        .execute(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT 1".to_owned(),
+        ))
+        .await
+        .map(|_| ())
+        .map_err(|err| err.to_string());
+
+    let migrations = match database {
+        Ok(_) => Migrator::check(db).await,
+        Err(_) => Err("unknown".to_string()),
+    };
+
+    let ready = database.is_ok() && migrations.is_ok();
+
+    (
+        if ready {
+            Status::Ok
+        } else {
+            Status::ServiceUnavailable
+        },
+        Json(json!({
+            "status": if ready { "ready" } else { "unavailable" },
+            "database": database.err().unwrap_or_else(|| "ok".to_string()),
+            "migrations": migrations.err().unwrap_or_else(|| "ok".to_string()),
+        })),
+    )
+}
+
+/// Prometheus metrics. Requires `IRON_GUARD_METRICS_TOKEN` as a bearer token
+/// when it is set.
+#[get("/metrics")]
+pub async fn metrics(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    metrics: &State<Metrics>,
+    token: BearerToken,
+) -> Result<String, ErrorResponder> {
+    if !config.metrics_token.is_empty() && token.0.as_ref() != Some(&config.metrics_token) {
+        return Err(error_response(
+            Status::Unauthorized,
+            "Invalid metrics token".to_string(),
+        ));
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let totals = Item::find()
+        .select_only()
+        .column_as(Expr::col(item::Column::Id).count(), "items")
+        .column_as(Expr::col(item::Column::Quantity).sum(), "quantity")
+        .into_model::<Totals>()
+        .one(db)
+        .await?;
+
+    let low_stock = Item::find()
+        .filter(item::Column::Mode.eq("bulk"))
+        .filter(item::Column::Quantity.lte(Decimal::from(config.low_stock_threshold)))
+        .count(db)
+        .await?;
+
+    let inventory = match totals {
+        Some(t) => Inventory {
+            items: t.items as u64,
+            quantity: t.quantity.unwrap_or_default().normalize().to_string(),
+            low_stock: low_stock as u64,
+        },
+        None => Inventory {
+            items: 0,
+            quantity: "0".to_string(),
+            low_stock: low_stock as u64,
+        },
+    };
+
+    Ok(metrics.render(&inventory))
+}
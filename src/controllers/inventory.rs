@@ -35,7 +35,7 @@ pub async fn index(
 #[get("/<uuid>")]
 pub async fn by_category(
     db: &State<DatabaseConnection>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
     uuid: Option<String>,
 ) -> Result<Json<ResponseInventory>, ErrorResponder> {
     let db = db as &DatabaseConnection;
@@ -49,7 +49,7 @@ pub async fn by_category(
     let mut f1 = category::Column::ParentId.is_null();
     let mut f2 = item::Column::CategoryId.is_null();
     if let Some(uuid) = uuid {
-        let c = Category::from_uuid(db, uuid.as_str()).await?;
+        let c = Category::from_uuid(db, user.organization_id, uuid.as_str()).await?;
 
         if let Some(c) = c {
             response.category = Some(ResponseCategory::from(&c));
@@ -59,6 +59,7 @@ pub async fn by_category(
     }
 
     response.categories = Category::find()
+        .filter(category::Column::OrganizationId.eq(user.organization_id))
         .filter(f1)
         .order_by_desc(category::Column::UpdatedAt)
         .all(db)
@@ -68,6 +69,7 @@ pub async fn by_category(
         .collect::<Vec<_>>();
 
     response.items = Item::find()
+        .filter(item::Column::OrganizationId.eq(user.organization_id))
         .filter(f2)
         .order_by_desc(item::Column::UpdatedAt)
         .all(db)
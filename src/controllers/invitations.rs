@@ -0,0 +1,321 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::{Duration, SystemTime};
+
+use bcrypt::{hash, verify, DEFAULT_COST};
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{prelude::DateTimeUtc, sea_query::Expr, *};
+use uuid::Uuid;
+
+use super::{
+    admin_required,
+    auth::{issue, AuthenticatedUser, ResponseSignIn, Role},
+    error_response, not_found,
+    passwords::{hash_token, invalid_token, random_token},
+    sessions::ClientInfo,
+    success, ErrorResponder, Response, ResponseList,
+};
+use crate::{
+    entities::{invitation, organization_user, prelude::*, user},
+    jwt::Keys,
+    mailer::{MailerBackend, Message},
+    Config,
+};
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestInvitation<'r> {
+    email: &'r str,
+    role: &'r str,
+}
+
+/// The name and password are only used when the invitee has no account yet.
+/// Existing users confirm the invitation with their current password.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestAccept<'r> {
+    token: &'r str,
+    firstname: Option<&'r str>,
+    lastname: Option<&'r str>,
+    password: &'r str,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseInvitation {
+    uuid: String,
+    email: String,
+    role: String,
+    expires_at: String,
+}
+
+impl From<invitation::Model> for ResponseInvitation {
+    fn from(invitation: invitation::Model) -> ResponseInvitation {
+        ResponseInvitation {
+            uuid: invitation.uuid,
+            email: invitation.email,
+            role: invitation.role,
+            expires_at: invitation.expires_at.to_rfc3339(),
+        }
+    }
+}
+
+impl Invitation {
+    /// Invitations to the organization that have not been accepted yet.
+    fn pending(organization_id: i32) -> Select<invitation::Entity> {
+        Invitation::find()
+            .filter(invitation::Column::OrganizationId.eq(organization_id))
+            .filter(invitation::Column::AcceptedAt.is_null())
+    }
+}
+
+#[get("/")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<Json<ResponseList<ResponseInvitation>>, ErrorResponder> {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let invitations = Invitation::pending(user.organization_id)
+        .order_by_desc(invitation::Column::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(ResponseInvitation::from)
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: invitations.len(),
+        results: invitations,
+    }))
+}
+
+#[post("/", data = "<req_invitation>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    mailer: &State<MailerBackend>,
+    config: &State<Config>,
+    user: AuthenticatedUser,
+    req_invitation: Json<RequestInvitation<'_>>,
+) -> Response {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let email = req_invitation.email.trim();
+    if email.is_empty() {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            "Email is required".to_string(),
+        ));
+    }
+
+    let member = User::members(user.organization_id)
+        .filter(user::Column::Email.eq(email))
+        .one(db)
+        .await?;
+
+    if member.is_some() {
+        return Err(error_response(
+            Status::Conflict,
+            format!("{} is already a member", email),
+        ));
+    }
+
+    // A new invitation replaces any earlier one for the same email.
+    Invitation::delete_many()
+        .filter(invitation::Column::OrganizationId.eq(user.organization_id))
+        .filter(invitation::Column::Email.eq(email))
+        .filter(invitation::Column::AcceptedAt.is_null())
+        .exec(db)
+        .await?;
+
+    let token = random_token();
+    let now = SystemTime::now();
+
+    Invitation::insert(invitation::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        organization_id: Set(user.organization_id),
+        user_id: Set(user.id),
+        email: Set(email.to_string()),
+        role: Set(Role::from_str(req_invitation.role).as_str().to_string()),
+        token_hash: Set(hash_token(&token)),
+        expires_at: Set(DateTimeUtc::from(
+            now + Duration::from_secs(config.invitation_ttl * 60 * 60),
+        )),
+        created_at: Set(DateTimeUtc::from(now)),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    let inviter = User::find_by_id(user.id).one(db).await?.unwrap();
+    let organization = Organization::find_by_id(user.organization_id)
+        .one(db)
+        .await?
+        .unwrap();
+
+    mailer
+        .send(&Message {
+            to: email.to_string(),
+            subject: format!("Join {} on Iron Guard", organization.name),
+            body: format!(
+                "{} {} invited you to join {} on Iron Guard.\r\n\r\n\
+                 Accept the invitation within {} hours:\r\n\
+                 {}/accept-invite?token={}",
+                inviter.firstname,
+                inviter.lastname,
+                organization.name,
+                config.invitation_ttl,
+                config.app_url,
+                token
+            ),
+        })
+        .await?;
+
+    success(Status::Created)
+}
+
+#[delete("/<uuid>")]
+pub async fn delete(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Response {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let invitation = match Invitation::pending(user.organization_id)
+        .filter(invitation::Column::Uuid.eq(uuid))
+        .one(db)
+        .await?
+    {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    invitation.delete(db).await?;
+
+    success(Status::Ok)
+}
+
+/// Joins the invitation's organization, creating the account if needed, and
+/// signs the user in there.
+#[post("/accept-invite", data = "<req_accept>")]
+pub async fn accept(
+    db: &State<DatabaseConnection>,
+    keys: &State<Keys>,
+    client: ClientInfo,
+    req_accept: Json<RequestAccept<'_>>,
+) -> Result<Json<ResponseSignIn>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let now = DateTimeUtc::from(SystemTime::now());
+
+    let invitation = match Invitation::find()
+        .filter(invitation::Column::TokenHash.eq(hash_token(req_accept.token)))
+        .filter(invitation::Column::AcceptedAt.is_null())
+        .filter(invitation::Column::ExpiresAt.gt(now))
+        .one(db)
+        .await?
+    {
+        Some(i) => i,
+        None => return Err(invalid_token()),
+    };
+
+    let existing = User::find()
+        .filter(user::Column::Email.eq(invitation.email.as_str()))
+        .one(db)
+        .await?;
+
+    if let Some(u) = &existing {
+        if !verify(req_accept.password, &u.password).unwrap() {
+            return Err(error_response(
+                Status::Unauthorized,
+                "Invalid credentials".to_string(),
+            ));
+        }
+    } else if req_accept.password.is_empty()
+        || req_accept.firstname.unwrap_or_default().is_empty()
+        || req_accept.lastname.unwrap_or_default().is_empty()
+    {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            "Name and password are required".to_string(),
+        ));
+    }
+
+    let txn = db.begin().await?;
+
+    let claimed = Invitation::update_many()
+        .col_expr(invitation::Column::AcceptedAt, Expr::value(now))
+        .filter(invitation::Column::Id.eq(invitation.id))
+        .filter(invitation::Column::AcceptedAt.is_null())
+        .exec(&txn)
+        .await?;
+
+    if claimed.rows_affected == 0 {
+        return Err(invalid_token());
+    }
+
+    let user_id = match existing {
+        Some(u) => u.id,
+        None => {
+            User::insert(user::ActiveModel {
+                uuid: Set(Uuid::new_v4().to_string()),
+                role: Set(invitation.role.to_owned()),
+                firstname: Set(req_accept.firstname.unwrap_or_default().to_string()),
+                lastname: Set(req_accept.lastname.unwrap_or_default().to_string()),
+                email: Set(invitation.email.to_owned()),
+                password: Set(hash(req_accept.password, DEFAULT_COST).unwrap()),
+                ..Default::default()
+            })
+            .exec(&txn)
+            .await?
+            .last_insert_id
+        }
+    };
+
+    let membership = match OrganizationUser::find()
+        .filter(organization_user::Column::OrganizationId.eq(invitation.organization_id))
+        .filter(organization_user::Column::UserId.eq(user_id))
+        .one(&txn)
+        .await?
+    {
+        Some(m) => m,
+        None => {
+            organization_user::ActiveModel {
+                organization_id: Set(invitation.organization_id),
+                user_id: Set(user_id),
+                role: Set(invitation.role.to_owned()),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?
+        }
+    };
+
+    txn.commit().await?;
+
+    let u = User::find_by_id(user_id).one(db).await?.unwrap();
+
+    Ok(Json(issue(db, keys, &client, &u, membership).await?))
+}
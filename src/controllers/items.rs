@@ -11,22 +11,66 @@ use rocket::{
     serde::{json::Json, Deserialize, Serialize},
     *, http::Status,
 };
-use sea_orm::{prelude::DateTimeUtc, *};
+use sea_orm::{
+    prelude::{DateTimeUtc, Decimal},
+    *,
+};
 use uuid::Uuid;
 
 use super::{
-    auth::AuthenticatedUser, categories::ResponseCategory, not_found, success, users::ResponseUser,
-    ErrorResponder, ResponseList, Response,
+    attachments::ResponseAttachment, auth::AuthenticatedUser, categories::ResponseCategory,
+    error_response, groups::ResponseGroup, locations::ResponseLocation, not_found,
+    precondition_failed, success, users::ResponseUser, ErrorResponder, ResponseList, Response,
+};
+use crate::{
+    entities::{attachment, item, prelude::*, purchase_order_line},
+    etag::{etag, Preconditions, Tagged},
+    storage::StorageBackend,
+    units,
 };
-use crate::entities::{item, prelude::*};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemMode {
+    Bulk,
+    Serialized,
+}
+
+impl ItemMode {
+    pub fn from_str(mode: &str) -> ItemMode {
+        match mode {
+            "serialized" => ItemMode::Serialized,
+            _ => ItemMode::Bulk,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemMode::Bulk => "bulk",
+            ItemMode::Serialized => "serialized",
+        }
+    }
+}
 
 #[derive(Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct RequestItem<'r> {
     category_uuid: Option<String>,
+    group_uuid: Option<String>,
     name: &'r str,
     description: Option<String>,
-    quantity: u32,
+    quantity: Decimal,
+    unit: Option<String>,
+    unit_cost: Option<Decimal>,
+    currency: Option<String>,
+    mode: Option<String>,
+    location_uuid: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestCheckOut {
+    quantity: Decimal,
+    unit: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -35,9 +79,17 @@ pub struct ResponseItem {
     pub uuid: String,
     pub category: Option<ResponseCategory>,
     pub user: Option<ResponseUser>,
+    pub group: Option<ResponseGroup>,
     pub name: String,
     pub description: Option<String>,
-    pub quantity: u32,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub quantity: Decimal,
+    pub unit: String,
+    pub unit_cost: Option<Decimal>,
+    pub currency: Option<String>,
+    pub mode: String,
+    pub location: Option<ResponseLocation>,
+    pub attachments: Option<Vec<ResponseAttachment>>,
 }
 
 impl From<&item::Model> for ResponseItem {
@@ -46,9 +98,16 @@ impl From<&item::Model> for ResponseItem {
             uuid: item.uuid.to_owned(),
             category: None,
             user: None,
+            group: None,
             name: item.name.to_owned(),
             description: item.description.to_owned(),
             quantity: item.quantity,
+            unit: item.unit.to_owned(),
+            unit_cost: item.unit_cost,
+            currency: item.currency.to_owned(),
+            mode: item.mode.to_owned(),
+            location: None,
+            attachments: None,
         }
     }
 }
@@ -56,16 +115,56 @@ impl From<&item::Model> for ResponseItem {
 impl Item {
     pub async fn from_uuid(
         db: &DatabaseConnection,
+        organization_id: i32,
         uuid: &str,
     ) -> Result<Option<item::Model>, DbErr> {
         Item::find()
+            .filter(item::Column::OrganizationId.eq(organization_id))
             .filter(item::Column::Uuid.eq(uuid))
             .one(db)
             .await
     }
 
-    pub async fn latest(db: &DatabaseConnection, count: u64) -> Result<Vec<ResponseItem>, DbErr> {
+    /// Resolves and validates the unit and quantity of a stored item.
+    /// Serialized items are always counted in pieces.
+    pub fn validate_quantity(
+        mode: ItemMode,
+        unit: Option<&str>,
+        quantity: Decimal,
+    ) -> Result<String, ErrorResponder> {
+        let unit = unit.unwrap_or(units::DEFAULT_UNIT);
+
+        if units::find(unit).is_none() {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                format!("Unknown unit {}", unit),
+            ));
+        }
+
+        if mode == ItemMode::Serialized && unit != units::DEFAULT_UNIT {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                format!("Serialized items are counted in {}", units::DEFAULT_UNIT),
+            ));
+        }
+
+        if !units::is_valid_quantity(quantity, unit) {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                format!("{} is not a valid quantity of {}", quantity, unit),
+            ));
+        }
+
+        Ok(unit.to_string())
+    }
+
+    pub async fn latest(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        count: u64,
+    ) -> Result<Vec<ResponseItem>, DbErr> {
         Ok(Item::find()
+            .filter(item::Column::OrganizationId.eq(organization_id))
             .order_by_desc(item::Column::UpdatedAt)
             .limit(count)
             .all(db)
@@ -79,11 +178,12 @@ impl Item {
 #[get("/")]
 pub async fn index(
     db: &State<DatabaseConnection>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
 ) -> Result<Json<ResponseList<ResponseItem>>, ErrorResponder> {
     let db = db as &DatabaseConnection;
 
     let items = Item::find()
+        .filter(item::Column::OrganizationId.eq(user.organization_id))
         .order_by_desc(item::Column::UpdatedAt)
         .all(db)
         .await?
@@ -107,21 +207,53 @@ pub async fn store(
 
     let mut category: Option<i32> = None;
     if let Some(category_uuid) = req_item.category_uuid.to_owned() {
-        if let Some(c) = Category::from_uuid(db, category_uuid.as_str()).await? {
+        if let Some(c) =
+            Category::from_uuid(db, user.organization_id, category_uuid.as_str()).await?
+        {
             category = Some(c.id);
         }
     }
 
-    Item::insert(item::ActiveModel {
+    // Serialized items start empty; their units are added as assets.
+    let mode = ItemMode::from_str(req_item.mode.as_deref().unwrap_or_default());
+    let quantity = match mode {
+        ItemMode::Bulk => req_item.quantity,
+        ItemMode::Serialized => Decimal::ZERO,
+    };
+    let unit = Item::validate_quantity(mode, req_item.unit.as_deref(), quantity)?;
+
+    let group = Group::id_from_uuid(db, user.organization_id, &req_item.group_uuid).await?;
+    let location =
+        Location::id_from_uuid(db, user.organization_id, &req_item.location_uuid).await?;
+
+    let item_id = Item::insert(item::ActiveModel {
         uuid: Set(Uuid::new_v4().to_string()),
+        organization_id: Set(user.organization_id),
         user_id: Set(user.id),
         category_id: Set(category),
+        group_id: Set(group),
         name: Set(req_item.name.to_owned()),
         description: Set(req_item.description.to_owned()),
-        quantity: Set(req_item.quantity),
+        quantity: Set(quantity),
+        unit: Set(unit),
+        unit_cost: Set(req_item.unit_cost),
+        currency: Set(req_item.currency.to_owned()),
+        mode: Set(mode.as_str().to_string()),
+        location_id: Set(location),
         ..Default::default()
     })
     .exec(db)
+    .await?
+    .last_insert_id;
+
+    StockMovement::record(
+        db,
+        item_id,
+        user.id,
+        quantity,
+        req_item.unit_cost,
+        "initial",
+    )
     .await?;
 
     success(Status::Created)
@@ -130,16 +262,22 @@ pub async fn store(
 #[get("/<uuid>")]
 pub async fn show(
     db: &State<DatabaseConnection>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
+    preconditions: Preconditions,
     uuid: &str,
-) -> Result<Json<ResponseItem>, ErrorResponder> {
+) -> Result<Tagged<Json<ResponseItem>>, ErrorResponder> {
     let db = db as &DatabaseConnection;
 
-    let item = match Item::from_uuid(db, uuid).await? {
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
         Some(i) => i,
         None => return Err(not_found()),
     };
 
+    let etag = etag(item.version);
+    if preconditions.not_modified(&etag) {
+        return Ok(Tagged::not_modified(etag));
+    }
+
     let user = item.find_related(User).one(db).await?.unwrap();
 
     let mut response = ResponseItem::from(&item);
@@ -151,39 +289,112 @@ pub async fn show(
     }
 
     response.user = Some(ResponseUser::from(user));
+    response.group = item
+        .find_related(Group)
+        .one(db)
+        .await?
+        .as_ref()
+        .map(ResponseGroup::from);
+    response.location = item
+        .find_related(Location)
+        .one(db)
+        .await?
+        .as_ref()
+        .map(ResponseLocation::from);
+    response.attachments = Some(Attachment::for_item(db, &item).await?);
 
-    Ok(Json(response))
+    Ok(Tagged::new(Json(response), etag))
 }
 
 #[put("/<uuid>", data = "<req_item>")]
 pub async fn update(
     db: &State<DatabaseConnection>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
+    preconditions: Preconditions,
     uuid: &str,
     req_item: Json<RequestItem<'_>>,
 ) -> Response {
     let db = db as &DatabaseConnection;
 
-    let mut item: item::ActiveModel = match Item::from_uuid(db, uuid).await? {
-        Some(i) => i.into(),
+    let existing = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
         None => return Err(not_found()),
     };
 
+    if !preconditions.allows(&etag(existing.version)) {
+        return Err(precondition_failed());
+    }
+
+    // A serialized item's quantity is the number of its active assets and
+    // cannot be set directly.
+    let mode = match &req_item.mode {
+        Some(mode) => ItemMode::from_str(mode),
+        None => ItemMode::from_str(&existing.mode),
+    };
+
+    // Assets only belong to serialized items, so the mode is kept while any
+    // exist, retired ones included.
+    if mode != ItemMode::from_str(&existing.mode) {
+        let assets = existing.find_related(Asset).count(db).await?;
+        if assets > 0 {
+            return Err(error_response(
+                Status::Conflict,
+                "Item has assets".to_string(),
+            ));
+        }
+    }
+    let quantity = match mode {
+        ItemMode::Bulk => req_item.quantity,
+        ItemMode::Serialized => Decimal::from(Asset::active_count(db, existing.id).await?),
+    };
+    let unit = Item::validate_quantity(mode, req_item.unit.as_deref(), quantity)?;
+
+    let txn = db.begin().await?;
+
+    StockMovement::record(
+        &txn,
+        existing.id,
+        user.id,
+        quantity - existing.quantity,
+        req_item.unit_cost,
+        "adjustment",
+    )
+    .await?;
+
+    let version = existing.version;
+    let mut item: item::ActiveModel = existing.into();
+
     let mut category: Option<i32> = None;
     if let Some(category_uuid) = req_item.category_uuid.to_owned() {
-        if let Some(c) = Category::from_uuid(db, category_uuid.as_str()).await? {
+        if let Some(c) =
+            Category::from_uuid(db, user.organization_id, category_uuid.as_str()).await?
+        {
             category = Some(c.id);
         }
     }
 
+    let group = Group::id_from_uuid(db, user.organization_id, &req_item.group_uuid).await?;
+    let location =
+        Location::id_from_uuid(db, user.organization_id, &req_item.location_uuid).await?;
+
     item.category_id = Set(category);
+    item.group_id = Set(group);
     item.name = Set(req_item.name.to_owned());
     item.description = Set(req_item.description.to_owned());
-    item.quantity = Set(req_item.quantity);
+    item.quantity = Set(quantity);
+    item.unit = Set(unit);
+    item.mode = Set(mode.as_str().to_string());
+    item.unit_cost = Set(req_item.unit_cost);
+    item.currency = Set(req_item.currency.to_owned());
+    item.location_id = Set(location);
 
     item.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
 
-    item.update(db).await?;
+    preconditions
+        .update(item, item::Column::Version, version, &txn)
+        .await?;
+
+    txn.commit().await?;
 
     success(Status::Ok)
 }
@@ -191,17 +402,125 @@ pub async fn update(
 #[delete("/<uuid>")]
 pub async fn delete(
     db: &State<DatabaseConnection>,
-    _user: AuthenticatedUser,
+    storage: &State<StorageBackend>,
+    user: AuthenticatedUser,
+    preconditions: Preconditions,
+    uuid: &str,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    if !preconditions.allows(&etag(item.version)) {
+        return Err(precondition_failed());
+    }
+
+    // Purchase orders keep their lines as a record, so their items stay.
+    let ordered = PurchaseOrderLine::find()
+        .filter(purchase_order_line::Column::ItemId.eq(item.id))
+        .count(db)
+        .await?;
+
+    if ordered > 0 {
+        return Err(error_response(
+            Status::Conflict,
+            "Item is on purchase orders".to_string(),
+        ));
+    }
+
+    let attachments = item.find_related(Attachment).all(db).await?;
+
+    // Every row goes in one transaction, so a refused delete leaves the item
+    // whole. Stored files are only removed once it has committed.
+    let txn = db.begin().await?;
+
+    Attachment::delete_many()
+        .filter(attachment::Column::ItemId.eq(item.id))
+        .exec(&txn)
+        .await?;
+    StockMovement::remove_for_item(&txn, &item).await?;
+
+    let version = item.version;
+    preconditions
+        .delete(
+            item.into_active_model(),
+            item::Column::Version,
+            version,
+            &txn,
+        )
+        .await?;
+
+    txn.commit().await?;
+
+    for attachment in attachments {
+        Attachment::remove_files(storage, &attachment).await?;
+    }
+
+    success(Status::Ok)
+}
+
+#[post("/<uuid>/check-out", data = "<req_check_out>")]
+pub async fn check_out(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
     uuid: &str,
+    req_check_out: Json<RequestCheckOut>,
 ) -> Response {
     let db = db as &DatabaseConnection;
 
-    let item = match Item::from_uuid(db, uuid).await? {
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
         Some(i) => i,
         None => return Err(not_found()),
     };
 
-    item.delete(db).await?;
+    if ItemMode::from_str(&item.mode) == ItemMode::Serialized {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            "Serialized items are checked out by assigning their assets".to_string(),
+        ));
+    }
+
+    // Quantities may be given in any unit compatible with the item's own.
+    let unit = req_check_out.unit.as_deref().unwrap_or(&item.unit);
+    let taken = match units::convert(req_check_out.quantity, unit, &item.unit) {
+        Some(q) => q,
+        None => {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                format!("Cannot convert {} to {}", unit, item.unit),
+            ))
+        }
+    };
+
+    if taken <= Decimal::ZERO
+        || taken > item.quantity
+        || !units::is_valid_quantity(taken, &item.unit)
+    {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            format!(
+                "Quantity must be more than 0 and at most {} {}",
+                item.quantity, item.unit
+            ),
+        ));
+    }
+
+    let txn = db.begin().await?;
+
+    Lot::consume(&txn, &item, taken).await?;
+
+    StockMovement::record(&txn, item.id, user.id, -taken, item.unit_cost, "check-out").await?;
+
+    let quantity = item.quantity - taken;
+    let mut item: item::ActiveModel = item.into();
+    item.quantity = Set(quantity);
+    item.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+    item.update(&txn).await?;
+
+    txn.commit().await?;
 
     success(Status::Ok)
 }
@@ -0,0 +1,205 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::SystemTime;
+
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{prelude::DateTimeUtc, *};
+use uuid::Uuid;
+
+use super::{
+    auth::AuthenticatedUser, error_response, items::ResponseItem, not_found, success,
+    ErrorResponder, Response, ResponseList,
+};
+use crate::entities::{item, location, prelude::*, stocktake};
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestLocation<'r> {
+    name: &'r str,
+    description: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseLocation {
+    uuid: String,
+    name: String,
+    description: Option<String>,
+    items: Option<Vec<ResponseItem>>,
+}
+
+impl From<&location::Model> for ResponseLocation {
+    fn from(location: &location::Model) -> ResponseLocation {
+        ResponseLocation {
+            uuid: location.uuid.to_owned(),
+            name: location.name.to_owned(),
+            description: location.description.to_owned(),
+            items: None,
+        }
+    }
+}
+
+impl Location {
+    pub async fn from_uuid(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        uuid: &str,
+    ) -> Result<Option<location::Model>, DbErr> {
+        Location::find()
+            .filter(location::Column::OrganizationId.eq(organization_id))
+            .filter(location::Column::Uuid.eq(uuid))
+            .one(db)
+            .await
+    }
+
+    /// Resolves an optional location uuid from a request, ignoring unknown locations.
+    pub async fn id_from_uuid(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        uuid: &Option<String>,
+    ) -> Result<Option<i32>, DbErr> {
+        Ok(match uuid {
+            Some(uuid) => Location::from_uuid(db, organization_id, uuid)
+                .await?
+                .map(|l| l.id),
+            None => None,
+        })
+    }
+}
+
+#[get("/")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<Json<ResponseList<ResponseLocation>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let locations = Location::find()
+        .filter(location::Column::OrganizationId.eq(user.organization_id))
+        .order_by_asc(location::Column::Name)
+        .all(db)
+        .await?
+        .iter()
+        .map(ResponseLocation::from)
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: locations.len(),
+        results: locations,
+    }))
+}
+
+#[post("/", data = "<req_location>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    req_location: Json<RequestLocation<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    Location::insert(location::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        organization_id: Set(user.organization_id),
+        name: Set(req_location.name.to_owned()),
+        description: Set(req_location.description.to_owned()),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    success(Status::Created)
+}
+
+#[get("/<uuid>")]
+pub async fn show(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Result<Json<ResponseLocation>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let location = match Location::from_uuid(db, user.organization_id, uuid).await? {
+        Some(l) => l,
+        None => return Err(not_found()),
+    };
+
+    let mut response = ResponseLocation::from(&location);
+
+    response.items = Some(
+        location
+            .find_related(Item)
+            .order_by_asc(item::Column::Name)
+            .all(db)
+            .await?
+            .iter()
+            .map(ResponseItem::from)
+            .collect::<Vec<_>>(),
+    );
+
+    Ok(Json(response))
+}
+
+#[put("/<uuid>", data = "<req_location>")]
+pub async fn update(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_location: Json<RequestLocation<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let mut location: location::ActiveModel =
+        match Location::from_uuid(db, user.organization_id, uuid).await? {
+            Some(l) => l.into(),
+            None => return Err(not_found()),
+        };
+
+    location.name = Set(req_location.name.to_owned());
+    location.description = Set(req_location.description.to_owned());
+
+    location.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+
+    location.update(db).await?;
+
+    success(Status::Ok)
+}
+
+#[delete("/<uuid>")]
+pub async fn delete(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let location = match Location::from_uuid(db, user.organization_id, uuid).await? {
+        Some(l) => l,
+        None => return Err(not_found()),
+    };
+
+    // Its items are left without a location, but stocktakes of it are kept.
+    let stocktakes = Stocktake::find()
+        .filter(stocktake::Column::LocationId.eq(location.id))
+        .count(db)
+        .await?;
+
+    if stocktakes > 0 {
+        return Err(error_response(
+            Status::Conflict,
+            "Location has stocktakes".to_string(),
+        ));
+    }
+
+    location.delete(db).await?;
+
+    success(Status::Ok)
+}
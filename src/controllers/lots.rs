@@ -0,0 +1,310 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::SystemTime;
+
+use chrono::{Duration, NaiveDate, Utc};
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{
+    prelude::{Date, DateTimeUtc, Decimal},
+    *,
+};
+use uuid::Uuid;
+
+use super::{
+    auth::AuthenticatedUser,
+    error_response,
+    items::{ItemMode, ResponseItem},
+    not_found, success, ErrorResponder, Response, ResponseList,
+};
+use crate::{
+    entities::{item, lot, prelude::*},
+    units, Config,
+};
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestLot<'r> {
+    batch_number: &'r str,
+    quantity: Decimal,
+    expiry_date: Option<String>,
+    unit_cost: Option<Decimal>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestLotUpdate<'r> {
+    batch_number: &'r str,
+    expiry_date: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseLot {
+    uuid: String,
+    item: Option<ResponseItem>,
+    batch_number: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    quantity: Decimal,
+    expiry_date: Option<String>,
+}
+
+impl From<&lot::Model> for ResponseLot {
+    fn from(lot: &lot::Model) -> ResponseLot {
+        ResponseLot {
+            uuid: lot.uuid.to_owned(),
+            item: None,
+            batch_number: lot.batch_number.to_owned(),
+            quantity: lot.quantity,
+            expiry_date: lot.expiry_date.map(|d| d.to_string()),
+        }
+    }
+}
+
+impl Lot {
+    pub async fn from_uuid(
+        db: &DatabaseConnection,
+        item: &item::Model,
+        uuid: &str,
+    ) -> Result<Option<lot::Model>, DbErr> {
+        Lot::find()
+            .filter(lot::Column::ItemId.eq(item.id))
+            .filter(lot::Column::Uuid.eq(uuid))
+            .one(db)
+            .await
+    }
+
+    /// Lots with stock left that expire within `days` days, soonest first.
+    /// Lots that have already expired are included.
+    pub async fn expiring(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        days: u32,
+    ) -> Result<Vec<ResponseLot>, DbErr> {
+        // Windows reaching past the last representable date cover every lot.
+        let until = Utc::now()
+            .date_naive()
+            .checked_add_signed(Duration::days(i64::from(days)))
+            .unwrap_or(NaiveDate::MAX);
+
+        Ok(Lot::find()
+            .find_also_related(Item)
+            .filter(item::Column::OrganizationId.eq(organization_id))
+            .filter(lot::Column::Quantity.gt(0))
+            .filter(lot::Column::ExpiryDate.lte(until))
+            .order_by_asc(lot::Column::ExpiryDate)
+            .all(db)
+            .await?
+            .iter()
+            .map(|(lot, item)| {
+                let mut response = ResponseLot::from(lot);
+                response.item = item.as_ref().map(ResponseItem::from);
+                response
+            })
+            .collect::<Vec<_>>())
+    }
+
+    /// Takes `quantity` out of an item's lots, first-expiry-first-out. Lots
+    /// without an expiry date go last. Any quantity beyond what the lots hold
+    /// comes out of the item's untracked stock.
+    pub async fn consume<C: ConnectionTrait>(
+        db: &C,
+        item: &item::Model,
+        quantity: Decimal,
+    ) -> Result<(), DbErr> {
+        let mut lots = item
+            .find_related(Lot)
+            .filter(lot::Column::Quantity.gt(0))
+            .order_by_asc(lot::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        lots.sort_by_key(|l| (l.expiry_date.is_none(), l.expiry_date));
+
+        let mut remaining = quantity;
+        for l in lots {
+            if remaining.is_zero() {
+                break;
+            }
+
+            let taken = remaining.min(l.quantity);
+            let left = l.quantity - taken;
+            remaining -= taken;
+
+            let mut l: lot::ActiveModel = l.into();
+            l.quantity = Set(left);
+            l.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+            l.update(db).await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_date(date: &Option<String>) -> Result<Option<Date>, ErrorResponder> {
+    match date {
+        Some(d) => match NaiveDate::parse_from_str(d, "%Y-%m-%d") {
+            Ok(d) => Ok(Some(d)),
+            Err(_) => Err(error_response(
+                Status::UnprocessableEntity,
+                "Dates must be formatted as YYYY-MM-DD".to_string(),
+            )),
+        },
+        None => Ok(None),
+    }
+}
+
+#[get("/<uuid>/lots")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Result<Json<ResponseList<ResponseLot>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let lots = item
+        .find_related(Lot)
+        .order_by_asc(lot::Column::ExpiryDate)
+        .all(db)
+        .await?
+        .iter()
+        .map(ResponseLot::from)
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: lots.len(),
+        results: lots,
+    }))
+}
+
+#[post("/<uuid>/lots", data = "<req_lot>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_lot: Json<RequestLot<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    if ItemMode::from_str(&item.mode) == ItemMode::Serialized {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            "Serialized items are received by adding their assets".to_string(),
+        ));
+    }
+
+    if req_lot.quantity.is_zero() || !units::is_valid_quantity(req_lot.quantity, &item.unit) {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            format!(
+                "{} is not a valid quantity of {}",
+                req_lot.quantity, item.unit
+            ),
+        ));
+    }
+
+    let expiry_date = parse_date(&req_lot.expiry_date)?;
+
+    let txn = db.begin().await?;
+
+    Lot::insert(lot::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        item_id: Set(item.id),
+        batch_number: Set(req_lot.batch_number.to_owned()),
+        quantity: Set(req_lot.quantity),
+        expiry_date: Set(expiry_date),
+        ..Default::default()
+    })
+    .exec(&txn)
+    .await?;
+
+    StockMovement::record(
+        &txn,
+        item.id,
+        user.id,
+        req_lot.quantity,
+        req_lot.unit_cost.or(item.unit_cost),
+        "lot",
+    )
+    .await?;
+
+    let quantity = item.quantity + req_lot.quantity;
+    let mut item: item::ActiveModel = item.into();
+    item.quantity = Set(quantity);
+    item.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+    item.update(&txn).await?;
+
+    txn.commit().await?;
+
+    success(Status::Created)
+}
+
+#[put("/<uuid>/lots/<lot_uuid>", data = "<req_lot>")]
+pub async fn update(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    lot_uuid: &str,
+    req_lot: Json<RequestLotUpdate<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let mut lot: lot::ActiveModel = match Lot::from_uuid(db, &item, lot_uuid).await? {
+        Some(l) => l.into(),
+        None => return Err(not_found()),
+    };
+
+    lot.batch_number = Set(req_lot.batch_number.to_owned());
+    lot.expiry_date = Set(parse_date(&req_lot.expiry_date)?);
+
+    lot.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+
+    lot.update(db).await?;
+
+    success(Status::Ok)
+}
+
+#[get("/expiring?<days>")]
+pub async fn expiring(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    user: AuthenticatedUser,
+    days: Option<u32>,
+) -> Result<Json<ResponseList<ResponseLot>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let lots = Lot::expiring(
+        db,
+        user.organization_id,
+        days.unwrap_or(config.expiry_warning_days),
+    )
+    .await?;
+
+    Ok(Json(ResponseList {
+        total: lots.len(),
+        results: lots,
+    }))
+}
@@ -7,14 +7,17 @@
  */
 use std::time::SystemTime;
 
-use bcrypt::{hash, DEFAULT_COST};
+use bcrypt::{hash, verify, DEFAULT_COST};
 use rocket::{
     serde::{json::Json, Deserialize},
     *, http::Status,
 };
 use sea_orm::{prelude::DateTimeUtc, *};
 
-use super::{auth::AuthenticatedUser, success, users::ResponseUser, ErrorResponder, Response};
+use super::{
+    auth::AuthenticatedUser, error_response, success, users::ResponseUser, ErrorResponder,
+    Response,
+};
 use crate::entities::{prelude::*, user};
 
 #[derive(Deserialize)]
@@ -24,6 +27,7 @@ pub struct RequestMe<'r> {
     lastname: &'r str,
     email: &'r str,
     password: &'r str,
+    current_password: Option<&'r str>,
 }
 
 #[get("/")]
@@ -33,9 +37,19 @@ pub async fn index(
 ) -> Result<Json<ResponseUser>, ErrorResponder> {
     let db = db as &DatabaseConnection;
 
-    let user = User::find_by_id(user.id).one(db).await?.unwrap();
+    // Reported with the role in the active organization.
+    let member = User::members(user.organization_id)
+        .filter(user::Column::Id.eq(user.id))
+        .one(db)
+        .await?
+        .unwrap();
+
+    let groups = Group::for_user(db, user.organization_id, user.id).await?;
+
+    let mut response = ResponseUser::from(member);
+    response.groups = Some(groups);
 
-    Ok(Json(ResponseUser::from(user)))
+    Ok(Json(response))
 }
 
 #[put("/", data = "<req_me>")]
@@ -46,13 +60,31 @@ pub async fn update(
 ) -> Response {
     let db = db as &DatabaseConnection;
 
-    let mut user: user::ActiveModel = User::find_by_id(user.id).one(db).await?.unwrap().into();
+    let existing = User::find_by_id(user.id).one(db).await?.unwrap();
+
+    // A stolen token alone must not be enough to take over the account,
+    // whether by setting its password or by resetting it through a new email.
+    let email_changed = !req_me.email.eq_ignore_ascii_case(&existing.email);
+    if (!req_me.password.is_empty() || email_changed)
+        && !verify(req_me.current_password.unwrap_or_default(), &existing.password)
+            .unwrap_or(false)
+    {
+        return Err(error_response(
+            Status::Forbidden,
+            "Current password is incorrect".to_string(),
+        ));
+    }
+
+    let (id, session, api_key) = (user.id, user.session, user.api_key);
+    let password_changed = !req_me.password.is_empty();
+
+    let mut user: user::ActiveModel = existing.into();
 
     user.firstname = Set(req_me.firstname.to_owned());
     user.lastname = Set(req_me.lastname.to_owned());
     user.email = Set(req_me.email.to_owned());
 
-    if !req_me.password.is_empty() {
+    if password_changed {
         user.password = Set(hash(req_me.password, DEFAULT_COST).unwrap());
     }
 
@@ -60,5 +92,11 @@ pub async fn update(
 
     user.update(db).await?;
 
+    // Whoever knew the old password is signed out everywhere else.
+    if password_changed {
+        Session::revoke_others(db, id, session).await?;
+        ApiKey::revoke_others(db, id, api_key).await?;
+    }
+
     success(Status::Ok)
 }
@@ -0,0 +1,56 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use rocket::{
+    serde::{json::Json, Serialize},
+    *,
+};
+use sea_orm::*;
+
+use super::{
+    admin_required,
+    auth::{AuthenticatedUser, Role},
+    ErrorResponder, ResponseList,
+};
+use crate::migrator::Migrator;
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseMigration {
+    name: String,
+    applied: bool,
+    applied_at: Option<String>,
+}
+
+/// The database migrations and when they were applied.
+#[get("/")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<Json<ResponseList<ResponseMigration>>, ErrorResponder> {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let (migrations, _) = Migrator::state(db).await?;
+
+    let migrations = migrations
+        .into_iter()
+        .map(|m| ResponseMigration {
+            name: m.name,
+            applied: m.applied_at.is_some(),
+            applied_at: m.applied_at.map(|at| at.to_rfc3339()),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: migrations.len(),
+        results: migrations,
+    }))
+}
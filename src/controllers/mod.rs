@@ -5,16 +5,39 @@
  * @link   https://afaan.dev
  * @link   https://github.com/AfaanBilal/iron-guard-server
  */
-use rocket::{http::Status, serde::Serialize};
+use rocket::{
+    http::{Header, Status},
+    serde::Serialize,
+};
 use sea_orm::DbErr;
 use serde_json::json;
 
+pub mod api_keys;
+pub mod assets;
+pub mod attachments;
 pub mod auth;
 pub mod categories;
 pub mod dashboard;
+pub mod groups;
+pub mod health;
 pub mod inventory;
+pub mod invitations;
 pub mod items;
+pub mod locations;
+pub mod lots;
 pub mod me;
+pub mod migrations;
+pub mod oidc;
+pub mod organizations;
+pub mod passwords;
+pub mod purchase_orders;
+pub mod reports;
+pub mod sessions;
+pub mod stock_movements;
+pub mod stocktakes;
+pub mod suppliers;
+pub mod two_factor;
+pub mod units;
 pub mod users;
 
 #[derive(Serialize)]
@@ -32,6 +55,8 @@ pub enum SuccessResponder {
 #[derive(Responder)]
 pub enum ErrorResponder {
     Error((Status, String)),
+    #[response(status = 429)]
+    TooManyRequests(String, Header<'static>),
 }
 
 impl From<DbErr> for ErrorResponder {
@@ -40,6 +65,12 @@ impl From<DbErr> for ErrorResponder {
     }
 }
 
+impl From<std::io::Error> for ErrorResponder {
+    fn from(err: std::io::Error) -> Self {
+        ErrorResponder::Error((Status::InternalServerError, err.to_string()))
+    }
+}
+
 type Response = Result<SuccessResponder, ErrorResponder>;
 
 pub fn success(status: Status) -> Response {
@@ -60,6 +91,17 @@ pub fn admin_required() -> ErrorResponder {
     ErrorResponder::Error((Status::Forbidden, "Admin Required".to_string()))
 }
 
+pub fn too_many_requests(retry_after: u64) -> ErrorResponder {
+    ErrorResponder::TooManyRequests(
+        json!({ "status": "error", "message": "Too many attempts" }).to_string(),
+        Header::new("Retry-After", retry_after.to_string()),
+    )
+}
+
 pub fn not_found() -> ErrorResponder {
     ErrorResponder::Error((Status::NotFound, "Not Found".to_string()))
 }
+
+pub fn precondition_failed() -> ErrorResponder {
+    ErrorResponder::Error((Status::PreconditionFailed, "Precondition Failed".to_string()))
+}
@@ -0,0 +1,260 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::{Duration, SystemTime};
+
+use rocket::{http::Status, response::Redirect, serde::json::Json, *};
+use sea_orm::{prelude::DateTimeUtc, *};
+
+use super::{
+    auth::{issue, ResponseSignIn, Role},
+    error_response, not_found,
+    passwords::{hash_token, random_token},
+    sessions::ClientInfo,
+    ErrorResponder,
+};
+use crate::{
+    entities::{oidc_login, organization, prelude::*, user},
+    jwt::Keys,
+    oidc::{self, Identity, Provider},
+    Config,
+};
+
+/// How long the user has to complete the sign-in at the provider.
+const LOGIN_TTL: u64 = 10 * 60;
+
+impl From<oidc::Error> for ErrorResponder {
+    fn from(err: oidc::Error) -> Self {
+        match err {
+            oidc::Error::Provider(_) => error_response(Status::BadGateway, err.to_string()),
+            oidc::Error::InvalidToken(_) => error_response(Status::Unauthorized, err.to_string()),
+        }
+    }
+}
+
+impl User {
+    /// The account for a provider identity: the one linked to its subject,
+    /// else the one with its verified email, which is then linked, else a
+    /// new one if `provision` is set.
+    async fn from_identity(
+        db: &DatabaseConnection,
+        identity: &Identity,
+        provision: bool,
+    ) -> Result<Option<user::Model>, DbErr> {
+        let linked = User::find()
+            .filter(user::Column::OidcSubject.eq(identity.subject.as_str()))
+            .one(db)
+            .await?;
+
+        if linked.is_some() {
+            return Ok(linked);
+        }
+
+        let email = match &identity.email {
+            Some(e) if identity.email_verified => e,
+            _ => return Ok(None),
+        };
+
+        if let Some(existing) = User::find()
+            .filter(user::Column::Email.eq(email.as_str()))
+            .one(db)
+            .await?
+        {
+            let mut u: user::ActiveModel = existing.into();
+            u.oidc_subject = Set(Some(identity.subject.to_owned()));
+            return Ok(Some(u.update(db).await?));
+        }
+
+        if !provision {
+            return Ok(None);
+        }
+
+        let (firstname, lastname) = match (&identity.given_name, &identity.family_name) {
+            (Some(given), family) => (given.to_owned(), family.to_owned().unwrap_or_default()),
+            _ => match identity.name.as_deref().unwrap_or(email).split_once(' ') {
+                Some((first, last)) => (first.to_string(), last.to_string()),
+                None => (
+                    identity.name.to_owned().unwrap_or(email.to_owned()),
+                    String::new(),
+                ),
+            },
+        };
+
+        Ok(Some(
+            User::provision(
+                db,
+                email,
+                &firstname,
+                &lastname,
+                Some(identity.subject.to_owned()),
+            )
+            .await?,
+        ))
+    }
+}
+
+/// Redirects to the provider's sign-in page.
+#[get("/oidc/login")]
+pub async fn login(
+    db: &State<DatabaseConnection>,
+    provider: &State<Option<Provider>>,
+) -> Result<Redirect, ErrorResponder> {
+    let provider = match provider.inner() {
+        Some(p) => p,
+        None => return Err(not_found()),
+    };
+
+    let db = db as &DatabaseConnection;
+
+    let discovery = provider.discover().await?;
+
+    let now = SystemTime::now();
+
+    OidcLogin::delete_many()
+        .filter(oidc_login::Column::ExpiresAt.lt(DateTimeUtc::from(now)))
+        .exec(db)
+        .await?;
+
+    let state = random_token();
+    let nonce = random_token();
+    let code_verifier = random_token();
+
+    OidcLogin::insert(oidc_login::ActiveModel {
+        state_hash: Set(hash_token(&state)),
+        code_verifier: Set(code_verifier.to_owned()),
+        nonce: Set(nonce.to_owned()),
+        expires_at: Set(DateTimeUtc::from(now + Duration::from_secs(LOGIN_TTL))),
+        created_at: Set(DateTimeUtc::from(now)),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    Ok(Redirect::to(provider.authorize_url(
+        &discovery,
+        &state,
+        &nonce,
+        &code_verifier,
+    )))
+}
+
+/// Completes the sign-in the provider redirected back from.
+#[get("/oidc/callback?<code>&<state>&<error>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn callback(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    keys: &State<Keys>,
+    provider: &State<Option<Provider>>,
+    client: ClientInfo,
+    code: Option<&str>,
+    state: Option<&str>,
+    error: Option<&str>,
+) -> Result<Json<ResponseSignIn>, ErrorResponder> {
+    let provider = match provider.inner() {
+        Some(p) => p,
+        None => return Err(not_found()),
+    };
+
+    if let Some(error) = error {
+        return Err(error_response(
+            Status::Unauthorized,
+            format!("Sign-in failed: {}", error),
+        ));
+    }
+
+    let (code, state) = match (code, state) {
+        (Some(c), Some(s)) => (c, s),
+        _ => {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                "Code and state are required".to_string(),
+            ))
+        }
+    };
+
+    let db = db as &DatabaseConnection;
+
+    let login = match OidcLogin::find()
+        .filter(oidc_login::Column::StateHash.eq(hash_token(state)))
+        .filter(oidc_login::Column::ExpiresAt.gt(DateTimeUtc::from(SystemTime::now())))
+        .one(db)
+        .await?
+    {
+        Some(l) => l,
+        None => return Err(invalid_state()),
+    };
+
+    // Each state completes one sign-in, even against a racing request.
+    let claimed = OidcLogin::delete_many()
+        .filter(oidc_login::Column::Id.eq(login.id))
+        .exec(db)
+        .await?;
+
+    if claimed.rows_affected == 0 {
+        return Err(invalid_state());
+    }
+
+    let discovery = provider.discover().await?;
+    let identity = provider
+        .exchange(&discovery, code, &login.code_verifier, &login.nonce)
+        .await?;
+
+    let organization = match config.oidc_organization.as_str() {
+        "" => None,
+        uuid => {
+            Organization::find()
+                .filter(organization::Column::Uuid.eq(uuid))
+                .one(db)
+                .await?
+        }
+    };
+
+    let u = match User::from_identity(db, &identity, organization.is_some()).await? {
+        Some(u) => u,
+        None => {
+            return Err(error_response(
+                Status::Forbidden,
+                "No account for this identity".to_string(),
+            ))
+        }
+    };
+
+    let membership = match organization {
+        Some(o) => {
+            let role = if oidc::has_role(
+                &identity.claims,
+                &config.oidc_role_claim,
+                &config.oidc_admin_role,
+            ) {
+                Role::Admin
+            } else {
+                Role::User
+            };
+
+            OrganizationUser::sync(db, o.id, u.id, role).await?
+        }
+        None => match OrganizationUser::membership(db, u.id, None).await? {
+            Some(m) => m,
+            None => {
+                return Err(error_response(
+                    Status::Forbidden,
+                    "Not a member of the organization".to_string(),
+                ))
+            }
+        },
+    };
+
+    Ok(Json(issue(db, keys, &client, &u, membership).await?))
+}
+
+fn invalid_state() -> ErrorResponder {
+    error_response(
+        Status::Unauthorized,
+        "Invalid or expired sign-in state".to_string(),
+    )
+}
@@ -0,0 +1,248 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::SystemTime;
+
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{prelude::DateTimeUtc, *};
+use uuid::Uuid;
+
+use super::{
+    admin_required,
+    auth::{acting_role, AuthenticatedUser, Role},
+    not_found, success, ErrorResponder, Response, ResponseList,
+};
+use crate::entities::{organization, organization_user, prelude::*};
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestOrganization<'r> {
+    name: &'r str,
+    require_admin_two_factor: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseOrganization {
+    uuid: String,
+    name: String,
+    role: String,
+    active: bool,
+    require_admin_two_factor: bool,
+}
+
+impl ResponseOrganization {
+    fn new(
+        organization: &organization::Model,
+        membership: &organization_user::Model,
+        user: &AuthenticatedUser,
+    ) -> ResponseOrganization {
+        ResponseOrganization {
+            uuid: organization.uuid.to_owned(),
+            name: organization.name.to_owned(),
+            role: membership.role.to_owned(),
+            active: organization.id == user.organization_id,
+            require_admin_two_factor: organization.require_admin_two_factor,
+        }
+    }
+}
+
+impl OrganizationUser {
+    /// The user's membership in the organization matching `organization_uuid`,
+    /// or in their first organization when no uuid is given.
+    pub async fn membership(
+        db: &DatabaseConnection,
+        user_id: i32,
+        organization_uuid: Option<&str>,
+    ) -> Result<Option<organization_user::Model>, DbErr> {
+        let mut query = OrganizationUser::find()
+            .filter(organization_user::Column::UserId.eq(user_id))
+            .order_by_asc(organization_user::Column::Id);
+
+        if let Some(uuid) = organization_uuid {
+            query = query
+                .inner_join(Organization)
+                .filter(organization::Column::Uuid.eq(uuid));
+        }
+
+        query.one(db).await
+    }
+
+    /// Gives the user `role` in the organization, adding them to it if needed.
+    /// Used to keep roles in sync with an external identity source.
+    pub async fn sync(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        user_id: i32,
+        role: Role,
+    ) -> Result<organization_user::Model, DbErr> {
+        let existing = OrganizationUser::find()
+            .filter(organization_user::Column::OrganizationId.eq(organization_id))
+            .filter(organization_user::Column::UserId.eq(user_id))
+            .one(db)
+            .await?;
+
+        match existing {
+            Some(m) if Role::from_str(&m.role) == role => Ok(m),
+            Some(m) => {
+                let mut membership: organization_user::ActiveModel = m.into();
+                membership.role = Set(role.as_str().to_string());
+                membership.update(db).await
+            }
+            None => {
+                organization_user::ActiveModel {
+                    organization_id: Set(organization_id),
+                    user_id: Set(user_id),
+                    role: Set(role.as_str().to_string()),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await
+            }
+        }
+    }
+}
+
+async fn find_membership(
+    db: &DatabaseConnection,
+    user: &AuthenticatedUser,
+    uuid: &str,
+) -> Result<(organization::Model, organization_user::Model), ErrorResponder> {
+    let found = Organization::find()
+        .find_also_related(OrganizationUser)
+        .filter(organization::Column::Uuid.eq(uuid))
+        .filter(organization_user::Column::UserId.eq(user.id))
+        .one(db)
+        .await?;
+
+    match found {
+        Some((organization, Some(membership))) => Ok((organization, membership)),
+        _ => Err(not_found()),
+    }
+}
+
+#[get("/")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<Json<ResponseList<ResponseOrganization>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let organizations = Organization::find()
+        .find_also_related(OrganizationUser)
+        .filter(organization_user::Column::UserId.eq(user.id))
+        .order_by_asc(organization::Column::Name)
+        .all(db)
+        .await?
+        .iter()
+        .filter_map(|(organization, membership)| {
+            membership
+                .as_ref()
+                .map(|m| ResponseOrganization::new(organization, m, &user))
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: organizations.len(),
+        results: organizations,
+    }))
+}
+
+#[post("/", data = "<req_organization>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    req_organization: Json<RequestOrganization<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let txn = db.begin().await?;
+
+    let organization_id = Organization::insert(organization::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        name: Set(req_organization.name.to_owned()),
+        require_admin_two_factor: Set(req_organization.require_admin_two_factor.unwrap_or(false)),
+        ..Default::default()
+    })
+    .exec(&txn)
+    .await?
+    .last_insert_id;
+
+    // The creator administers the new organization.
+    OrganizationUser::insert(organization_user::ActiveModel {
+        organization_id: Set(organization_id),
+        user_id: Set(user.id),
+        role: Set("admin".to_string()),
+        ..Default::default()
+    })
+    .exec(&txn)
+    .await?;
+
+    txn.commit().await?;
+
+    success(Status::Created)
+}
+
+#[get("/<uuid>")]
+pub async fn show(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Result<Json<ResponseOrganization>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let (organization, membership) = find_membership(db, &user, uuid).await?;
+
+    Ok(Json(ResponseOrganization::new(
+        &organization,
+        &membership,
+        &user,
+    )))
+}
+
+#[put("/<uuid>", data = "<req_organization>")]
+pub async fn update(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_organization: Json<RequestOrganization<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let (organization, membership) = find_membership(db, &user, uuid).await?;
+
+    // The token's role already withholds admin rights until two-factor
+    // authentication is set up where it is required. Other organizations
+    // are checked the same way.
+    let role = if organization.id == user.organization_id {
+        user.role
+    } else {
+        let member = User::find_by_id(user.id).one(db).await?.unwrap();
+        acting_role(db, &member, &membership).await?
+    };
+
+    if role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let mut organization: organization::ActiveModel = organization.into();
+
+    organization.name = Set(req_organization.name.to_owned());
+    if let Some(required) = req_organization.require_admin_two_factor {
+        organization.require_admin_two_factor = Set(required);
+    }
+
+    organization.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+
+    organization.update(db).await?;
+
+    success(Status::Ok)
+}
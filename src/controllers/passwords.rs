@@ -0,0 +1,170 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::{Duration, SystemTime};
+
+use bcrypt::{hash, DEFAULT_COST};
+use rand::Rng;
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize},
+    *,
+};
+use sea_orm::{prelude::DateTimeUtc, sea_query::Expr, *};
+use sha2::{Digest, Sha256};
+
+use super::{auth::password_sign_in_disabled, error_response, success, ErrorResponder, Response};
+use crate::{
+    entities::{password_reset, prelude::*, user},
+    mailer::{MailerBackend, Message},
+    Config,
+};
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestForgot<'r> {
+    email: &'r str,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestReset<'r> {
+    token: &'r str,
+    password: &'r str,
+}
+
+/// A random token for emailed links.
+pub fn random_token() -> String {
+    hex::encode(rand::thread_rng().gen::<[u8; 32]>())
+}
+
+/// Only a digest of each emailed token is stored, so a leaked table cannot
+/// be used to take over accounts.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Emails a reset link to the user. Always succeeds, so the response does
+/// not reveal whether an account exists for the email.
+#[post("/forgot-password", data = "<req_forgot>")]
+pub async fn forgot(
+    db: &State<DatabaseConnection>,
+    mailer: &State<MailerBackend>,
+    config: &State<Config>,
+    req_forgot: Json<RequestForgot<'_>>,
+) -> Response {
+    if !config.password_sign_in {
+        return Err(password_sign_in_disabled());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let user = match User::find()
+        .filter(user::Column::Email.eq(req_forgot.email))
+        .one(db)
+        .await?
+    {
+        Some(u) => u,
+        None => return success(Status::Ok),
+    };
+
+    // Only the latest link works.
+    PasswordReset::delete_many()
+        .filter(password_reset::Column::UserId.eq(user.id))
+        .filter(password_reset::Column::UsedAt.is_null())
+        .exec(db)
+        .await?;
+
+    let token = random_token();
+    let now = SystemTime::now();
+
+    PasswordReset::insert(password_reset::ActiveModel {
+        user_id: Set(user.id),
+        token_hash: Set(hash_token(&token)),
+        expires_at: Set(DateTimeUtc::from(
+            now + Duration::from_secs(config.password_reset_ttl * 60),
+        )),
+        created_at: Set(DateTimeUtc::from(now)),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    mailer
+        .send(&Message {
+            to: user.email.to_owned(),
+            subject: "Reset your Iron Guard password".to_string(),
+            body: format!(
+                "Hi {},\r\n\r\nReset your password here within {} minutes:\r\n\
+                 {}/reset-password?token={}\r\n\r\n\
+                 If you did not ask for this, you can ignore this email.",
+                user.firstname, config.password_reset_ttl, config.app_url, token
+            ),
+        })
+        .await?;
+
+    success(Status::Ok)
+}
+
+#[post("/reset-password", data = "<req_reset>")]
+pub async fn reset(db: &State<DatabaseConnection>, req_reset: Json<RequestReset<'_>>) -> Response {
+    let db = db as &DatabaseConnection;
+
+    if req_reset.password.is_empty() {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            "Password is required".to_string(),
+        ));
+    }
+
+    let now = DateTimeUtc::from(SystemTime::now());
+
+    let reset = match PasswordReset::find()
+        .filter(password_reset::Column::TokenHash.eq(hash_token(req_reset.token)))
+        .filter(password_reset::Column::UsedAt.is_null())
+        .filter(password_reset::Column::ExpiresAt.gt(now))
+        .one(db)
+        .await?
+    {
+        Some(r) => r,
+        None => return Err(invalid_token()),
+    };
+
+    // Claim the token before using it, so a concurrent request cannot use it too.
+    let claimed = PasswordReset::update_many()
+        .col_expr(password_reset::Column::UsedAt, Expr::value(now))
+        .filter(password_reset::Column::Id.eq(reset.id))
+        .filter(password_reset::Column::UsedAt.is_null())
+        .exec(db)
+        .await?;
+
+    if claimed.rows_affected == 0 {
+        return Err(invalid_token());
+    }
+
+    let mut user: user::ActiveModel = match User::find_by_id(reset.user_id).one(db).await? {
+        Some(u) => u.into(),
+        None => return Err(invalid_token()),
+    };
+
+    user.password = Set(hash(req_reset.password, DEFAULT_COST).unwrap());
+    user.updated_at = Set(now);
+
+    let user = user.update(db).await?;
+
+    // Whoever knew the old password is signed out too.
+    Session::revoke_all(db, user.id).await?;
+
+    success(Status::Ok)
+}
+
+pub fn invalid_token() -> ErrorResponder {
+    error_response(
+        Status::UnprocessableEntity,
+        "Invalid or expired token".to_string(),
+    )
+}
@@ -0,0 +1,596 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::SystemTime;
+
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{
+    prelude::{DateTimeUtc, Decimal},
+    *,
+};
+use uuid::Uuid;
+
+use super::{
+    auth::AuthenticatedUser,
+    error_response,
+    items::{ItemMode, ResponseItem},
+    not_found, success,
+    suppliers::ResponseSupplier,
+    ErrorResponder, Response, ResponseList,
+};
+use crate::{
+    entities::{item, prelude::*, purchase_order, purchase_order_line, supplier, supplier_item},
+    units,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PurchaseOrderStatus {
+    Draft,
+    Sent,
+    PartiallyReceived,
+    Closed,
+}
+
+impl PurchaseOrderStatus {
+    pub fn from_str(status: &str) -> PurchaseOrderStatus {
+        match status {
+            "sent" => PurchaseOrderStatus::Sent,
+            "partially_received" => PurchaseOrderStatus::PartiallyReceived,
+            "closed" => PurchaseOrderStatus::Closed,
+            _ => PurchaseOrderStatus::Draft,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PurchaseOrderStatus::Draft => "draft",
+            PurchaseOrderStatus::Sent => "sent",
+            PurchaseOrderStatus::PartiallyReceived => "partially_received",
+            PurchaseOrderStatus::Closed => "closed",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestPurchaseOrderLine {
+    item_uuid: String,
+    quantity: Decimal,
+    unit_cost: Option<Decimal>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestPurchaseOrder<'r> {
+    supplier_uuid: &'r str,
+    notes: Option<String>,
+    lines: Vec<RequestPurchaseOrderLine>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestReceiptLine {
+    line_uuid: String,
+    quantity: Decimal,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestReceipt {
+    lines: Vec<RequestReceiptLine>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponsePurchaseOrderLine {
+    uuid: String,
+    item: Option<ResponseItem>,
+    #[serde(with = "rust_decimal::serde::float")]
+    quantity_ordered: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    quantity_received: Decimal,
+    unit_cost: Option<Decimal>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponsePurchaseOrder {
+    uuid: String,
+    status: String,
+    notes: Option<String>,
+    supplier: Option<ResponseSupplier>,
+    lines: Option<Vec<ResponsePurchaseOrderLine>>,
+}
+
+impl From<&purchase_order::Model> for ResponsePurchaseOrder {
+    fn from(order: &purchase_order::Model) -> ResponsePurchaseOrder {
+        ResponsePurchaseOrder {
+            uuid: order.uuid.to_owned(),
+            status: order.status.to_owned(),
+            notes: order.notes.to_owned(),
+            supplier: None,
+            lines: None,
+        }
+    }
+}
+
+impl PurchaseOrder {
+    pub async fn from_uuid(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        uuid: &str,
+    ) -> Result<Option<purchase_order::Model>, DbErr> {
+        PurchaseOrder::find()
+            .filter(purchase_order::Column::OrganizationId.eq(organization_id))
+            .filter(purchase_order::Column::Uuid.eq(uuid))
+            .one(db)
+            .await
+    }
+}
+
+fn invalid_status(order: &purchase_order::Model) -> ErrorResponder {
+    error_response(
+        Status::Conflict,
+        format!("Purchase order is {}", order.status),
+    )
+}
+
+/// Resolves request lines to line models, defaulting each unit cost to the
+/// supplier's price for the item and then to the item's own unit cost.
+async fn make_lines(
+    db: &DatabaseConnection,
+    supplier: &supplier::Model,
+    lines: &[RequestPurchaseOrderLine],
+) -> Result<Vec<purchase_order_line::ActiveModel>, ErrorResponder> {
+    let mut models = vec![];
+
+    for line in lines {
+        let item = match Item::from_uuid(db, supplier.organization_id, &line.item_uuid).await? {
+            Some(i) => i,
+            None => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    format!("Unknown item {}", line.item_uuid),
+                ))
+            }
+        };
+
+        if line.quantity.is_zero() || !units::is_valid_quantity(line.quantity, &item.unit) {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                format!("{} is not a valid quantity of {}", line.quantity, item.unit),
+            ));
+        }
+
+        let supplier_cost = SupplierItem::find()
+            .filter(supplier_item::Column::SupplierId.eq(supplier.id))
+            .filter(supplier_item::Column::ItemId.eq(item.id))
+            .one(db)
+            .await?
+            .and_then(|link| link.unit_cost);
+
+        models.push(purchase_order_line::ActiveModel {
+            uuid: Set(Uuid::new_v4().to_string()),
+            item_id: Set(item.id),
+            quantity_ordered: Set(line.quantity),
+            quantity_received: Set(Decimal::ZERO),
+            unit_cost: Set(line.unit_cost.or(supplier_cost).or(item.unit_cost)),
+            ..Default::default()
+        });
+    }
+
+    Ok(models)
+}
+
+async fn insert_lines<C: ConnectionTrait>(
+    db: &C,
+    order_id: i32,
+    lines: Vec<purchase_order_line::ActiveModel>,
+) -> Result<(), DbErr> {
+    for mut line in lines {
+        line.purchase_order_id = Set(order_id);
+        PurchaseOrderLine::insert(line).exec(db).await?;
+    }
+
+    Ok(())
+}
+
+#[get("/?<status>")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    status: Option<&str>,
+) -> Result<Json<ResponseList<ResponsePurchaseOrder>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let mut query = PurchaseOrder::find()
+        .filter(purchase_order::Column::OrganizationId.eq(user.organization_id))
+        .order_by_desc(purchase_order::Column::UpdatedAt);
+    if let Some(status) = status {
+        query = query.filter(purchase_order::Column::Status.eq(status));
+    }
+
+    let orders = query
+        .all(db)
+        .await?
+        .iter()
+        .map(ResponsePurchaseOrder::from)
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: orders.len(),
+        results: orders,
+    }))
+}
+
+#[post("/", data = "<req_order>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    req_order: Json<RequestPurchaseOrder<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let supplier =
+        match Supplier::from_uuid(db, user.organization_id, req_order.supplier_uuid).await? {
+            Some(s) => s,
+            None => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    "Unknown supplier".to_string(),
+                ))
+            }
+        };
+
+    let lines = make_lines(db, &supplier, &req_order.lines).await?;
+
+    let txn = db.begin().await?;
+
+    let order_id = PurchaseOrder::insert(purchase_order::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        organization_id: Set(user.organization_id),
+        supplier_id: Set(supplier.id),
+        user_id: Set(user.id),
+        status: Set(PurchaseOrderStatus::Draft.as_str().to_string()),
+        notes: Set(req_order.notes.to_owned()),
+        ..Default::default()
+    })
+    .exec(&txn)
+    .await?
+    .last_insert_id;
+
+    insert_lines(&txn, order_id, lines).await?;
+
+    txn.commit().await?;
+
+    success(Status::Created)
+}
+
+#[get("/<uuid>")]
+pub async fn show(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Result<Json<ResponsePurchaseOrder>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let order = match PurchaseOrder::from_uuid(db, user.organization_id, uuid).await? {
+        Some(o) => o,
+        None => return Err(not_found()),
+    };
+
+    let mut response = ResponsePurchaseOrder::from(&order);
+
+    response.supplier = order
+        .find_related(Supplier)
+        .one(db)
+        .await?
+        .as_ref()
+        .map(ResponseSupplier::from);
+
+    response.lines = Some(
+        order
+            .find_related(PurchaseOrderLine)
+            .find_also_related(Item)
+            .order_by_asc(purchase_order_line::Column::Id)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|(line, item)| ResponsePurchaseOrderLine {
+                uuid: line.uuid,
+                item: item.as_ref().map(ResponseItem::from),
+                quantity_ordered: line.quantity_ordered,
+                quantity_received: line.quantity_received,
+                unit_cost: line.unit_cost,
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    Ok(Json(response))
+}
+
+#[put("/<uuid>", data = "<req_order>")]
+pub async fn update(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_order: Json<RequestPurchaseOrder<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let order = match PurchaseOrder::from_uuid(db, user.organization_id, uuid).await? {
+        Some(o) => o,
+        None => return Err(not_found()),
+    };
+
+    if PurchaseOrderStatus::from_str(&order.status) != PurchaseOrderStatus::Draft {
+        return Err(invalid_status(&order));
+    }
+
+    let supplier =
+        match Supplier::from_uuid(db, user.organization_id, req_order.supplier_uuid).await? {
+            Some(s) => s,
+            None => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    "Unknown supplier".to_string(),
+                ))
+            }
+        };
+
+    let lines = make_lines(db, &supplier, &req_order.lines).await?;
+
+    let txn = db.begin().await?;
+
+    PurchaseOrderLine::delete_many()
+        .filter(purchase_order_line::Column::PurchaseOrderId.eq(order.id))
+        .exec(&txn)
+        .await?;
+
+    insert_lines(&txn, order.id, lines).await?;
+
+    let mut order: purchase_order::ActiveModel = order.into();
+
+    order.supplier_id = Set(supplier.id);
+    order.notes = Set(req_order.notes.to_owned());
+
+    order.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+
+    order.update(&txn).await?;
+
+    txn.commit().await?;
+
+    success(Status::Ok)
+}
+
+#[delete("/<uuid>")]
+pub async fn delete(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let order = match PurchaseOrder::from_uuid(db, user.organization_id, uuid).await? {
+        Some(o) => o,
+        None => return Err(not_found()),
+    };
+
+    if PurchaseOrderStatus::from_str(&order.status) != PurchaseOrderStatus::Draft {
+        return Err(invalid_status(&order));
+    }
+
+    let txn = db.begin().await?;
+
+    PurchaseOrderLine::delete_many()
+        .filter(purchase_order_line::Column::PurchaseOrderId.eq(order.id))
+        .exec(&txn)
+        .await?;
+
+    order.delete(&txn).await?;
+
+    txn.commit().await?;
+
+    success(Status::Ok)
+}
+
+async fn set_status(
+    db: &DatabaseConnection,
+    order: purchase_order::Model,
+    status: PurchaseOrderStatus,
+) -> Result<(), DbErr> {
+    let mut order: purchase_order::ActiveModel = order.into();
+
+    order.status = Set(status.as_str().to_string());
+    order.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+
+    order.update(db).await?;
+
+    Ok(())
+}
+
+#[post("/<uuid>/send")]
+pub async fn send(db: &State<DatabaseConnection>, user: AuthenticatedUser, uuid: &str) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let order = match PurchaseOrder::from_uuid(db, user.organization_id, uuid).await? {
+        Some(o) => o,
+        None => return Err(not_found()),
+    };
+
+    if PurchaseOrderStatus::from_str(&order.status) != PurchaseOrderStatus::Draft {
+        return Err(invalid_status(&order));
+    }
+
+    if order.find_related(PurchaseOrderLine).count(db).await? == 0 {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            "Purchase order has no lines".to_string(),
+        ));
+    }
+
+    set_status(db, order, PurchaseOrderStatus::Sent).await?;
+
+    success(Status::Ok)
+}
+
+#[post("/<uuid>/receive", data = "<req_receipt>")]
+pub async fn receive(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_receipt: Json<RequestReceipt>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let order = match PurchaseOrder::from_uuid(db, user.organization_id, uuid).await? {
+        Some(o) => o,
+        None => return Err(not_found()),
+    };
+
+    match PurchaseOrderStatus::from_str(&order.status) {
+        PurchaseOrderStatus::Sent | PurchaseOrderStatus::PartiallyReceived => {}
+        _ => return Err(invalid_status(&order)),
+    }
+
+    let txn = db.begin().await?;
+
+    for receipt in &req_receipt.lines {
+        let line = PurchaseOrderLine::find()
+            .filter(purchase_order_line::Column::PurchaseOrderId.eq(order.id))
+            .filter(purchase_order_line::Column::Uuid.eq(receipt.line_uuid.as_str()))
+            .one(&txn)
+            .await?;
+
+        let line = match line {
+            Some(l) => l,
+            None => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    format!("Unknown line {}", receipt.line_uuid),
+                ))
+            }
+        };
+
+        let item = match Item::find_by_id(line.item_id).one(&txn).await? {
+            Some(i) => i,
+            None => return Err(not_found()),
+        };
+
+        if ItemMode::from_str(&item.mode) == ItemMode::Serialized {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                format!(
+                    "Line {} is for a serialized item; add its assets instead",
+                    receipt.line_uuid
+                ),
+            ));
+        }
+
+        if !units::is_valid_quantity(receipt.quantity, &item.unit) {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                format!(
+                    "{} is not a valid quantity of {}",
+                    receipt.quantity, item.unit
+                ),
+            ));
+        }
+
+        let (received, quantity) = match (
+            line.quantity_received.checked_add(receipt.quantity),
+            item.quantity.checked_add(receipt.quantity),
+        ) {
+            (Some(r), Some(q)) => (r, q),
+            _ => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    format!("{} is too large a quantity", receipt.quantity),
+                ))
+            }
+        };
+
+        if received > line.quantity_ordered {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                format!(
+                    "Line {} would exceed the ordered quantity",
+                    receipt.line_uuid
+                ),
+            ));
+        }
+
+        StockMovement::record(
+            &txn,
+            item.id,
+            user.id,
+            receipt.quantity,
+            line.unit_cost,
+            "purchase",
+        )
+        .await?;
+
+        let mut item: item::ActiveModel = item.into();
+        item.quantity = Set(quantity);
+        item.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+        item.update(&txn).await?;
+
+        let mut line: purchase_order_line::ActiveModel = line.into();
+        line.quantity_received = Set(received);
+        line.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+        line.update(&txn).await?;
+    }
+
+    let outstanding = PurchaseOrderLine::find()
+        .filter(purchase_order_line::Column::PurchaseOrderId.eq(order.id))
+        .all(&txn)
+        .await?
+        .iter()
+        .any(|line| line.quantity_received < line.quantity_ordered);
+
+    let status = if outstanding {
+        PurchaseOrderStatus::PartiallyReceived
+    } else {
+        PurchaseOrderStatus::Closed
+    };
+
+    let mut order: purchase_order::ActiveModel = order.into();
+    order.status = Set(status.as_str().to_string());
+    order.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+    order.update(&txn).await?;
+
+    txn.commit().await?;
+
+    success(Status::Ok)
+}
+
+#[post("/<uuid>/close")]
+pub async fn close(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let order = match PurchaseOrder::from_uuid(db, user.organization_id, uuid).await? {
+        Some(o) => o,
+        None => return Err(not_found()),
+    };
+
+    match PurchaseOrderStatus::from_str(&order.status) {
+        PurchaseOrderStatus::Sent | PurchaseOrderStatus::PartiallyReceived => {}
+        _ => return Err(invalid_status(&order)),
+    }
+
+    set_status(db, order, PurchaseOrderStatus::Closed).await?;
+
+    success(Status::Ok)
+}
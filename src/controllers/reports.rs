@@ -0,0 +1,391 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::SystemTime,
+};
+
+use rocket::{
+    serde::{json::Json, Serialize},
+    *,
+};
+use sea_orm::{
+    prelude::{DateTimeUtc, Decimal},
+    *,
+};
+
+use super::{auth::AuthenticatedUser, ErrorResponder};
+use crate::{
+    entities::{category, item, location, prelude::*, stock_movement},
+    Config,
+};
+
+const HISTORY_DAYS: u32 = 30;
+const HISTORY_POINTS: u32 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CostMethod {
+    Unit,
+    Average,
+    Fifo,
+}
+
+impl CostMethod {
+    pub fn from_str(method: &str) -> CostMethod {
+        match method {
+            "average" => CostMethod::Average,
+            "fifo" => CostMethod::Fifo,
+            _ => CostMethod::Unit,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            CostMethod::Unit => "unit",
+            CostMethod::Average => "average",
+            CostMethod::Fifo => "fifo",
+        }
+    }
+}
+
+/// Totals keyed by currency code.
+type Totals = BTreeMap<String, Decimal>;
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseCategoryValue {
+    uuid: Option<String>,
+    name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    quantity: Decimal,
+    value: Totals,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseLocationValue {
+    uuid: Option<String>,
+    name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    quantity: Decimal,
+    value: Totals,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseValuation {
+    method: &'static str,
+    #[serde(with = "rust_decimal::serde::float")]
+    quantity: Decimal,
+    value: Totals,
+    categories: Vec<ResponseCategoryValue>,
+    locations: Vec<ResponseLocationValue>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseValuationPoint {
+    date: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    quantity: Decimal,
+    value: Totals,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseValuationHistory {
+    method: &'static str,
+    results: Vec<ResponseValuationPoint>,
+}
+
+/// Values `quantity` units of an item. `movements` must be ordered oldest first.
+///
+/// Receipts without a cost, and any quantity not covered by costed receipts,
+/// fall back to the item's own `unit_cost`.
+pub fn item_value(
+    item: &item::Model,
+    quantity: Decimal,
+    movements: &[&stock_movement::Model],
+    method: CostMethod,
+) -> Decimal {
+    let fallback = item.unit_cost.unwrap_or_default();
+    if quantity <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+
+    let receipts = movements
+        .iter()
+        .filter(|m| m.quantity > Decimal::ZERO && m.unit_cost.is_some())
+        .map(|m| (m.quantity, m.unit_cost.unwrap_or_default()))
+        .collect::<Vec<_>>();
+
+    match method {
+        CostMethod::Unit => fallback * quantity,
+        CostMethod::Average => {
+            let received: Decimal = receipts.iter().map(|(q, _)| q).sum();
+            if received.is_zero() {
+                return fallback * quantity;
+            }
+
+            let cost: Decimal = receipts.iter().map(|(q, c)| q * c).sum();
+            cost / received * quantity
+        }
+        CostMethod::Fifo => {
+            // Issues consume the oldest receipts first, so what is on hand is
+            // made up of the newest ones.
+            let mut remaining = quantity;
+            let mut value = Decimal::ZERO;
+
+            for (q, c) in receipts.iter().rev() {
+                if remaining.is_zero() {
+                    break;
+                }
+
+                let taken = remaining.min(*q);
+                value += taken * c;
+                remaining -= taken;
+            }
+
+            value + fallback * remaining
+        }
+    }
+}
+
+struct Snapshot {
+    quantity: Decimal,
+    value: Totals,
+    by_category: HashMap<i32, (Decimal, Totals)>,
+    uncategorized: (Decimal, Totals),
+    by_location: HashMap<i32, (Decimal, Totals)>,
+    unlocated: (Decimal, Totals),
+}
+
+fn add(totals: &mut Totals, currency: &str, value: Decimal) {
+    *totals.entry(currency.to_owned()).or_default() += value;
+}
+
+/// Values the inventory as it stood at `at`, or as it stands now when `at` is `None`.
+fn snapshot(
+    config: &Config,
+    items: &[item::Model],
+    categories: &HashMap<i32, category::Model>,
+    movements: &HashMap<i32, Vec<stock_movement::Model>>,
+    method: CostMethod,
+    at: Option<DateTimeUtc>,
+) -> Snapshot {
+    let mut snapshot = Snapshot {
+        quantity: Decimal::ZERO,
+        value: Totals::new(),
+        by_category: HashMap::new(),
+        uncategorized: (Decimal::ZERO, Totals::new()),
+        by_location: HashMap::new(),
+        unlocated: (Decimal::ZERO, Totals::new()),
+    };
+
+    for item in items {
+        if at.is_some_and(|at| item.created_at > at) {
+            continue;
+        }
+
+        let history = movements
+            .get(&item.id)
+            .map(|m| m.iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let (before, after): (Vec<_>, Vec<_>) = history
+            .into_iter()
+            .partition(|m| at.is_none_or(|at| m.created_at <= at));
+
+        let quantity = item.quantity - after.iter().map(|m| m.quantity).sum::<Decimal>();
+
+        let value = item_value(item, quantity, &before, method);
+        let currency = item.currency.as_deref().unwrap_or(&config.currency);
+
+        snapshot.quantity += quantity;
+        add(&mut snapshot.value, currency, value);
+
+        let location = match item.location_id {
+            Some(id) => snapshot.by_location.entry(id).or_default(),
+            None => &mut snapshot.unlocated,
+        };
+        location.0 += quantity;
+        add(&mut location.1, currency, value);
+
+        // Roll the value up through every ancestor so each category reports
+        // its whole subtree.
+        let mut category_id = item.category_id;
+        let mut depth = 0;
+        if category_id.is_none() {
+            snapshot.uncategorized.0 += quantity;
+            add(&mut snapshot.uncategorized.1, currency, value);
+        }
+
+        while let Some(id) = category_id {
+            if depth > categories.len() {
+                break;
+            }
+
+            let entry = snapshot.by_category.entry(id).or_default();
+            entry.0 += quantity;
+            add(&mut entry.1, currency, value);
+
+            category_id = categories.get(&id).and_then(|c| c.parent_id);
+            depth += 1;
+        }
+    }
+
+    snapshot
+}
+
+async fn load(
+    db: &DatabaseConnection,
+    organization_id: i32,
+) -> Result<
+    (
+        Vec<item::Model>,
+        HashMap<i32, category::Model>,
+        HashMap<i32, Vec<stock_movement::Model>>,
+    ),
+    DbErr,
+> {
+    let items = Item::find()
+        .filter(item::Column::OrganizationId.eq(organization_id))
+        .all(db)
+        .await?;
+
+    let categories = Category::find()
+        .filter(category::Column::OrganizationId.eq(organization_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|c| (c.id, c))
+        .collect::<HashMap<_, _>>();
+
+    let mut movements: HashMap<i32, Vec<stock_movement::Model>> = HashMap::new();
+    for m in StockMovement::find()
+        .inner_join(Item)
+        .filter(item::Column::OrganizationId.eq(organization_id))
+        .order_by_asc(stock_movement::Column::CreatedAt)
+        .order_by_asc(stock_movement::Column::Id)
+        .all(db)
+        .await?
+    {
+        movements.entry(m.item_id).or_default().push(m);
+    }
+
+    Ok((items, categories, movements))
+}
+
+#[get("/valuation?<method>")]
+pub async fn valuation(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    user: AuthenticatedUser,
+    method: Option<&str>,
+) -> Result<Json<ResponseValuation>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+    let method = CostMethod::from_str(method.unwrap_or_default());
+
+    let (items, categories, movements) = load(db, user.organization_id).await?;
+    let snapshot = snapshot(config, &items, &categories, &movements, method, None);
+
+    let mut response = ResponseValuation {
+        method: method.as_str(),
+        quantity: snapshot.quantity,
+        value: snapshot.value,
+        categories: vec![],
+        locations: vec![],
+    };
+
+    let mut rows = snapshot.by_category.into_iter().collect::<Vec<_>>();
+    rows.sort_by_key(|(id, _)| *id);
+
+    for (id, (quantity, value)) in rows {
+        if let Some(c) = categories.get(&id) {
+            response.categories.push(ResponseCategoryValue {
+                uuid: Some(c.uuid.to_owned()),
+                name: c.name.to_owned(),
+                quantity,
+                value,
+            });
+        }
+    }
+
+    response.categories.push(ResponseCategoryValue {
+        uuid: None,
+        name: "Uncategorized".to_string(),
+        quantity: snapshot.uncategorized.0,
+        value: snapshot.uncategorized.1,
+    });
+
+    let locations = Location::find()
+        .filter(location::Column::OrganizationId.eq(user.organization_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|l| (l.id, l))
+        .collect::<HashMap<_, _>>();
+
+    let mut rows = snapshot.by_location.into_iter().collect::<Vec<_>>();
+    rows.sort_by_key(|(id, _)| *id);
+
+    for (id, (quantity, value)) in rows {
+        if let Some(l) = locations.get(&id) {
+            response.locations.push(ResponseLocationValue {
+                uuid: Some(l.uuid.to_owned()),
+                name: l.name.to_owned(),
+                quantity,
+                value,
+            });
+        }
+    }
+
+    response.locations.push(ResponseLocationValue {
+        uuid: None,
+        name: "No location".to_string(),
+        quantity: snapshot.unlocated.0,
+        value: snapshot.unlocated.1,
+    });
+
+    Ok(Json(response))
+}
+
+#[get("/valuation/history?<method>&<days>&<points>")]
+pub async fn valuation_history(
+    db: &State<DatabaseConnection>,
+    config: &State<Config>,
+    user: AuthenticatedUser,
+    method: Option<&str>,
+    days: Option<u32>,
+    points: Option<u32>,
+) -> Result<Json<ResponseValuationHistory>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+    let method = CostMethod::from_str(method.unwrap_or_default());
+    let days = days.unwrap_or(HISTORY_DAYS).clamp(1, 366);
+    let points = points.unwrap_or(HISTORY_POINTS).clamp(1, 120);
+
+    let (items, categories, movements) = load(db, user.organization_id).await?;
+    let now = DateTimeUtc::from(SystemTime::now());
+
+    let mut results = vec![];
+    for i in (0..points).rev() {
+        let at = now - chrono::Duration::days(i64::from(days * i));
+        let snapshot = snapshot(config, &items, &categories, &movements, method, Some(at));
+
+        results.push(ResponseValuationPoint {
+            date: at.to_rfc3339(),
+            quantity: snapshot.quantity,
+            value: snapshot.value,
+        });
+    }
+
+    Ok(Json(ResponseValuationHistory {
+        method: method.as_str(),
+        results,
+    }))
+}
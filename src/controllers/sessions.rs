@@ -0,0 +1,262 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{
+    convert::Infallible,
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+
+use rocket::{
+    http::Status,
+    request::{self, FromRequest, Request},
+    serde::{json::Json, Serialize},
+    *,
+};
+use sea_orm::{prelude::DateTimeUtc, sea_query::Expr, *};
+use uuid::Uuid;
+
+use super::{
+    admin_required,
+    auth::{AuthenticatedUser, Role},
+    not_found, success, ErrorResponder, Response, ResponseList,
+};
+use crate::{
+    entities::{prelude::*, session},
+    throttle, Config,
+};
+
+/// How stale `last_seen_at` may get before a request updates it, so most
+/// requests do not write.
+const LAST_SEEN_INTERVAL: u64 = 60;
+
+/// The device a sign-in comes from.
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<IpAddr>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientInfo {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(ClientInfo {
+            user_agent: req
+                .headers()
+                .get_one("User-Agent")
+                .map(|ua| ua.chars().take(255).collect()),
+            ip: throttle::client_ip(req, req.rocket().state::<Config>().unwrap().trust_proxy),
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseSession {
+    uuid: String,
+    user_agent: Option<String>,
+    ip: Option<String>,
+    current: bool,
+    last_seen_at: String,
+    expires_at: String,
+    created_at: String,
+}
+
+impl Session {
+    /// Records a sign-in lasting `ttl` seconds, and forgets the user's
+    /// expired sessions.
+    pub async fn start(
+        db: &DatabaseConnection,
+        user_id: i32,
+        client: &ClientInfo,
+        ttl: u64,
+    ) -> Result<session::Model, DbErr> {
+        let now = SystemTime::now();
+
+        Session::delete_many()
+            .filter(session::Column::UserId.eq(user_id))
+            .filter(session::Column::ExpiresAt.lt(DateTimeUtc::from(now)))
+            .exec(db)
+            .await?;
+
+        session::ActiveModel {
+            uuid: Set(Uuid::new_v4().to_string()),
+            user_id: Set(user_id),
+            user_agent: Set(client.user_agent.to_owned()),
+            ip: Set(client.ip.map(|ip| ip.to_string())),
+            expires_at: Set(DateTimeUtc::from(now + Duration::from_secs(ttl))),
+            last_seen_at: Set(DateTimeUtc::from(now)),
+            created_at: Set(DateTimeUtc::from(now)),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
+
+    /// Keeps the session alive for a newly issued token lasting `ttl` seconds.
+    pub async fn extend(
+        db: &DatabaseConnection,
+        session: &session::Model,
+        ttl: u64,
+    ) -> Result<(), DbErr> {
+        let now = SystemTime::now();
+
+        Session::update_many()
+            .col_expr(
+                session::Column::ExpiresAt,
+                Expr::value(DateTimeUtc::from(now + Duration::from_secs(ttl))),
+            )
+            .col_expr(
+                session::Column::LastSeenAt,
+                Expr::value(DateTimeUtc::from(now)),
+            )
+            .filter(session::Column::Id.eq(session.id))
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// The user's session, unless it was revoked or has expired.
+    pub async fn authenticate(
+        db: &DatabaseConnection,
+        user_id: i32,
+        uuid: &str,
+    ) -> Result<Option<session::Model>, DbErr> {
+        let now = SystemTime::now();
+
+        let session = match Session::find()
+            .filter(session::Column::Uuid.eq(uuid))
+            .filter(session::Column::UserId.eq(user_id))
+            .filter(session::Column::ExpiresAt.gt(DateTimeUtc::from(now)))
+            .one(db)
+            .await?
+        {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+
+        if session.last_seen_at < DateTimeUtc::from(now - Duration::from_secs(LAST_SEEN_INTERVAL)) {
+            Session::update_many()
+                .col_expr(
+                    session::Column::LastSeenAt,
+                    Expr::value(DateTimeUtc::from(now)),
+                )
+                .filter(session::Column::Id.eq(session.id))
+                .exec(db)
+                .await?;
+        }
+
+        Ok(Some(session))
+    }
+
+    /// Ends every session of the user, signing them out everywhere.
+    pub async fn revoke_all(db: &DatabaseConnection, user_id: i32) -> Result<(), DbErr> {
+        Session::delete_many()
+            .filter(session::Column::UserId.eq(user_id))
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Ends every session of the user except `current`, signing them out
+    /// everywhere else.
+    pub async fn revoke_others(
+        db: &DatabaseConnection,
+        user_id: i32,
+        current: Option<i32>,
+    ) -> Result<(), DbErr> {
+        let mut delete = Session::delete_many().filter(session::Column::UserId.eq(user_id));
+        if let Some(id) = current {
+            delete = delete.filter(session::Column::Id.ne(id));
+        }
+
+        delete.exec(db).await?;
+
+        Ok(())
+    }
+}
+
+/// The user's active sessions, most recently used first. `current` marks the
+/// one making the request.
+#[get("/sessions")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<Json<ResponseList<ResponseSession>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let sessions = Session::find()
+        .filter(session::Column::UserId.eq(user.id))
+        .filter(session::Column::ExpiresAt.gt(DateTimeUtc::from(SystemTime::now())))
+        .order_by_desc(session::Column::LastSeenAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|s| ResponseSession {
+            current: user.session == Some(s.id),
+            uuid: s.uuid,
+            user_agent: s.user_agent,
+            ip: s.ip,
+            last_seen_at: s.last_seen_at.to_rfc3339(),
+            expires_at: s.expires_at.to_rfc3339(),
+            created_at: s.created_at.to_rfc3339(),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: sessions.len(),
+        results: sessions,
+    }))
+}
+
+/// Ends one of the user's sessions. Ending the current one signs out.
+#[delete("/sessions/<uuid>")]
+pub async fn delete(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let result = Session::delete_many()
+        .filter(session::Column::UserId.eq(user.id))
+        .filter(session::Column::Uuid.eq(uuid))
+        .exec(db)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(not_found());
+    }
+
+    success(Status::Ok)
+}
+
+/// Signs a member out everywhere.
+#[delete("/<user_uuid>/sessions")]
+pub async fn user_delete(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    user_uuid: &str,
+) -> Response {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let member = match User::from_uuid(db, user.organization_id, user_uuid).await? {
+        Some((u, _)) => u,
+        None => return Err(not_found()),
+    };
+
+    Session::revoke_all(db, member.id).await?;
+
+    success(Status::Ok)
+}
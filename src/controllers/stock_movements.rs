@@ -0,0 +1,110 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use rocket::{
+    serde::{json::Json, Serialize},
+    *,
+};
+use sea_orm::{prelude::Decimal, *};
+use uuid::Uuid;
+
+use super::{auth::AuthenticatedUser, not_found, ErrorResponder, ResponseList};
+use crate::entities::{item, prelude::*, stock_movement};
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseStockMovement {
+    uuid: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    quantity: Decimal,
+    unit_cost: Option<Decimal>,
+    reason: String,
+    created_at: String,
+}
+
+impl From<&stock_movement::Model> for ResponseStockMovement {
+    fn from(movement: &stock_movement::Model) -> ResponseStockMovement {
+        ResponseStockMovement {
+            uuid: movement.uuid.to_owned(),
+            quantity: movement.quantity,
+            unit_cost: movement.unit_cost,
+            reason: movement.reason.to_owned(),
+            created_at: movement.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl StockMovement {
+    /// Records a change in an item's quantity. Positive quantities are receipts,
+    /// negative quantities are issues. Zero-quantity movements are skipped.
+    pub async fn record<C: ConnectionTrait>(
+        db: &C,
+        item_id: i32,
+        user_id: i32,
+        quantity: Decimal,
+        unit_cost: Option<Decimal>,
+        reason: &str,
+    ) -> Result<(), DbErr> {
+        if quantity.is_zero() {
+            return Ok(());
+        }
+
+        StockMovement::insert(stock_movement::ActiveModel {
+            uuid: Set(Uuid::new_v4().to_string()),
+            item_id: Set(item_id),
+            user_id: Set(user_id),
+            quantity: Set(quantity),
+            unit_cost: Set(unit_cost),
+            reason: Set(reason.to_owned()),
+            ..Default::default()
+        })
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_for_item<C: ConnectionTrait>(
+        db: &C,
+        item: &item::Model,
+    ) -> Result<(), DbErr> {
+        StockMovement::delete_many()
+            .filter(stock_movement::Column::ItemId.eq(item.id))
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[get("/<uuid>/movements")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Result<Json<ResponseList<ResponseStockMovement>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let item = match Item::from_uuid(db, user.organization_id, uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let movements = item
+        .find_related(StockMovement)
+        .order_by_desc(stock_movement::Column::CreatedAt)
+        .all(db)
+        .await?
+        .iter()
+        .map(ResponseStockMovement::from)
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: movements.len(),
+        results: movements,
+    }))
+}
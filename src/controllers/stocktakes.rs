@@ -0,0 +1,466 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{collections::HashMap, time::SystemTime};
+
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{
+    prelude::{DateTimeUtc, Decimal},
+    *,
+};
+use uuid::Uuid;
+
+use super::{
+    admin_required,
+    auth::{AuthenticatedUser, Role},
+    categories::ResponseCategory,
+    error_response,
+    items::{ItemMode, ResponseItem},
+    locations::ResponseLocation,
+    not_found, success, ErrorResponder, Response, ResponseList,
+};
+use crate::{
+    entities::{item, prelude::*, stocktake, stocktake_count},
+    units,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StocktakeStatus {
+    Open,
+    Approved,
+    Cancelled,
+}
+
+impl StocktakeStatus {
+    pub fn from_str(status: &str) -> StocktakeStatus {
+        match status {
+            "approved" => StocktakeStatus::Approved,
+            "cancelled" => StocktakeStatus::Cancelled,
+            _ => StocktakeStatus::Open,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StocktakeStatus::Open => "open",
+            StocktakeStatus::Approved => "approved",
+            StocktakeStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestStocktake {
+    category_uuid: Option<String>,
+    location_uuid: Option<String>,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestCount {
+    item_uuid: String,
+    quantity: Decimal,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestCounts {
+    counts: Vec<RequestCount>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseStocktakeLine {
+    item: ResponseItem,
+    #[serde(with = "rust_decimal::serde::float")]
+    expected: Decimal,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    counted: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    variance: Option<Decimal>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseStocktake {
+    uuid: String,
+    status: String,
+    notes: Option<String>,
+    category: Option<ResponseCategory>,
+    location: Option<ResponseLocation>,
+    approved_at: Option<String>,
+    lines: Option<Vec<ResponseStocktakeLine>>,
+}
+
+impl From<&stocktake::Model> for ResponseStocktake {
+    fn from(stocktake: &stocktake::Model) -> ResponseStocktake {
+        ResponseStocktake {
+            uuid: stocktake.uuid.to_owned(),
+            status: stocktake.status.to_owned(),
+            notes: stocktake.notes.to_owned(),
+            category: None,
+            location: None,
+            approved_at: stocktake.approved_at.map(|t| t.to_rfc3339()),
+            lines: None,
+        }
+    }
+}
+
+impl Stocktake {
+    pub async fn from_uuid(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        uuid: &str,
+    ) -> Result<Option<stocktake::Model>, DbErr> {
+        Stocktake::find()
+            .filter(stocktake::Column::OrganizationId.eq(organization_id))
+            .filter(stocktake::Column::Uuid.eq(uuid))
+            .one(db)
+            .await
+    }
+
+    /// Bulk items within the session's category subtree and at its location,
+    /// or every bulk item when unscoped. Serialized items are counted by their
+    /// assets instead.
+    pub async fn items(
+        db: &DatabaseConnection,
+        stocktake: &stocktake::Model,
+    ) -> Result<Vec<item::Model>, DbErr> {
+        let mut query = Item::find()
+            .filter(item::Column::OrganizationId.eq(stocktake.organization_id))
+            .filter(item::Column::Mode.ne(ItemMode::Serialized.as_str()))
+            .order_by_asc(item::Column::Name);
+
+        if let Some(category_id) = stocktake.category_id {
+            let ids = Category::subtree_ids(db, stocktake.organization_id, category_id).await?;
+            query = query.filter(item::Column::CategoryId.is_in(ids));
+        }
+
+        if let Some(location_id) = stocktake.location_id {
+            query = query.filter(item::Column::LocationId.eq(location_id));
+        }
+
+        query.all(db).await
+    }
+}
+
+fn not_open(stocktake: &stocktake::Model) -> ErrorResponder {
+    error_response(
+        Status::Conflict,
+        format!("Stocktake is {}", stocktake.status),
+    )
+}
+
+#[get("/")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<Json<ResponseList<ResponseStocktake>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let stocktakes = Stocktake::find()
+        .filter(stocktake::Column::OrganizationId.eq(user.organization_id))
+        .order_by_desc(stocktake::Column::UpdatedAt)
+        .all(db)
+        .await?
+        .iter()
+        .map(ResponseStocktake::from)
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: stocktakes.len(),
+        results: stocktakes,
+    }))
+}
+
+#[post("/", data = "<req_stocktake>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    req_stocktake: Json<RequestStocktake>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let mut category: Option<i32> = None;
+    if let Some(category_uuid) = req_stocktake.category_uuid.to_owned() {
+        match Category::from_uuid(db, user.organization_id, category_uuid.as_str()).await? {
+            Some(c) => category = Some(c.id),
+            None => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    "Unknown category".to_string(),
+                ))
+            }
+        }
+    }
+
+    let mut location: Option<i32> = None;
+    if let Some(location_uuid) = req_stocktake.location_uuid.to_owned() {
+        match Location::from_uuid(db, user.organization_id, location_uuid.as_str()).await? {
+            Some(l) => location = Some(l.id),
+            None => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    "Unknown location".to_string(),
+                ))
+            }
+        }
+    }
+
+    Stocktake::insert(stocktake::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        organization_id: Set(user.organization_id),
+        user_id: Set(user.id),
+        category_id: Set(category),
+        location_id: Set(location),
+        status: Set(StocktakeStatus::Open.as_str().to_string()),
+        notes: Set(req_stocktake.notes.to_owned()),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    success(Status::Created)
+}
+
+#[get("/<uuid>")]
+pub async fn show(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Result<Json<ResponseStocktake>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let stocktake = match Stocktake::from_uuid(db, user.organization_id, uuid).await? {
+        Some(s) => s,
+        None => return Err(not_found()),
+    };
+
+    let mut response = ResponseStocktake::from(&stocktake);
+
+    if let Some(category_id) = stocktake.category_id {
+        response.category = Category::find_by_id(category_id)
+            .one(db)
+            .await?
+            .as_ref()
+            .map(ResponseCategory::from);
+    }
+
+    response.location = stocktake
+        .find_related(Location)
+        .one(db)
+        .await?
+        .as_ref()
+        .map(ResponseLocation::from);
+
+    let counts = stocktake
+        .find_related(StocktakeCount)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|c| (c.item_id, c))
+        .collect::<HashMap<_, _>>();
+
+    let mut lines = vec![];
+    for item in Stocktake::items(db, &stocktake).await? {
+        let count = counts.get(&item.id);
+
+        // Once approved the item quantity already reflects the count, so the
+        // variance is reported against the quantity recorded at approval.
+        let expected = count
+            .and_then(|c| c.expected_quantity)
+            .unwrap_or(item.quantity);
+        let counted = count.map(|c| c.counted_quantity);
+
+        lines.push(ResponseStocktakeLine {
+            item: ResponseItem::from(&item),
+            expected,
+            counted,
+            variance: counted.map(|c| c - expected),
+        });
+    }
+
+    response.lines = Some(lines);
+
+    Ok(Json(response))
+}
+
+#[put("/<uuid>/counts", data = "<req_counts>")]
+pub async fn count(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_counts: Json<RequestCounts>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let stocktake = match Stocktake::from_uuid(db, user.organization_id, uuid).await? {
+        Some(s) => s,
+        None => return Err(not_found()),
+    };
+
+    if StocktakeStatus::from_str(&stocktake.status) != StocktakeStatus::Open {
+        return Err(not_open(&stocktake));
+    }
+
+    let items = Stocktake::items(db, &stocktake)
+        .await?
+        .into_iter()
+        .map(|i| (i.uuid.to_owned(), i))
+        .collect::<HashMap<_, _>>();
+
+    let txn = db.begin().await?;
+
+    for req_count in &req_counts.counts {
+        let item = match items.get(&req_count.item_uuid) {
+            Some(i) => i,
+            None => {
+                return Err(error_response(
+                    Status::UnprocessableEntity,
+                    format!("Item {} is not part of this stocktake", req_count.item_uuid),
+                ))
+            }
+        };
+
+        if !units::is_valid_quantity(req_count.quantity, &item.unit) {
+            return Err(error_response(
+                Status::UnprocessableEntity,
+                format!(
+                    "{} is not a valid quantity of {}",
+                    req_count.quantity, item.unit
+                ),
+            ));
+        }
+
+        let existing = StocktakeCount::find()
+            .filter(stocktake_count::Column::StocktakeId.eq(stocktake.id))
+            .filter(stocktake_count::Column::ItemId.eq(item.id))
+            .one(&txn)
+            .await?;
+
+        match existing {
+            Some(c) => {
+                let mut c: stocktake_count::ActiveModel = c.into();
+                c.user_id = Set(user.id);
+                c.counted_quantity = Set(req_count.quantity);
+                c.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+                c.update(&txn).await?;
+            }
+            None => {
+                StocktakeCount::insert(stocktake_count::ActiveModel {
+                    stocktake_id: Set(stocktake.id),
+                    item_id: Set(item.id),
+                    user_id: Set(user.id),
+                    counted_quantity: Set(req_count.quantity),
+                    ..Default::default()
+                })
+                .exec(&txn)
+                .await?;
+            }
+        }
+    }
+
+    txn.commit().await?;
+
+    success(Status::Ok)
+}
+
+#[post("/<uuid>/approve")]
+pub async fn approve(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Response {
+    if user.role != Role::Admin {
+        return Err(admin_required());
+    }
+
+    let db = db as &DatabaseConnection;
+
+    let stocktake = match Stocktake::from_uuid(db, user.organization_id, uuid).await? {
+        Some(s) => s,
+        None => return Err(not_found()),
+    };
+
+    if StocktakeStatus::from_str(&stocktake.status) != StocktakeStatus::Open {
+        return Err(not_open(&stocktake));
+    }
+
+    let txn = db.begin().await?;
+    let now = DateTimeUtc::from(SystemTime::now());
+
+    for count in stocktake.find_related(StocktakeCount).all(&txn).await? {
+        // Items made serialized since they were counted keep their assets' count.
+        let item = match Item::find_by_id(count.item_id).one(&txn).await? {
+            Some(i) if ItemMode::from_str(&i.mode) == ItemMode::Bulk => i,
+            _ => continue,
+        };
+
+        StockMovement::record(
+            &txn,
+            item.id,
+            user.id,
+            count.counted_quantity - item.quantity,
+            item.unit_cost,
+            "stocktake",
+        )
+        .await?;
+
+        let expected = item.quantity;
+
+        let mut item: item::ActiveModel = item.into();
+        item.quantity = Set(count.counted_quantity);
+        item.updated_at = Set(now);
+        item.update(&txn).await?;
+
+        let mut count: stocktake_count::ActiveModel = count.into();
+        count.expected_quantity = Set(Some(expected));
+        count.update(&txn).await?;
+    }
+
+    let mut stocktake: stocktake::ActiveModel = stocktake.into();
+    stocktake.status = Set(StocktakeStatus::Approved.as_str().to_string());
+    stocktake.approved_by = Set(Some(user.id));
+    stocktake.approved_at = Set(Some(now));
+    stocktake.updated_at = Set(now);
+    stocktake.update(&txn).await?;
+
+    txn.commit().await?;
+
+    success(Status::Ok)
+}
+
+#[post("/<uuid>/cancel")]
+pub async fn cancel(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let stocktake = match Stocktake::from_uuid(db, user.organization_id, uuid).await? {
+        Some(s) => s,
+        None => return Err(not_found()),
+    };
+
+    if StocktakeStatus::from_str(&stocktake.status) != StocktakeStatus::Open {
+        return Err(not_open(&stocktake));
+    }
+
+    let mut stocktake: stocktake::ActiveModel = stocktake.into();
+    stocktake.status = Set(StocktakeStatus::Cancelled.as_str().to_string());
+    stocktake.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+    stocktake.update(db).await?;
+
+    success(Status::Ok)
+}
@@ -0,0 +1,305 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::SystemTime;
+
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{
+    prelude::{DateTimeUtc, Decimal},
+    *,
+};
+use uuid::Uuid;
+
+use super::{
+    auth::AuthenticatedUser, items::ResponseItem, not_found, success, ErrorResponder, Response,
+    ResponseList,
+};
+use crate::entities::{prelude::*, supplier, supplier_item};
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestSupplier<'r> {
+    name: &'r str,
+    email: Option<String>,
+    phone: Option<String>,
+    address: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestSupplierItem<'r> {
+    item_uuid: &'r str,
+    sku: Option<String>,
+    lead_time_days: Option<u32>,
+    unit_cost: Option<Decimal>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseSupplierItem {
+    item: ResponseItem,
+    sku: Option<String>,
+    lead_time_days: Option<u32>,
+    unit_cost: Option<Decimal>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseSupplier {
+    uuid: String,
+    name: String,
+    email: Option<String>,
+    phone: Option<String>,
+    address: Option<String>,
+    items: Option<Vec<ResponseSupplierItem>>,
+}
+
+impl From<&supplier::Model> for ResponseSupplier {
+    fn from(supplier: &supplier::Model) -> ResponseSupplier {
+        ResponseSupplier {
+            uuid: supplier.uuid.to_owned(),
+            name: supplier.name.to_owned(),
+            email: supplier.email.to_owned(),
+            phone: supplier.phone.to_owned(),
+            address: supplier.address.to_owned(),
+            items: None,
+        }
+    }
+}
+
+impl Supplier {
+    pub async fn from_uuid(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        uuid: &str,
+    ) -> Result<Option<supplier::Model>, DbErr> {
+        Supplier::find()
+            .filter(supplier::Column::OrganizationId.eq(organization_id))
+            .filter(supplier::Column::Uuid.eq(uuid))
+            .one(db)
+            .await
+    }
+}
+
+#[get("/")]
+pub async fn index(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<Json<ResponseList<ResponseSupplier>>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let suppliers = Supplier::find()
+        .filter(supplier::Column::OrganizationId.eq(user.organization_id))
+        .order_by_asc(supplier::Column::Name)
+        .all(db)
+        .await?
+        .iter()
+        .map(ResponseSupplier::from)
+        .collect::<Vec<_>>();
+
+    Ok(Json(ResponseList {
+        total: suppliers.len(),
+        results: suppliers,
+    }))
+}
+
+#[post("/", data = "<req_supplier>")]
+pub async fn store(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    req_supplier: Json<RequestSupplier<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    Supplier::insert(supplier::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        organization_id: Set(user.organization_id),
+        user_id: Set(user.id),
+        name: Set(req_supplier.name.to_owned()),
+        email: Set(req_supplier.email.to_owned()),
+        phone: Set(req_supplier.phone.to_owned()),
+        address: Set(req_supplier.address.to_owned()),
+        ..Default::default()
+    })
+    .exec(db)
+    .await?;
+
+    success(Status::Created)
+}
+
+#[get("/<uuid>")]
+pub async fn show(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Result<Json<ResponseSupplier>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let supplier = match Supplier::from_uuid(db, user.organization_id, uuid).await? {
+        Some(s) => s,
+        None => return Err(not_found()),
+    };
+
+    let mut response = ResponseSupplier::from(&supplier);
+
+    response.items = Some(
+        supplier
+            .find_related(SupplierItem)
+            .find_also_related(Item)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|(link, item)| {
+                item.map(|item| ResponseSupplierItem {
+                    item: ResponseItem::from(&item),
+                    sku: link.sku,
+                    lead_time_days: link.lead_time_days,
+                    unit_cost: link.unit_cost,
+                })
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    Ok(Json(response))
+}
+
+#[put("/<uuid>", data = "<req_supplier>")]
+pub async fn update(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_supplier: Json<RequestSupplier<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let mut supplier: supplier::ActiveModel =
+        match Supplier::from_uuid(db, user.organization_id, uuid).await? {
+            Some(s) => s.into(),
+            None => return Err(not_found()),
+        };
+
+    supplier.name = Set(req_supplier.name.to_owned());
+    supplier.email = Set(req_supplier.email.to_owned());
+    supplier.phone = Set(req_supplier.phone.to_owned());
+    supplier.address = Set(req_supplier.address.to_owned());
+
+    supplier.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+
+    supplier.update(db).await?;
+
+    success(Status::Ok)
+}
+
+#[delete("/<uuid>")]
+pub async fn delete(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let supplier = match Supplier::from_uuid(db, user.organization_id, uuid).await? {
+        Some(s) => s,
+        None => return Err(not_found()),
+    };
+
+    supplier.delete(db).await?;
+
+    success(Status::Ok)
+}
+
+#[put("/<uuid>/items", data = "<req_supplier_item>")]
+pub async fn link_item(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    req_supplier_item: Json<RequestSupplierItem<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let supplier = match Supplier::from_uuid(db, user.organization_id, uuid).await? {
+        Some(s) => s,
+        None => return Err(not_found()),
+    };
+
+    let item = match Item::from_uuid(db, user.organization_id, req_supplier_item.item_uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let existing = SupplierItem::find()
+        .filter(supplier_item::Column::SupplierId.eq(supplier.id))
+        .filter(supplier_item::Column::ItemId.eq(item.id))
+        .one(db)
+        .await?;
+
+    match existing {
+        Some(link) => {
+            let mut link: supplier_item::ActiveModel = link.into();
+
+            link.sku = Set(req_supplier_item.sku.to_owned());
+            link.lead_time_days = Set(req_supplier_item.lead_time_days);
+            link.unit_cost = Set(req_supplier_item.unit_cost);
+
+            link.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+
+            link.update(db).await?;
+
+            success(Status::Ok)
+        }
+        None => {
+            SupplierItem::insert(supplier_item::ActiveModel {
+                supplier_id: Set(supplier.id),
+                item_id: Set(item.id),
+                sku: Set(req_supplier_item.sku.to_owned()),
+                lead_time_days: Set(req_supplier_item.lead_time_days),
+                unit_cost: Set(req_supplier_item.unit_cost),
+                ..Default::default()
+            })
+            .exec(db)
+            .await?;
+
+            success(Status::Created)
+        }
+    }
+}
+
+#[delete("/<uuid>/items/<item_uuid>")]
+pub async fn unlink_item(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    uuid: &str,
+    item_uuid: &str,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let supplier = match Supplier::from_uuid(db, user.organization_id, uuid).await? {
+        Some(s) => s,
+        None => return Err(not_found()),
+    };
+
+    let item = match Item::from_uuid(db, user.organization_id, item_uuid).await? {
+        Some(i) => i,
+        None => return Err(not_found()),
+    };
+
+    let result = SupplierItem::delete_many()
+        .filter(supplier_item::Column::SupplierId.eq(supplier.id))
+        .filter(supplier_item::Column::ItemId.eq(item.id))
+        .exec(db)
+        .await?;
+
+    if result.rows_affected == 0 {
+        return Err(not_found());
+    }
+
+    success(Status::Ok)
+}
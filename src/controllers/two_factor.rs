@@ -0,0 +1,250 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::SystemTime;
+
+use bcrypt::verify;
+use rand::Rng;
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    *,
+};
+use sea_orm::{prelude::DateTimeUtc, sea_query::Expr, *};
+
+use super::{
+    auth::AuthenticatedUser, error_response, passwords::hash_token, success, ErrorResponder,
+    Response,
+};
+use crate::{
+    entities::{prelude::*, recovery_code, user},
+    totp,
+};
+
+const ISSUER: &str = "Iron Guard";
+const RECOVERY_CODES: usize = 10;
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestCode<'r> {
+    code: &'r str,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RequestDisable<'r> {
+    password: &'r str,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseEnrollment {
+    secret: String,
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseRecoveryCodes {
+    recovery_codes: Vec<String>,
+}
+
+impl RecoveryCode {
+    /// Replaces the user's recovery codes and returns the new ones. Only
+    /// their digests are kept.
+    async fn regenerate<C: ConnectionTrait>(db: &C, user_id: i32) -> Result<Vec<String>, DbErr> {
+        RecoveryCode::delete_many()
+            .filter(recovery_code::Column::UserId.eq(user_id))
+            .exec(db)
+            .await?;
+
+        let codes = (0..RECOVERY_CODES)
+            .map(|_| {
+                let code = hex::encode(rand::thread_rng().gen::<[u8; 5]>());
+                format!("{}-{}", &code[..5], &code[5..])
+            })
+            .collect::<Vec<_>>();
+
+        RecoveryCode::insert_many(codes.iter().map(|code| recovery_code::ActiveModel {
+            user_id: Set(user_id),
+            code_hash: Set(hash_token(code)),
+            ..Default::default()
+        }))
+        .exec(db)
+        .await?;
+
+        Ok(codes)
+    }
+}
+
+/// Checks a second factor for a user with 2FA enabled: a current TOTP code,
+/// or an unused recovery code, which is then used up.
+pub async fn check_code(
+    db: &DatabaseConnection,
+    user: &user::Model,
+    code: &str,
+) -> Result<bool, DbErr> {
+    let secret = match &user.totp_secret {
+        Some(s) if user.totp_enabled_at.is_some() => s,
+        _ => return Ok(false),
+    };
+
+    if let Some(step) = totp::verify(secret, code, totp::current_step(), user.totp_last_step) {
+        // Only the first use of a code counts, even against a racing request.
+        let claimed = User::update_many()
+            .col_expr(user::Column::TotpLastStep, Expr::value(step))
+            .filter(user::Column::Id.eq(user.id))
+            .filter(
+                Condition::any()
+                    .add(user::Column::TotpLastStep.is_null())
+                    .add(user::Column::TotpLastStep.lt(step)),
+            )
+            .exec(db)
+            .await?;
+
+        return Ok(claimed.rows_affected == 1);
+    }
+
+    let claimed = RecoveryCode::update_many()
+        .col_expr(
+            recovery_code::Column::UsedAt,
+            Expr::value(DateTimeUtc::from(SystemTime::now())),
+        )
+        .filter(recovery_code::Column::UserId.eq(user.id))
+        .filter(recovery_code::Column::CodeHash.eq(hash_token(code.trim())))
+        .filter(recovery_code::Column::UsedAt.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(claimed.rows_affected == 1)
+}
+
+fn invalid_code() -> ErrorResponder {
+    error_response(Status::UnprocessableEntity, "Invalid code".to_string())
+}
+
+/// Starts enrollment with a new secret. 2FA is enabled once a code from it
+/// is verified.
+#[post("/2fa")]
+pub async fn enroll(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+) -> Result<Json<ResponseEnrollment>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let existing = User::find_by_id(user.id).one(db).await?.unwrap();
+
+    if existing.totp_enabled_at.is_some() {
+        return Err(error_response(
+            Status::Conflict,
+            "Two-factor authentication is already enabled".to_string(),
+        ));
+    }
+
+    let secret = totp::generate_secret();
+    let uri = totp::provisioning_uri(&secret, ISSUER, &existing.email);
+
+    let mut u: user::ActiveModel = existing.into();
+    u.totp_secret = Set(Some(secret.to_owned()));
+    u.totp_last_step = Set(None);
+    u.update(db).await?;
+
+    Ok(Json(ResponseEnrollment { secret, uri }))
+}
+
+#[post("/2fa/verify", data = "<req_code>")]
+pub async fn confirm(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    req_code: Json<RequestCode<'_>>,
+) -> Result<Json<ResponseRecoveryCodes>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let existing = User::find_by_id(user.id).one(db).await?.unwrap();
+
+    let step = match (&existing.totp_secret, existing.totp_enabled_at) {
+        (Some(secret), None) => totp::verify(secret, req_code.code, totp::current_step(), None),
+        _ => {
+            return Err(error_response(
+                Status::Conflict,
+                "No two-factor enrollment in progress".to_string(),
+            ))
+        }
+    };
+
+    let step = match step {
+        Some(s) => s,
+        None => return Err(invalid_code()),
+    };
+
+    let txn = db.begin().await?;
+
+    let mut u: user::ActiveModel = existing.into();
+    u.totp_enabled_at = Set(Some(DateTimeUtc::from(SystemTime::now())));
+    u.totp_last_step = Set(Some(step));
+    u.update(&txn).await?;
+
+    let recovery_codes = RecoveryCode::regenerate(&txn, user.id).await?;
+
+    txn.commit().await?;
+
+    Ok(Json(ResponseRecoveryCodes { recovery_codes }))
+}
+
+#[post("/2fa/recovery-codes", data = "<req_code>")]
+pub async fn recovery_codes(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    req_code: Json<RequestCode<'_>>,
+) -> Result<Json<ResponseRecoveryCodes>, ErrorResponder> {
+    let db = db as &DatabaseConnection;
+
+    let existing = User::find_by_id(user.id).one(db).await?.unwrap();
+
+    if !check_code(db, &existing, req_code.code).await? {
+        return Err(invalid_code());
+    }
+
+    let recovery_codes = RecoveryCode::regenerate(db, user.id).await?;
+
+    Ok(Json(ResponseRecoveryCodes { recovery_codes }))
+}
+
+#[delete("/2fa", data = "<req_disable>")]
+pub async fn disable(
+    db: &State<DatabaseConnection>,
+    user: AuthenticatedUser,
+    req_disable: Json<RequestDisable<'_>>,
+) -> Response {
+    let db = db as &DatabaseConnection;
+
+    let existing = User::find_by_id(user.id).one(db).await?.unwrap();
+
+    if !verify(req_disable.password, &existing.password).unwrap() {
+        return Err(error_response(
+            Status::Forbidden,
+            "Current password is incorrect".to_string(),
+        ));
+    }
+
+    let txn = db.begin().await?;
+
+    RecoveryCode::delete_many()
+        .filter(recovery_code::Column::UserId.eq(user.id))
+        .exec(&txn)
+        .await?;
+
+    let mut u: user::ActiveModel = existing.into();
+    u.totp_secret = Set(None);
+    u.totp_enabled_at = Set(None);
+    u.totp_last_step = Set(None);
+    u.update(&txn).await?;
+
+    txn.commit().await?;
+
+    success(Status::Ok)
+}
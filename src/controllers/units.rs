@@ -0,0 +1,44 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use rocket::{
+    serde::{json::Json, Serialize},
+    *,
+};
+use sea_orm::prelude::Decimal;
+
+use super::{auth::AuthenticatedUser, ResponseList};
+use crate::units::{Unit, UNITS};
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ResponseUnit {
+    code: &'static str,
+    dimension: &'static str,
+    #[serde(with = "rust_decimal::serde::float")]
+    factor: Decimal,
+}
+
+impl From<&Unit> for ResponseUnit {
+    fn from(unit: &Unit) -> ResponseUnit {
+        ResponseUnit {
+            code: unit.code,
+            dimension: unit.dimension.as_str(),
+            factor: unit.factor(),
+        }
+    }
+}
+
+#[get("/")]
+pub async fn index(_user: AuthenticatedUser) -> Json<ResponseList<ResponseUnit>> {
+    let units = UNITS.iter().map(ResponseUnit::from).collect::<Vec<_>>();
+
+    Json(ResponseList {
+        total: units.len(),
+        results: units,
+    })
+}
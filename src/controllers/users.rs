@@ -18,9 +18,16 @@ use uuid::Uuid;
 use super::{
     admin_required,
     auth::{AuthenticatedUser, Role},
-    not_found, success, ErrorResponder, ResponseList, Response,
+    error_response,
+    groups::ResponseGroup,
+    not_found,
+    passwords::random_token,
+    precondition_failed, success, ErrorResponder, ResponseList, Response,
+};
+use crate::{
+    entities::{organization_user, prelude::*, user},
+    etag::{etag, Preconditions, Tagged},
 };
-use crate::entities::{prelude::*, user};
 
 #[derive(Deserialize)]
 #[serde(crate = "rocket::serde")]
@@ -29,6 +36,7 @@ pub struct RequestUser<'r> {
     firstname: &'r str,
     lastname: &'r str,
     email: &'r str,
+    #[serde(default)]
     password: &'r str,
 }
 
@@ -40,6 +48,8 @@ pub struct ResponseUser {
     firstname: String,
     lastname: String,
     email: String,
+    two_factor: bool,
+    pub groups: Option<Vec<ResponseGroup>>,
 }
 
 impl From<user::Model> for ResponseUser {
@@ -50,23 +60,49 @@ impl From<user::Model> for ResponseUser {
             firstname: user.firstname,
             lastname: user.lastname,
             email: user.email,
+            two_factor: user.totp_enabled_at.is_some(),
+            groups: None,
+        }
+    }
+}
+
+/// A member of an organization, reported with their role there.
+impl From<(user::Model, Option<organization_user::Model>)> for ResponseUser {
+    fn from((user, membership): (user::Model, Option<organization_user::Model>)) -> ResponseUser {
+        let mut response = ResponseUser::from(user);
+        if let Some(m) = membership {
+            response.role = m.role;
         }
+
+        response
     }
 }
 
 impl User {
+    /// Users who are members of the organization, with their memberships.
+    pub fn members(organization_id: i32) -> SelectTwo<user::Entity, organization_user::Entity> {
+        User::find()
+            .find_also_related(OrganizationUser)
+            .filter(organization_user::Column::OrganizationId.eq(organization_id))
+    }
+
     pub async fn from_uuid(
         db: &DatabaseConnection,
+        organization_id: i32,
         uuid: &str,
-    ) -> Result<Option<user::Model>, DbErr> {
-        User::find()
+    ) -> Result<Option<(user::Model, Option<organization_user::Model>)>, DbErr> {
+        User::members(organization_id)
             .filter(user::Column::Uuid.eq(uuid))
             .one(db)
             .await
     }
 
-    pub async fn latest(db: &DatabaseConnection, count: u64) -> Result<Vec<ResponseUser>, DbErr> {
-        Ok(User::find()
+    pub async fn latest(
+        db: &DatabaseConnection,
+        organization_id: i32,
+        count: u64,
+    ) -> Result<Vec<ResponseUser>, DbErr> {
+        Ok(User::members(organization_id)
             .order_by_desc(user::Column::UpdatedAt)
             .limit(count)
             .all(db)
@@ -75,6 +111,29 @@ impl User {
             .map(ResponseUser::from)
             .collect::<Vec<_>>())
     }
+
+    /// Creates an account for a user from an external identity source. Its
+    /// password is random and unknown, so it signs in through that source.
+    pub async fn provision(
+        db: &DatabaseConnection,
+        email: &str,
+        firstname: &str,
+        lastname: &str,
+        oidc_subject: Option<String>,
+    ) -> Result<user::Model, DbErr> {
+        user::ActiveModel {
+            uuid: Set(Uuid::new_v4().to_string()),
+            role: Set(Role::User.as_str().to_string()),
+            firstname: Set(firstname.to_string()),
+            lastname: Set(lastname.to_string()),
+            email: Set(email.to_string()),
+            password: Set(hash(random_token(), DEFAULT_COST).unwrap()),
+            oidc_subject: Set(oidc_subject),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
 }
 
 #[get("/")]
@@ -88,12 +147,19 @@ pub async fn index(
 
     let db = db as &DatabaseConnection;
 
-    let users = User::find()
+    let mut groups = Group::by_user(db, user.organization_id).await?;
+
+    let users = User::members(user.organization_id)
         .order_by_desc(user::Column::UpdatedAt)
         .all(db)
         .await?
         .into_iter()
-        .map(ResponseUser::from)
+        .map(|(u, membership)| {
+            let user_groups = groups.remove(&u.id).unwrap_or_default();
+            let mut response = ResponseUser::from((u, membership));
+            response.groups = Some(user_groups);
+            response
+        })
         .collect::<Vec<_>>();
 
     Ok(Json(ResponseList {
@@ -102,6 +168,8 @@ pub async fn index(
     }))
 }
 
+/// Creates an account in the active organization. Existing accounts are
+/// invited instead, so that their owner agrees to join.
 #[post("/", data = "<req_user>")]
 pub async fn store(
     db: &State<DatabaseConnection>,
@@ -114,18 +182,59 @@ pub async fn store(
 
     let db = db as &DatabaseConnection;
 
-    User::insert(user::ActiveModel {
+    let existing = User::find()
+        .filter(user::Column::Email.eq(req_user.email))
+        .one(db)
+        .await?;
+
+    if existing.is_some() {
+        return Err(error_response(
+            Status::Conflict,
+            format!(
+                "{} already has an account. Invite them instead.",
+                req_user.email
+            ),
+        ));
+    }
+
+    if req_user.password.is_empty() {
+        return Err(error_response(
+            Status::UnprocessableEntity,
+            "Password is required".to_string(),
+        ));
+    }
+
+    let password = match hash(req_user.password, DEFAULT_COST) {
+        Ok(p) => p,
+        Err(err) => return Err(error_response(Status::InternalServerError, err.to_string())),
+    };
+
+    let txn = db.begin().await?;
+
+    let user_id = User::insert(user::ActiveModel {
         uuid: Set(Uuid::new_v4().to_string()),
-        role: Set(req_user.role.to_owned()),
+        role: Set(Role::from_str(req_user.role).as_str().to_string()),
         firstname: Set(req_user.firstname.to_owned()),
         lastname: Set(req_user.lastname.to_owned()),
         email: Set(req_user.email.to_owned()),
-        password: Set(hash(req_user.password, DEFAULT_COST).unwrap()),
+        password: Set(password),
         ..Default::default()
     })
-    .exec(db)
+    .exec(&txn)
+    .await?
+    .last_insert_id;
+
+    OrganizationUser::insert(organization_user::ActiveModel {
+        organization_id: Set(user.organization_id),
+        user_id: Set(user_id),
+        role: Set(Role::from_str(req_user.role).as_str().to_string()),
+        ..Default::default()
+    })
+    .exec(&txn)
     .await?;
 
+    txn.commit().await?;
+
     success(Status::Created)
 }
 
@@ -133,26 +242,38 @@ pub async fn store(
 pub async fn show(
     db: &State<DatabaseConnection>,
     user: AuthenticatedUser,
+    preconditions: Preconditions,
     uuid: &str,
-) -> Result<Json<ResponseUser>, ErrorResponder> {
+) -> Result<Tagged<Json<ResponseUser>>, ErrorResponder> {
     if user.role != Role::Admin {
         return Err(admin_required());
     }
 
     let db = db as &DatabaseConnection;
 
-    let user = match User::from_uuid(db, uuid).await? {
-        Some(u) => u,
+    let member = match User::from_uuid(db, user.organization_id, uuid).await? {
+        Some(m) => m,
         None => return Err(not_found()),
     };
 
-    Ok(Json(ResponseUser::from(user)))
+    let etag = etag(member.0.version);
+    if preconditions.not_modified(&etag) {
+        return Ok(Tagged::not_modified(etag));
+    }
+
+    let groups = Group::for_user(db, user.organization_id, member.0.id).await?;
+
+    let mut response = ResponseUser::from(member);
+    response.groups = Some(groups);
+
+    Ok(Tagged::new(Json(response), etag))
 }
 
 #[put("/<uuid>", data = "<req_user>")]
 pub async fn update(
     db: &State<DatabaseConnection>,
     user: AuthenticatedUser,
+    preconditions: Preconditions,
     uuid: &str,
     req_user: Json<RequestUser<'_>>,
 ) -> Response {
@@ -162,31 +283,74 @@ pub async fn update(
 
     let db = db as &DatabaseConnection;
 
-    let mut user: user::ActiveModel = match User::from_uuid(db, uuid).await? {
-        Some(u) => u.into(),
-        None => return Err(not_found()),
+    let (member, membership) = match User::from_uuid(db, user.organization_id, uuid).await? {
+        Some((u, Some(m))) => (u, m),
+        _ => return Err(not_found()),
     };
 
-    user.role = Set(req_user.role.to_owned());
-    user.firstname = Set(req_user.firstname.to_owned());
-    user.lastname = Set(req_user.lastname.to_owned());
-    user.email = Set(req_user.email.to_owned());
+    if !preconditions.allows(&etag(member.version)) {
+        return Err(precondition_failed());
+    }
+
+    // An account's password and email are only ever changed by its owner.
+    if !req_user.password.is_empty() || !req_user.email.eq_ignore_ascii_case(&member.email) {
+        return Err(error_response(
+            Status::Forbidden,
+            "Only the account's owner can change its password or email".to_string(),
+        ));
+    }
 
-    if !req_user.password.is_empty() {
-        user.password = Set(hash(req_user.password, DEFAULT_COST).unwrap());
+    // An account shared with other organizations is not this one's to edit;
+    // only the membership is.
+    let shared = OrganizationUser::find()
+        .filter(organization_user::Column::UserId.eq(member.id))
+        .filter(organization_user::Column::OrganizationId.ne(user.organization_id))
+        .count(db)
+        .await?
+        > 0;
+
+    if shared && (req_user.firstname != member.firstname || req_user.lastname != member.lastname)
+    {
+        return Err(error_response(
+            Status::Forbidden,
+            format!(
+                "{} also belongs to another organization. Only its role can be changed.",
+                member.email
+            ),
+        ));
     }
 
-    user.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+    let txn = db.begin().await?;
+
+    let mut membership: organization_user::ActiveModel = membership.into();
+    membership.role = Set(Role::from_str(req_user.role).as_str().to_string());
+    membership.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+    membership.update(&txn).await?;
+
+    let version = member.version;
+    let mut member: user::ActiveModel = member.into();
+
+    member.firstname = Set(req_user.firstname.to_owned());
+    member.lastname = Set(req_user.lastname.to_owned());
+
+    member.updated_at = Set(DateTimeUtc::from(SystemTime::now()));
+
+    preconditions
+        .update(member, user::Column::Version, version, &txn)
+        .await?;
 
-    user.update(db).await?;
+    txn.commit().await?;
 
     success(Status::Ok)
 }
 
+/// Removes a user from the active organization, deleting the account once
+/// they no longer belong to any organization.
 #[delete("/<uuid>")]
 pub async fn delete(
     db: &State<DatabaseConnection>,
     user: AuthenticatedUser,
+    preconditions: Preconditions,
     uuid: &str,
 ) -> Response {
     if user.role != Role::Admin {
@@ -195,12 +359,37 @@ pub async fn delete(
 
     let db = db as &DatabaseConnection;
 
-    let user = match User::from_uuid(db, uuid).await? {
-        Some(u) => u,
-        None => return Err(not_found()),
+    let (member, membership) = match User::from_uuid(db, user.organization_id, uuid).await? {
+        Some((u, Some(m))) => (u, m),
+        _ => return Err(not_found()),
     };
 
-    user.delete(db).await?;
+    if !preconditions.allows(&etag(member.version)) {
+        return Err(precondition_failed());
+    }
+
+    let txn = db.begin().await?;
+
+    membership.delete(&txn).await?;
+
+    let remaining = OrganizationUser::find()
+        .filter(organization_user::Column::UserId.eq(member.id))
+        .count(&txn)
+        .await?;
+
+    // Either way the account is checked against the version the client saw.
+    let version = member.version;
+    if remaining == 0 {
+        preconditions
+            .delete(member.into_active_model(), user::Column::Version, version, &txn)
+            .await?;
+    } else {
+        preconditions
+            .update(member.into_active_model(), user::Column::Version, version, &txn)
+            .await?;
+    }
+
+    txn.commit().await?;
 
     success(Status::Ok)
 }
@@ -0,0 +1,145 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::io::Cursor;
+
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    http::{Header, Method, Status},
+    Request, Response,
+};
+
+use crate::Config;
+
+/// Allows cross-origin requests from the configured origins, or from any with
+/// `*`, and answers their preflight requests.
+#[allow(clippy::upper_case_acronyms)]
+pub struct CORS {
+    origins: Vec<String>,
+    methods: Vec<String>,
+    headers: Vec<String>,
+    max_age: u64,
+    credentials: bool,
+}
+
+impl CORS {
+    pub fn new(config: &Config) -> CORS {
+        CORS {
+            origins: config.cors_origins.to_owned(),
+            methods: config.cors_methods.to_owned(),
+            headers: config.cors_headers.to_owned(),
+            max_age: config.cors_max_age,
+            credentials: config.cors_credentials,
+        }
+    }
+
+    fn any_origin(&self) -> bool {
+        self.origins.iter().any(|o| o == "*")
+    }
+
+    /// The `Access-Control-Allow-Origin` value for a request from `origin`.
+    fn allow_origin(&self, origin: &str) -> Option<String> {
+        if self.any_origin() {
+            Some("*".to_string())
+        } else if self.origins.iter().any(|o| o == origin) {
+            Some(origin.to_string())
+        } else {
+            None
+        }
+    }
+
+    fn allows_method(&self, method: &str) -> bool {
+        self.methods
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(method.trim()))
+    }
+
+    /// Whether every header in a comma-separated list is allowed.
+    fn allows_headers(&self, headers: &str) -> bool {
+        if self.headers.iter().any(|h| h == "*") {
+            return true;
+        }
+
+        headers
+            .split(',')
+            .map(str::trim)
+            .filter(|h| !h.is_empty())
+            .all(|h| self.headers.iter().any(|a| a.eq_ignore_ascii_case(h)))
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for CORS {
+    fn info(&self) -> Info {
+        Info {
+            name: "Add CORS headers",
+            kind: Kind::Response,
+        }
+    }
+
+    /// Preflight requests match no route, so their response is replaced here.
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let origin = match request.headers().get_one("Origin") {
+            Some(o) => o,
+            None => return,
+        };
+
+        if !self.any_origin() {
+            response.set_header(Header::new("Vary", "Origin"));
+        }
+
+        let allow_origin = self.allow_origin(origin);
+
+        let request_method = request.headers().get_one("Access-Control-Request-Method");
+        if let (Method::Options, Some(request_method)) = (request.method(), request_method) {
+            let request_headers = request
+                .headers()
+                .get_one("Access-Control-Request-Headers")
+                .unwrap_or_default();
+
+            response.remove_header("Content-Type");
+            response.set_sized_body(0, Cursor::new(""));
+
+            if allow_origin.is_none()
+                || !self.allows_method(request_method)
+                || !self.allows_headers(request_headers)
+            {
+                response.set_status(Status::Forbidden);
+                return;
+            }
+
+            response.set_status(Status::NoContent);
+            response.set_header(Header::new(
+                "Access-Control-Allow-Methods",
+                self.methods.join(", "),
+            ));
+            response.set_header(Header::new(
+                "Access-Control-Allow-Headers",
+                self.headers.join(", "),
+            ));
+
+            if self.max_age > 0 {
+                response.set_header(Header::new(
+                    "Access-Control-Max-Age",
+                    self.max_age.to_string(),
+                ));
+            }
+        }
+
+        let allow_origin = match allow_origin {
+            Some(o) => o,
+            None => return,
+        };
+
+        response.set_header(Header::new("Access-Control-Allow-Origin", allow_origin));
+        response.set_header(Header::new("Access-Control-Expose-Headers", "ETag"));
+
+        if self.credentials {
+            response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        }
+    }
+}
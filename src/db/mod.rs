@@ -5,22 +5,34 @@
  * @link   https://afaan.dev
  * @link   https://github.com/AfaanBilal/iron-guard-server
  */
+use std::time::Duration;
+
 use sea_orm::*;
 
 use crate::Config;
 
 pub(super) async fn connect(config: &Config) -> Result<DatabaseConnection, DbErr> {
-    let mut opts = ConnectOptions::new(format!(
-        "{}://{}:{}@{}:{}/{}",
-        config.db_type,
-        config.db_username,
-        config.db_password,
-        config.db_host,
-        config.db_port,
-        config.db_database
-    ));
+    let url = if config.database_url.is_empty() {
+        format!(
+            "{}://{}:{}@{}:{}/{}",
+            config.db_type,
+            config.db_username,
+            config.db_password,
+            config.db_host,
+            config.db_port,
+            config.db_database
+        )
+    } else {
+        config.database_url.to_owned()
+    };
+
+    let mut opts = ConnectOptions::new(url);
 
-    opts.sqlx_logging(false);
+    opts.max_connections(config.db_max_connections)
+        .min_connections(config.db_min_connections)
+        .connect_timeout(Duration::from_secs(config.db_connect_timeout))
+        .idle_timeout(Duration::from_secs(config.db_idle_timeout))
+        .sqlx_logging(config.log_queries);
 
     Database::connect(opts).await
 }
@@ -9,11 +9,14 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(unique)]
     pub uuid: String,
+    pub organization_id: i32,
     pub parent_id: Option<i32>,
     pub user_id: i32,
+    pub group_id: Option<i32>,
     pub name: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub description: Option<String>,
+    pub version: i32,
     #[sea_orm(column_type = "Text", nullable)]
     pub meta: Option<String>,
     pub created_at: DateTimeUtc,
@@ -33,6 +36,22 @@ pub enum Relation {
     SelfRef,
     #[sea_orm(has_many = "super::item::Entity")]
     Item,
+    #[sea_orm(
+        belongs_to = "super::group::Entity",
+        from = "Column::GroupId",
+        to = "super::group::Column::Id",
+        on_update = "Restrict",
+        on_delete = "SetNull"
+    )]
+    Group,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    Organization,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UserId",
@@ -49,10 +68,31 @@ impl Related<super::item::Entity> for Entity {
     }
 }
 
+impl Related<super::group::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Group.def()
+    }
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
     }
 }
 
-impl ActiveModelBehavior for ActiveModel {}
+impl ActiveModelBehavior for ActiveModel {
+    /// Every update moves the version on, changing the ETag.
+    fn before_save(mut self, insert: bool) -> Result<Self, DbErr> {
+        if let (false, sea_orm::ActiveValue::Unchanged(version)) = (insert, &self.version) {
+            self.version = sea_orm::ActiveValue::Set(version + 1);
+        }
+
+        Ok(self)
+    }
+}
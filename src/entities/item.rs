@@ -9,12 +9,22 @@ pub struct Model {
     pub id: i32,
     #[sea_orm(unique)]
     pub uuid: String,
+    pub organization_id: i32,
     pub category_id: Option<i32>,
     pub user_id: i32,
+    pub group_id: Option<i32>,
     pub name: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub description: Option<String>,
-    pub quantity: u32,
+    #[sea_orm(column_type = "Decimal(Some((16, 4)))")]
+    pub quantity: Decimal,
+    pub unit: String,
+    #[sea_orm(column_type = "Decimal(Some((12, 4)))", nullable)]
+    pub unit_cost: Option<Decimal>,
+    pub currency: Option<String>,
+    pub mode: String,
+    pub location_id: Option<i32>,
+    pub version: i32,
     #[sea_orm(column_type = "Text", nullable)]
     pub meta: Option<String>,
     pub created_at: DateTimeUtc,
@@ -24,6 +34,18 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::asset::Entity")]
+    Asset,
+    #[sea_orm(has_many = "super::attachment::Entity")]
+    Attachment,
+    #[sea_orm(has_many = "super::lot::Entity")]
+    Lot,
+    #[sea_orm(has_many = "super::purchase_order_line::Entity")]
+    PurchaseOrderLine,
+    #[sea_orm(has_many = "super::stock_movement::Entity")]
+    StockMovement,
+    #[sea_orm(has_many = "super::supplier_item::Entity")]
+    SupplierItem,
     #[sea_orm(
         belongs_to = "super::category::Entity",
         from = "Column::CategoryId",
@@ -32,6 +54,30 @@ pub enum Relation {
         on_delete = "Restrict"
     )]
     Category,
+    #[sea_orm(
+        belongs_to = "super::group::Entity",
+        from = "Column::GroupId",
+        to = "super::group::Column::Id",
+        on_update = "Restrict",
+        on_delete = "SetNull"
+    )]
+    Group,
+    #[sea_orm(
+        belongs_to = "super::location::Entity",
+        from = "Column::LocationId",
+        to = "super::location::Column::Id",
+        on_update = "Restrict",
+        on_delete = "SetNull"
+    )]
+    Location,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    Organization,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UserId",
@@ -42,16 +88,79 @@ pub enum Relation {
     User,
 }
 
+impl Related<super::lot::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Lot.def()
+    }
+}
+
+impl Related<super::purchase_order_line::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PurchaseOrderLine.def()
+    }
+}
+
+impl Related<super::supplier_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SupplierItem.def()
+    }
+}
+
+impl Related<super::stock_movement::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockMovement.def()
+    }
+}
+
+impl Related<super::asset::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Asset.def()
+    }
+}
+
+impl Related<super::attachment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Attachment.def()
+    }
+}
+
 impl Related<super::category::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Category.def()
     }
 }
 
+impl Related<super::group::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Group.def()
+    }
+}
+
+impl Related<super::location::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Location.def()
+    }
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
     }
 }
 
-impl ActiveModelBehavior for ActiveModel {}
+impl ActiveModelBehavior for ActiveModel {
+    /// Every update moves the version on, changing the ETag.
+    fn before_save(mut self, insert: bool) -> Result<Self, DbErr> {
+        if let (false, sea_orm::ActiveValue::Unchanged(version)) = (insert, &self.version) {
+            self.version = sea_orm::ActiveValue::Set(version + 1);
+        }
+
+        Ok(self)
+    }
+}
@@ -2,6 +2,28 @@
 
 pub mod prelude;
 
+pub mod api_key;
+pub mod asset;
+pub mod asset_event;
+pub mod attachment;
 pub mod category;
+pub mod group;
+pub mod group_user;
+pub mod invitation;
 pub mod item;
+pub mod location;
+pub mod lot;
+pub mod oidc_login;
+pub mod organization;
+pub mod organization_user;
+pub mod password_reset;
+pub mod purchase_order;
+pub mod purchase_order_line;
+pub mod recovery_code;
+pub mod session;
+pub mod stock_movement;
+pub mod stocktake;
+pub mod stocktake_count;
+pub mod supplier;
+pub mod supplier_item;
 pub mod user;
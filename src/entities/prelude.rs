@@ -1,5 +1,27 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.0
 
+pub use super::api_key::Entity as ApiKey;
+pub use super::asset::Entity as Asset;
+pub use super::asset_event::Entity as AssetEvent;
+pub use super::attachment::Entity as Attachment;
 pub use super::category::Entity as Category;
+pub use super::group::Entity as Group;
+pub use super::group_user::Entity as GroupUser;
+pub use super::invitation::Entity as Invitation;
 pub use super::item::Entity as Item;
+pub use super::location::Entity as Location;
+pub use super::lot::Entity as Lot;
+pub use super::oidc_login::Entity as OidcLogin;
+pub use super::organization::Entity as Organization;
+pub use super::organization_user::Entity as OrganizationUser;
+pub use super::password_reset::Entity as PasswordReset;
+pub use super::purchase_order::Entity as PurchaseOrder;
+pub use super::purchase_order_line::Entity as PurchaseOrderLine;
+pub use super::recovery_code::Entity as RecoveryCode;
+pub use super::session::Entity as Session;
+pub use super::stock_movement::Entity as StockMovement;
+pub use super::stocktake::Entity as Stocktake;
+pub use super::stocktake_count::Entity as StocktakeCount;
+pub use super::supplier::Entity as Supplier;
+pub use super::supplier_item::Entity as SupplierItem;
 pub use super::user::Entity as User;
@@ -15,6 +15,12 @@ pub struct Model {
     #[sea_orm(unique)]
     pub email: String,
     pub password: String,
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<DateTimeUtc>,
+    pub totp_last_step: Option<i64>,
+    #[sea_orm(unique)]
+    pub oidc_subject: Option<String>,
+    pub version: i32,
     #[sea_orm(column_type = "Text", nullable)]
     pub meta: Option<String>,
     pub created_at: DateTimeUtc,
@@ -24,10 +30,52 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::api_key::Entity")]
+    ApiKey,
+    #[sea_orm(has_many = "super::attachment::Entity")]
+    Attachment,
     #[sea_orm(has_many = "super::category::Entity")]
     Category,
+    #[sea_orm(has_many = "super::group_user::Entity")]
+    GroupUser,
+    #[sea_orm(has_many = "super::invitation::Entity")]
+    Invitation,
     #[sea_orm(has_many = "super::item::Entity")]
     Item,
+    #[sea_orm(has_many = "super::organization_user::Entity")]
+    OrganizationUser,
+    #[sea_orm(has_many = "super::password_reset::Entity")]
+    PasswordReset,
+    #[sea_orm(has_many = "super::recovery_code::Entity")]
+    RecoveryCode,
+    #[sea_orm(has_many = "super::session::Entity")]
+    Session,
+    #[sea_orm(has_many = "super::stock_movement::Entity")]
+    StockMovement,
+}
+
+impl Related<super::api_key::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ApiKey.def()
+    }
+}
+
+impl Related<super::session::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Session.def()
+    }
+}
+
+impl Related<super::stock_movement::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockMovement.def()
+    }
+}
+
+impl Related<super::attachment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Attachment.def()
+    }
 }
 
 impl Related<super::category::Entity> for Entity {
@@ -36,10 +84,69 @@ impl Related<super::category::Entity> for Entity {
     }
 }
 
+impl Related<super::invitation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Invitation.def()
+    }
+}
+
 impl Related<super::item::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Item.def()
     }
 }
 
-impl ActiveModelBehavior for ActiveModel {}
+impl Related<super::group_user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GroupUser.def()
+    }
+}
+
+impl Related<super::group::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::group_user::Relation::Group.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::group_user::Relation::User.def().rev())
+    }
+}
+
+impl Related<super::organization_user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrganizationUser.def()
+    }
+}
+
+impl Related<super::recovery_code::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecoveryCode.def()
+    }
+}
+
+impl Related<super::password_reset::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PasswordReset.def()
+    }
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::organization_user::Relation::Organization.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::organization_user::Relation::User.def().rev())
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    /// Every update moves the version on, changing the ETag.
+    fn before_save(mut self, insert: bool) -> Result<Self, DbErr> {
+        if let (false, sea_orm::ActiveValue::Unchanged(version)) = (insert, &self.version) {
+            self.version = sea_orm::ActiveValue::Set(version + 1);
+        }
+
+        Ok(self)
+    }
+}
@@ -0,0 +1,152 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::convert::Infallible;
+
+use rocket::{
+    http::Header,
+    request::{self, FromRequest, Request},
+};
+use sea_orm::*;
+
+use crate::controllers::{precondition_failed, ErrorResponder};
+
+/// The entity tag of a resource at `version`.
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+/// A response carrying the `ETag` of the resource it shows, or an empty
+/// `304 Not Modified` when the client's copy is current.
+#[derive(Responder)]
+pub enum Tagged<T> {
+    Fresh(T, Header<'static>),
+    #[response(status = 304)]
+    NotModified((), Header<'static>),
+}
+
+impl<T> Tagged<T> {
+    pub fn new(body: T, etag: String) -> Tagged<T> {
+        Tagged::Fresh(body, Header::new("ETag", etag))
+    }
+
+    pub fn not_modified(etag: String) -> Tagged<T> {
+        Tagged::NotModified((), Header::new("ETag", etag))
+    }
+}
+
+/// The `If-Match` and `If-None-Match` headers of a request.
+pub struct Preconditions {
+    if_match: Option<String>,
+    if_none_match: Option<String>,
+}
+
+impl Preconditions {
+    pub fn new(if_match: Option<&str>, if_none_match: Option<&str>) -> Preconditions {
+        Preconditions {
+            if_match: if_match.map(str::to_string),
+            if_none_match: if_none_match.map(str::to_string),
+        }
+    }
+
+    /// Whether the client made its change conditional with `If-Match`.
+    fn is_conditional(&self) -> bool {
+        self.if_match.is_some()
+    }
+
+    /// Whether a change may go ahead: `If-Match` is missing or lists `etag`.
+    /// Weak tags never match, as they only promise an equivalent resource.
+    pub fn allows(&self, etag: &str) -> bool {
+        match &self.if_match {
+            Some(header) => lists(header, etag, false),
+            None => true,
+        }
+    }
+
+    /// Whether `If-None-Match` lists `etag`, so the client's copy is current.
+    pub fn not_modified(&self, etag: &str) -> bool {
+        match &self.if_none_match {
+            Some(header) => lists(header, etag, true),
+            None => false,
+        }
+    }
+
+    /// Saves `model`, read at `version`, moving its `column` on. With `If-Match`
+    /// it is only saved if nothing else changed it since, and fails with
+    /// `412 Precondition Failed` otherwise.
+    pub async fn update<A, C>(
+        &self,
+        mut model: A,
+        column: <A::Entity as EntityTrait>::Column,
+        version: i32,
+        db: &C,
+    ) -> Result<(), ErrorResponder>
+    where
+        A: ActiveModelTrait,
+        <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+        C: ConnectionTrait,
+    {
+        model.set(column, (version + 1).into());
+
+        let mut update = <A::Entity as EntityTrait>::update(model);
+        if self.is_conditional() {
+            update = update.filter(column.eq(version));
+        }
+
+        match update.exec(db).await {
+            Ok(_) => Ok(()),
+            Err(DbErr::RecordNotFound(_)) if self.is_conditional() => Err(precondition_failed()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Deletes `model`, read at `version`. With `If-Match` it is only deleted
+    /// if nothing else changed it since, and fails with `412 Precondition
+    /// Failed` otherwise.
+    pub async fn delete<A, C>(
+        &self,
+        model: A,
+        column: <A::Entity as EntityTrait>::Column,
+        version: i32,
+        db: &C,
+    ) -> Result<(), ErrorResponder>
+    where
+        A: ActiveModelTrait,
+        C: ConnectionTrait,
+    {
+        let mut delete = <A::Entity as EntityTrait>::delete(model);
+        if self.is_conditional() {
+            delete = delete.filter(column.eq(version));
+        }
+
+        if delete.exec(db).await?.rows_affected == 0 && self.is_conditional() {
+            return Err(precondition_failed());
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a comma-separated list of entity tags, or `*`, includes `etag`.
+fn lists(header: &str, etag: &str, weak: bool) -> bool {
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag == etag || (weak && tag.strip_prefix("W/") == Some(etag)))
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Preconditions {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(Preconditions::new(
+            req.headers().get_one("If-Match"),
+            req.headers().get_one("If-None-Match"),
+        ))
+    }
+}
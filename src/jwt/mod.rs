@@ -0,0 +1,304 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{fs, io};
+
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use ring::signature::{Ed25519KeyPair, KeyPair, RsaKeyPair};
+use rocket::serde::{
+    de::DeserializeOwned,
+    json::{serde_json::json, Value},
+    Serialize,
+};
+use sha2::{Digest, Sha256};
+
+use crate::Config;
+
+/// The secret `IRON_GUARD_SECRET` defaults to, only accepted in dev mode.
+pub const DEFAULT_SECRET: &str = "test";
+
+/// Keys for signing and verifying tokens. With HS256 a single shared secret
+/// does both. With RS256 or EdDSA tokens are signed with a private key and
+/// carry its `kid`, and verified with the public keys published as a JWKS.
+/// Public keys of earlier signing keys stay valid, so keys can be rotated
+/// without signing everyone out.
+pub struct Keys {
+    /// How long issued tokens last, in seconds.
+    pub ttl: u64,
+    algorithm: Algorithm,
+    kid: Option<String>,
+    signing: EncodingKey,
+    verifying: Vec<VerifyingKey>,
+}
+
+/// A public key. `jwk` is how it is published.
+struct VerifyingKey {
+    algorithm: Algorithm,
+    kid: Option<String>,
+    key: DecodingKey,
+    jwk: Option<Value>,
+}
+
+impl Keys {
+    pub fn make(config: &Config, dev: bool) -> io::Result<Keys> {
+        match config.jwt_algorithm.as_str() {
+            "HS256" => {
+                if config.secret == DEFAULT_SECRET && !dev {
+                    return Err(invalid(
+                        "IRON_GUARD_SECRET must be changed from the default outside dev mode"
+                            .to_string(),
+                    ));
+                }
+
+                Ok(Keys {
+                    ttl: config.token_ttl * 60,
+                    algorithm: Algorithm::HS256,
+                    kid: None,
+                    signing: EncodingKey::from_secret(config.secret.as_bytes()),
+                    verifying: vec![VerifyingKey {
+                        algorithm: Algorithm::HS256,
+                        kid: None,
+                        key: DecodingKey::from_secret(config.secret.as_bytes()),
+                        jwk: None,
+                    }],
+                })
+            }
+            "RS256" | "EdDSA" => {
+                let pem = fs::read(&config.jwt_private_key)?;
+                let (algorithm, signing, public) = private_key(&pem)?;
+
+                let expected = match config.jwt_algorithm.as_str() {
+                    "RS256" => Algorithm::RS256,
+                    _ => Algorithm::EdDSA,
+                };
+
+                if algorithm != expected {
+                    return Err(invalid(format!(
+                        "{} is not an {} key",
+                        config.jwt_private_key, config.jwt_algorithm
+                    )));
+                }
+
+                let mut verifying = vec![public];
+                for path in &config.jwt_public_keys {
+                    let key = public_key(&fs::read(path)?)?;
+                    if !verifying.iter().any(|k| k.kid == key.kid) {
+                        verifying.push(key);
+                    }
+                }
+
+                Ok(Keys {
+                    ttl: config.token_ttl * 60,
+                    algorithm,
+                    kid: verifying[0].kid.to_owned(),
+                    signing,
+                    verifying,
+                })
+            }
+            other => Err(invalid(format!("Unsupported JWT algorithm: {}", other))),
+        }
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T) -> String {
+        let mut header = Header::new(self.algorithm);
+        header.kid = self.kid.to_owned();
+
+        encode(&header, claims, &self.signing).unwrap()
+    }
+
+    /// The claims of a token signed by one of the keys, if it has not expired.
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Option<T> {
+        let header = decode_header(token).ok()?;
+
+        let key = self
+            .verifying
+            .iter()
+            .find(|k| k.kid == header.kid && k.algorithm == header.alg)?;
+
+        decode::<T>(token, &key.key, &Validation::new(key.algorithm))
+            .ok()
+            .map(|data| data.claims)
+    }
+
+    /// The public keys as a JSON Web Key Set. Empty with HS256.
+    pub fn jwks(&self) -> Value {
+        json!({
+            "keys": self
+                .verifying
+                .iter()
+                .filter_map(|k| k.jwk.to_owned())
+                .collect::<Vec<_>>()
+        })
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn b64(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+/// The JWK thumbprint (RFC 7638) of the required members, used as the `kid`.
+fn thumbprint(required: &Value) -> String {
+    b64(&Sha256::digest(required.to_string().as_bytes()))
+}
+
+fn rsa_key(n: &[u8], e: &[u8]) -> VerifyingKey {
+    let required = json!({ "e": b64(e), "kty": "RSA", "n": b64(n) });
+    let kid = thumbprint(&required);
+
+    let mut jwk = required;
+    jwk["kid"] = json!(kid);
+    jwk["alg"] = json!("RS256");
+    jwk["use"] = json!("sig");
+
+    VerifyingKey {
+        algorithm: Algorithm::RS256,
+        kid: Some(kid),
+        key: DecodingKey::from_rsa_raw_components(n, e),
+        jwk: Some(jwk),
+    }
+}
+
+fn ed25519_key(x: &[u8]) -> VerifyingKey {
+    let required = json!({ "crv": "Ed25519", "kty": "OKP", "x": b64(x) });
+    let kid = thumbprint(&required);
+
+    let mut jwk = required;
+    jwk["kid"] = json!(kid);
+    jwk["alg"] = json!("EdDSA");
+    jwk["use"] = json!("sig");
+
+    VerifyingKey {
+        algorithm: Algorithm::EdDSA,
+        kid: Some(kid),
+        key: DecodingKey::from_ed_der(x),
+        jwk: Some(jwk),
+    }
+}
+
+/// Reads a PKCS#1 RSA or PKCS#8 RSA or Ed25519 private key.
+fn private_key(pem: &[u8]) -> io::Result<(Algorithm, EncodingKey, VerifyingKey)> {
+    let parsed = pem::parse(pem).map_err(|e| invalid(e.to_string()))?;
+    let der = parsed.contents.as_slice();
+
+    if parsed.tag == "RSA PRIVATE KEY" {
+        if let Ok(pair) = RsaKeyPair::from_der(der) {
+            let public = pair.public_key();
+            return Ok((
+                Algorithm::RS256,
+                EncodingKey::from_rsa_der(der),
+                rsa_key(
+                    public.modulus().big_endian_without_leading_zero(),
+                    public.exponent().big_endian_without_leading_zero(),
+                ),
+            ));
+        }
+    } else if parsed.tag == "PRIVATE KEY" {
+        if let Ok(pair) = RsaKeyPair::from_pkcs8(der) {
+            let public = pair.public_key();
+            return Ok((
+                Algorithm::RS256,
+                EncodingKey::from_rsa_pem(pem).map_err(|e| invalid(e.to_string()))?,
+                rsa_key(
+                    public.modulus().big_endian_without_leading_zero(),
+                    public.exponent().big_endian_without_leading_zero(),
+                ),
+            ));
+        }
+
+        if let Ok(pair) = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der) {
+            return Ok((
+                Algorithm::EdDSA,
+                EncodingKey::from_ed_der(der),
+                ed25519_key(pair.public_key().as_ref()),
+            ));
+        }
+    }
+
+    Err(invalid("Unsupported private key".to_string()))
+}
+
+/// Reads an RSA or Ed25519 public key, as PKCS#1 (`RSA PUBLIC KEY`) or
+/// SubjectPublicKeyInfo (`PUBLIC KEY`).
+fn public_key(pem: &[u8]) -> io::Result<VerifyingKey> {
+    let parsed = pem::parse(pem).map_err(|e| invalid(e.to_string()))?;
+    let unsupported = || invalid("Unsupported public key".to_string());
+
+    let (algorithm_oid, key) = match parsed.tag.as_str() {
+        "RSA PUBLIC KEY" => (RSA_OID, parsed.contents.as_slice()),
+        "PUBLIC KEY" => {
+            // SEQUENCE { SEQUENCE { OID, ... }, BIT STRING }
+            let (spki, _) = der_read(&parsed.contents, 0x30).ok_or_else(unsupported)?;
+            let (algorithm, rest) = der_read(spki, 0x30).ok_or_else(unsupported)?;
+            let (oid, _) = der_read(algorithm, 0x06).ok_or_else(unsupported)?;
+            let (bits, _) = der_read(rest, 0x03).ok_or_else(unsupported)?;
+            // The first byte counts unused bits, always 0 for keys.
+            (oid, bits.get(1..).ok_or_else(unsupported)?)
+        }
+        _ => return Err(unsupported()),
+    };
+
+    if algorithm_oid == RSA_OID {
+        // SEQUENCE { INTEGER n, INTEGER e }
+        let (sequence, _) = der_read(key, 0x30).ok_or_else(unsupported)?;
+        let (n, rest) = der_read(sequence, 0x02).ok_or_else(unsupported)?;
+        let (e, _) = der_read(rest, 0x02).ok_or_else(unsupported)?;
+
+        Ok(rsa_key(trim_zeros(n), trim_zeros(e)))
+    } else if algorithm_oid == ED25519_OID && key.len() == 32 {
+        Ok(ed25519_key(key))
+    } else {
+        Err(unsupported())
+    }
+}
+
+/// rsaEncryption (1.2.840.113549.1.1.1) and Ed25519 (1.3.101.112).
+const RSA_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+const ED25519_OID: &[u8] = &[0x2b, 0x65, 0x70];
+
+/// Reads one DER element with the expected tag, returning its contents and
+/// the input after it.
+fn der_read(input: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    if *input.first()? != tag {
+        return None;
+    }
+
+    let first = *input.get(1)? as usize;
+    let (length, start) = if first < 0x80 {
+        (first, 2)
+    } else {
+        let count = first & 0x7f;
+        if count == 0 || count > 4 {
+            return None;
+        }
+
+        let length = input
+            .get(2..2 + count)?
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+
+        (length, 2 + count)
+    };
+
+    let end = start.checked_add(length)?;
+
+    Some((input.get(start..end)?, input.get(end..)?))
+}
+
+fn trim_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|b| *b != 0)
+        .unwrap_or(bytes.len().saturating_sub(1));
+
+    &bytes[start..]
+}
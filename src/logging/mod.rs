@@ -0,0 +1,195 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::{Instant, SystemTime};
+
+use chrono::{SecondsFormat, Utc};
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    http::Header,
+    Data, Request, Response,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::telemetry::{self, Span, SpanKind, Tracer};
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Longest `X-Request-Id` taken from a client; longer ones are replaced.
+const MAX_REQUEST_ID: usize = 128;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Json,
+    Text,
+    Off,
+}
+
+impl LogFormat {
+    pub fn from_str(format: &str) -> Option<LogFormat> {
+        match format {
+            "json" => Some(LogFormat::Json),
+            "text" => Some(LogFormat::Text),
+            "off" => Some(LogFormat::Off),
+            _ => None,
+        }
+    }
+}
+
+/// Tracks a request from arrival to response.
+struct Context {
+    request_id: String,
+    trace_id: String,
+    span_id: String,
+    parent_span_id: Option<String>,
+    started: Instant,
+    start: SystemTime,
+}
+
+/// The authenticated user of a request, once a guard has found them.
+struct RequestUser(Option<i32>);
+
+/// Records the user making the request for its log line.
+pub fn set_user(request: &Request<'_>, user_id: i32) {
+    request.local_cache(|| RequestUser(Some(user_id)));
+}
+
+/// A client's request id, if it is safe to log and echo.
+fn valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.:".contains(c))
+}
+
+/// Logs every request with its id, user, route, status and latency, echoes
+/// or assigns an `X-Request-Id`, and traces requests when a tracer is set.
+pub struct RequestLog {
+    format: LogFormat,
+    tracer: Option<Tracer>,
+}
+
+impl RequestLog {
+    pub fn new(format: LogFormat, tracer: Option<Tracer>) -> RequestLog {
+        RequestLog { format, tracer }
+    }
+
+    fn write(&self, entry: &Value) {
+        match self.format {
+            LogFormat::Json => println!("{}", entry),
+            LogFormat::Text => println!(
+                "{} {} {} {}ms request_id={} user_id={}",
+                entry["method"].as_str().unwrap_or_default(),
+                entry["path"].as_str().unwrap_or_default(),
+                entry["status"],
+                entry["latency_ms"],
+                entry["request_id"].as_str().unwrap_or_default(),
+                entry["user_id"],
+            ),
+            LogFormat::Off => {}
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for RequestLog {
+    fn info(&self) -> Info {
+        Info {
+            name: "Log requests",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let request_id = match request.headers().get_one(REQUEST_ID_HEADER) {
+            Some(id) if valid_request_id(id) => id.to_string(),
+            _ => Uuid::new_v4().to_string(),
+        };
+
+        let (trace_id, parent_span_id) = match request
+            .headers()
+            .get_one("traceparent")
+            .and_then(telemetry::parse_traceparent)
+        {
+            Some((trace_id, parent)) => (trace_id, Some(parent)),
+            None => (telemetry::random_id(16), None),
+        };
+
+        request.local_cache(|| Context {
+            request_id,
+            trace_id,
+            span_id: telemetry::random_id(8),
+            parent_span_id,
+            started: Instant::now(),
+            start: SystemTime::now(),
+        });
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let context = request.local_cache(|| Context {
+            request_id: Uuid::new_v4().to_string(),
+            trace_id: telemetry::random_id(16),
+            span_id: telemetry::random_id(8),
+            parent_span_id: None,
+            started: Instant::now(),
+            start: SystemTime::now(),
+        });
+
+        response.set_header(Header::new(
+            REQUEST_ID_HEADER,
+            context.request_id.to_owned(),
+        ));
+
+        let RequestUser(user_id) = request.local_cache(|| RequestUser(None));
+        let route = match request.route() {
+            Some(route) => route.uri.path().to_string(),
+            None => "unmatched".to_string(),
+        };
+        let status = response.status().code;
+        let latency = context.started.elapsed();
+
+        self.write(&json!({
+            "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            "level": if status >= 500 { "error" } else { "info" },
+            "request_id": context.request_id,
+            "trace_id": context.trace_id,
+            "method": request.method().as_str(),
+            "route": route,
+            "path": request.uri().path().as_str(),
+            "status": status,
+            "latency_ms": latency.as_micros() as f64 / 1000.0,
+            "user_id": user_id,
+        }));
+
+        if let Some(tracer) = &self.tracer {
+            let mut attributes = vec![
+                ("http.method", json!(request.method().as_str())),
+                ("http.route", json!(route)),
+                ("http.target", json!(request.uri().path().as_str())),
+                ("http.status_code", json!(status)),
+                ("request.id", json!(context.request_id)),
+            ];
+            if let Some(id) = user_id {
+                attributes.push(("enduser.id", json!(id)));
+            }
+
+            tracer.record(Span {
+                trace_id: context.trace_id.to_owned(),
+                span_id: context.span_id.to_owned(),
+                parent_span_id: context.parent_span_id.to_owned(),
+                name: format!("{} {}", request.method().as_str(), route),
+                kind: SpanKind::Server,
+                start: context.start,
+                end: context.start + latency,
+                attributes,
+                error: status >= 500,
+            });
+        }
+    }
+}
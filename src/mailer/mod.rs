@@ -0,0 +1,89 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{io, path::PathBuf, time::SystemTime};
+
+use rocket::tokio::fs;
+use uuid::Uuid;
+
+use crate::Config;
+
+pub struct Message {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers outgoing email.
+///
+/// The bundled backends only record messages, which is enough for local
+/// development and tests; a transport such as SMTP plugs in behind the same
+/// trait.
+#[rocket::async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: &Message) -> io::Result<()>;
+}
+
+pub type MailerBackend = Box<dyn Mailer>;
+
+fn format(from: &str, message: &Message) -> String {
+    format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\n\r\n{}\r\n",
+        from, message.to, message.subject, message.body
+    )
+}
+
+/// Writes messages to the server log.
+pub struct LogMailer {
+    from: String,
+}
+
+#[rocket::async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: &Message) -> io::Result<()> {
+        log::info!("Mail\n{}", format(&self.from, message));
+
+        Ok(())
+    }
+}
+
+/// Writes each message to its own `.eml` file in a directory.
+pub struct FileMailer {
+    from: String,
+    root: PathBuf,
+}
+
+#[rocket::async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, message: &Message) -> io::Result<()> {
+        fs::create_dir_all(&self.root).await?;
+
+        let sent = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        fs::write(
+            self.root.join(format!("{}-{}.eml", sent, Uuid::new_v4())),
+            format(&self.from, message),
+        )
+        .await
+    }
+}
+
+pub fn make(config: &Config) -> Result<MailerBackend, String> {
+    let from = config.mail_from.to_owned();
+
+    match config.mail_type.as_str() {
+        "log" => Ok(Box::new(LogMailer { from })),
+        "file" => Ok(Box::new(FileMailer {
+            from,
+            root: PathBuf::from(&config.mail_path),
+        })),
+        other => Err(format!("Unsupported mail type: {}", other)),
+    }
+}
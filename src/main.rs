@@ -8,64 +8,107 @@
 
 #[macro_use]
 extern crate rocket;
+use clap::Parser;
+use cli::{Cli, Command};
+use cors::CORS;
 use migrator::Migrator;
 use rocket::{
-    fairing::{Fairing, Info, Kind},
-    http::Header,
-    Request, Response,
+    data::{Limits, ToByteUnit},
+    Build, Rocket,
 };
 use sea_orm_migration::prelude::*;
+use std::{error::Error, time::Duration};
+use throttle::{Lockout, RateLimit, Window};
 
+mod auth_provider;
+mod cli;
+mod config;
 mod controllers;
+mod cors;
 mod db;
 mod entities;
+mod etag;
+mod jwt;
+mod logging;
+mod mailer;
+mod metrics;
 mod migrator;
+mod oidc;
+mod storage;
+mod telemetry;
+mod throttle;
+mod totp;
+mod units;
 
 pub struct Config {
     secret: String,
+    jwt_algorithm: String,
+    jwt_private_key: String,
+    jwt_public_keys: Vec<String>,
+    token_ttl: u64,
+    database_url: String,
     db_type: String,
     db_host: String,
     db_port: String,
     db_username: String,
     db_password: String,
     db_database: String,
-}
-
-impl Config {
-    pub fn make() -> Config {
-        Config {
-            secret: std::env::var("IRON_GUARD_SECRET").unwrap_or("test".to_string()),
-            db_type: std::env::var("IRON_GUARD_DB_TYPE").unwrap_or("mysql".to_string()),
-            db_host: std::env::var("IRON_GUARD_DB_HOST").unwrap_or("localhost".to_string()),
-            db_port: std::env::var("IRON_GUARD_DB_PORT").unwrap_or("3306".to_string()),
-            db_username: std::env::var("IRON_GUARD_DB_USERNAME").unwrap_or("root".to_string()),
-            db_password: std::env::var("IRON_GUARD_DB_PASSWORD").unwrap_or("".to_string()),
-            db_database: std::env::var("IRON_GUARD_DB_DATABASE")
-                .unwrap_or("iron_guard".to_string()),
-        }
-    }
-}
-
-pub struct CORS;
-
-#[rocket::async_trait]
-impl Fairing for CORS {
-    fn info(&self) -> Info {
-        Info {
-            name: "Add CORS headers",
-            kind: Kind::Response,
-        }
-    }
-
-    async fn on_response<'r>(&self, _request: &'r Request<'_>, response: &mut Response<'r>) {
-        response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
-        response.set_header(Header::new(
-            "Access-Control-Allow-Methods",
-            "GET, POST, PUT, PATCH, DELETE, OPTIONS",
-        ));
-        response.set_header(Header::new("Access-Control-Allow-Headers", "*"));
-        response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
-    }
+    db_max_connections: u32,
+    db_min_connections: u32,
+    db_connect_timeout: u64,
+    db_idle_timeout: u64,
+    auto_migrate: bool,
+    storage_type: String,
+    storage_path: String,
+    upload_max_size: u64,
+    upload_allowed_types: Vec<String>,
+    currency: String,
+    expiry_warning_days: u32,
+    mail_type: String,
+    mail_path: String,
+    mail_from: String,
+    app_url: String,
+    cors_origins: Vec<String>,
+    cors_methods: Vec<String>,
+    cors_headers: Vec<String>,
+    cors_max_age: u64,
+    cors_credentials: bool,
+    password_reset_ttl: u64,
+    invitation_ttl: u64,
+    lockout_threshold: u32,
+    lockout_ip_threshold: u32,
+    lockout_base: u64,
+    lockout_max: u64,
+    rate_limit_auth: Option<Window>,
+    rate_limit_api: Option<Window>,
+    trust_proxy: bool,
+    password_sign_in: bool,
+    api_keys: bool,
+    attachments: bool,
+    metrics_token: String,
+    low_stock_threshold: u64,
+    log_format: String,
+    log_queries: bool,
+    otel_endpoint: String,
+    otel_service_name: String,
+    oidc_issuer: String,
+    oidc_client_id: String,
+    oidc_client_secret: String,
+    oidc_redirect_url: String,
+    oidc_scopes: String,
+    oidc_organization: String,
+    oidc_role_claim: String,
+    oidc_admin_role: String,
+    auth_providers: Vec<String>,
+    ldap_url: String,
+    ldap_bind_dn: String,
+    ldap_bind_password: String,
+    ldap_base_dn: String,
+    ldap_user_filter: String,
+    ldap_group_base_dn: String,
+    ldap_admin_groups: Vec<String>,
+    ldap_user_groups: Vec<String>,
+    ldap_organization: String,
 }
 
 #[get("/")]
@@ -83,42 +126,165 @@ fn unauthorized() -> &'static str {
     "401 Unauthorized"
 }
 
+#[catch(403)]
+fn forbidden() -> &'static str {
+    "403 Forbidden"
+}
+
 #[catch(404)]
 fn not_found() -> &'static str {
     "404 Not Found"
 }
 
-#[options("/<_..>")]
-fn options() -> &'static str {
-    ""
+#[catch(429)]
+fn too_many_requests() -> &'static str {
+    "429 Too Many Requests"
+}
+
+/// Reports a startup error and exits. Rocket's logger is not set up yet.
+/// Only `main` exits, so tests building the server see the error instead.
+fn fail(err: impl std::fmt::Display) -> ! {
+    eprintln!("Iron Guard could not start: {}", err);
+    std::process::exit(1)
 }
 
-#[launch]
-async fn rocket() -> _ {
-    let config = Config::make();
+#[rocket::main]
+async fn main() {
+    match Cli::parse().command.unwrap_or(Command::Serve) {
+        Command::Serve => {
+            let rocket = match rocket().await {
+                Ok(rocket) => rocket,
+                Err(err) => fail(err),
+            };
 
-    let db = match db::connect(&config).await {
-        Ok(db) => db,
-        Err(err) => panic!("{}", err),
-    };
+            if let Err(err) = rocket.launch().await {
+                fail(err);
+            }
+        }
+        command => {
+            if let Err(err) = cli::run(command).await {
+                eprintln!("Error: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+async fn rocket() -> Result<Rocket<Build>, Box<dyn Error>> {
+    let profile = rocket::Config::figment().profile().clone();
+
+    build(Config::load(profile.as_str().as_str())?).await
+}
 
-    match Migrator::up(&db, None).await {
-        Err(err) => panic!("{}", err),
-        Ok(_) => 0,
-    };
+/// The server for `config`, which `rocket` loads for the Rocket profile.
+async fn build(config: Config) -> Result<Rocket<Build>, Box<dyn Error>> {
+    let dev = rocket::Config::figment().profile() == rocket::Config::DEBUG_PROFILE;
+    let keys = jwt::Keys::make(&config, dev)?;
 
-    rocket::build()
-        .attach(CORS)
+    let mut db = db::connect(&config).await?;
+
+    let metrics = metrics::Metrics::new(config.db_max_connections, config.db_min_connections);
+    let tracer = telemetry::Tracer::make(&config);
+
+    let (recorder, query_tracer) = (metrics.clone(), tracer.clone());
+    db.set_metric_callback(move |query| {
+        recorder.record_query(query.elapsed, query.failed);
+        if let Some(tracer) = &query_tracer {
+            tracer.record_query(&query.statement.sql, query.elapsed, query.failed);
+        }
+    });
+
+    let log_format = logging::LogFormat::from_str(&config.log_format).unwrap();
+
+    if config.auto_migrate {
+        Migrator::up(&db, None).await?;
+    }
+
+    Migrator::check(&db).await?;
+
+    let storage = storage::make(&config)?;
+    let mailer = mailer::make(&config)?;
+
+    let lockout = Lockout::new(
+        config.lockout_threshold,
+        config.lockout_ip_threshold,
+        Duration::from_secs(config.lockout_base),
+        Duration::from_secs(config.lockout_max),
+    );
+    let auth_providers = auth_provider::make(&config)?;
+
+    let oidc_provider = oidc::Provider::make(&config);
+    let rate_limit = RateLimit::new(
+        config.rate_limit_auth,
+        config.rate_limit_api,
+        config.trust_proxy,
+    );
+
+    let limits = Limits::default()
+        .limit("file", config.upload_max_size.bytes())
+        .limit("data-form", (config.upload_max_size + 1024 * 1024).bytes());
+
+    // Read before `config` is handed to Rocket.
+    let (api_keys, attachments) = (config.api_keys, config.attachments);
+
+    let mut rocket = rocket::custom(rocket::Config::figment().merge(("limits", limits)))
+        .attach(logging::RequestLog::new(log_format, tracer.clone()))
+        .attach(metrics.clone())
+        .attach(CORS::new(&config))
+        .attach(rate_limit)
         .manage(config)
+        .manage(metrics)
+        .manage(keys)
         .manage(db)
-        .register("/", catchers![bad_request, unauthorized, not_found])
-        .mount("/", routes![options])
-        .mount("/", routes![index])
-        .mount("/auth", routes![controllers::auth::sign_in])
+        .manage(storage)
+        .manage(mailer)
+        .manage(lockout)
+        .manage(auth_providers)
+        .manage(oidc_provider)
+        .register("/", catchers![
+                bad_request,
+                unauthorized,
+                forbidden,
+                not_found,
+                too_many_requests
+            ])
+        .mount(
+            "/",
+            routes![
+                index,
+                throttle::rate_limited,
+                controllers::health::healthz,
+                controllers::health::readyz,
+                controllers::health::metrics
+            ],
+        )
+        .mount("/.well-known", routes![controllers::auth::jwks])
+        .mount(
+            "/auth",
+            routes![
+                controllers::auth::sign_in,
+                controllers::auth::switch,
+                controllers::passwords::forgot,
+                controllers::passwords::reset,
+                controllers::invitations::accept,
+                controllers::auth::two_factor,
+                controllers::oidc::login,
+                controllers::oidc::callback
+            ],
+        )
         .mount("/dashboard", routes![controllers::dashboard::index])
         .mount(
             "/me",
-            routes![controllers::me::index, controllers::me::update],
+            routes![
+                controllers::me::index,
+                controllers::me::update,
+                controllers::two_factor::enroll,
+                controllers::two_factor::confirm,
+                controllers::two_factor::recovery_codes,
+                controllers::two_factor::disable,
+                controllers::sessions::index,
+                controllers::sessions::delete
+            ],
         )
         .mount(
             "/inventory",
@@ -134,7 +300,8 @@ async fn rocket() -> _ {
                 controllers::users::store,
                 controllers::users::show,
                 controllers::users::update,
-                controllers::users::delete
+                controllers::users::delete,
+                controllers::sessions::user_delete
             ],
         )
         .mount(
@@ -154,9 +321,141 @@ async fn rocket() -> _ {
                 controllers::items::store,
                 controllers::items::show,
                 controllers::items::update,
-                controllers::items::delete
+                controllers::items::delete,
+                controllers::items::check_out,
+                controllers::stock_movements::index,
+                controllers::lots::index,
+                controllers::lots::store,
+                controllers::lots::update,
+                controllers::assets::index,
+                controllers::assets::store,
+                controllers::assets::show,
+                controllers::assets::update
+            ],
+        )
+        .mount("/lots", routes![controllers::lots::expiring])
+        .mount("/migrations", routes![controllers::migrations::index])
+        .mount("/units", routes![controllers::units::index])
+        .mount(
+            "/organizations",
+            routes![
+                controllers::organizations::index,
+                controllers::organizations::store,
+                controllers::organizations::show,
+                controllers::organizations::update
+            ],
+        )
+        .mount(
+            "/invitations",
+            routes![
+                controllers::invitations::index,
+                controllers::invitations::store,
+                controllers::invitations::delete
+            ],
+        )
+        .mount(
+            "/groups",
+            routes![
+                controllers::groups::index,
+                controllers::groups::store,
+                controllers::groups::show,
+                controllers::groups::update,
+                controllers::groups::delete,
+                controllers::groups::add_member,
+                controllers::groups::remove_member
+            ],
+        )
+        .mount(
+            "/locations",
+            routes![
+                controllers::locations::index,
+                controllers::locations::store,
+                controllers::locations::show,
+                controllers::locations::update,
+                controllers::locations::delete
+            ],
+        )
+        .mount(
+            "/suppliers",
+            routes![
+                controllers::suppliers::index,
+                controllers::suppliers::store,
+                controllers::suppliers::show,
+                controllers::suppliers::update,
+                controllers::suppliers::delete,
+                controllers::suppliers::link_item,
+                controllers::suppliers::unlink_item
             ],
         )
+        .mount(
+            "/purchase-orders",
+            routes![
+                controllers::purchase_orders::index,
+                controllers::purchase_orders::store,
+                controllers::purchase_orders::show,
+                controllers::purchase_orders::update,
+                controllers::purchase_orders::delete,
+                controllers::purchase_orders::send,
+                controllers::purchase_orders::receive,
+                controllers::purchase_orders::close
+            ],
+        )
+        .mount(
+            "/stocktakes",
+            routes![
+                controllers::stocktakes::index,
+                controllers::stocktakes::store,
+                controllers::stocktakes::show,
+                controllers::stocktakes::count,
+                controllers::stocktakes::approve,
+                controllers::stocktakes::cancel
+            ],
+        )
+        .mount(
+            "/reports",
+            routes![
+                controllers::reports::valuation,
+                controllers::reports::valuation_history
+            ],
+        );
+
+    if let Some(tracer) = tracer {
+        rocket = rocket.attach(tracer);
+    }
+
+    if api_keys {
+        rocket = rocket
+            .mount(
+                "/me",
+                routes![
+                    controllers::api_keys::index,
+                    controllers::api_keys::store,
+                    controllers::api_keys::delete
+                ],
+            )
+            .mount(
+                "/users",
+                routes![
+                    controllers::api_keys::user_index,
+                    controllers::api_keys::user_delete
+                ],
+            );
+    }
+
+    if attachments {
+        rocket = rocket.mount(
+            "/items",
+            routes![
+                controllers::attachments::index,
+                controllers::attachments::store,
+                controllers::attachments::show,
+                controllers::attachments::thumbnail,
+                controllers::attachments::delete
+            ],
+        );
+    }
+
+    Ok(rocket)
 }
 
 #[cfg(test)]
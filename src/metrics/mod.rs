@@ -0,0 +1,279 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    Data, Request, Response,
+};
+
+/// Upper bounds in seconds of the latency histogram buckets.
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+
+        for (bucket, le) in self.buckets.iter_mut().zip(BUCKETS) {
+            if secs <= le {
+                *bucket += 1;
+            }
+        }
+
+        self.sum += secs;
+        self.count += 1;
+    }
+
+    /// Writes the series of the histogram `name`.
+    fn render(&self, out: &mut String, name: &str, labels: &[String]) {
+        let buckets = BUCKETS
+            .iter()
+            .map(|le| le.to_string())
+            .zip(self.buckets)
+            .chain([("+Inf".to_string(), self.count)]);
+
+        for (le, count) in buckets {
+            let mut series = labels.to_vec();
+            series.push(format!("le=\"{}\"", le));
+            let _ = writeln!(out, "{}_bucket{} {}", name, braces(&series), count);
+        }
+
+        let _ = writeln!(out, "{}_sum{} {}", name, braces(labels), self.sum);
+        let _ = writeln!(out, "{}_count{} {}", name, braces(labels), self.count);
+    }
+}
+
+fn braces(labels: &[String]) -> String {
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels.join(","))
+    }
+}
+
+#[derive(Default)]
+struct Requests {
+    /// Count per method, route and status.
+    counts: BTreeMap<(String, String, u16), u64>,
+    /// Latency per method and route.
+    latencies: BTreeMap<(String, String), Histogram>,
+}
+
+#[derive(Default)]
+struct Queries {
+    latency: Histogram,
+    failed: u64,
+}
+
+/// Inventory totals across every organization, read when scraped.
+pub struct Inventory {
+    pub items: u64,
+    pub quantity: String,
+    pub low_stock: u64,
+}
+
+/// Request and database query metrics in the Prometheus text format. Kept in
+/// memory by each server instance.
+#[derive(Clone)]
+pub struct Metrics {
+    requests: Arc<Mutex<Requests>>,
+    queries: Arc<Mutex<Queries>>,
+    pool_max: u32,
+    pool_min: u32,
+}
+
+/// When the request arrived.
+struct Started(Instant);
+
+impl Metrics {
+    pub fn new(pool_max: u32, pool_min: u32) -> Metrics {
+        Metrics {
+            requests: Arc::default(),
+            queries: Arc::default(),
+            pool_max,
+            pool_min,
+        }
+    }
+
+    /// Records a request to `route`, the matched route's path or `unmatched`.
+    pub fn record_request(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let mut requests = self.requests.lock().unwrap();
+
+        *requests
+            .counts
+            .entry((method.to_string(), route.to_string(), status))
+            .or_default() += 1;
+
+        requests
+            .latencies
+            .entry((method.to_string(), route.to_string()))
+            .or_default()
+            .observe(elapsed);
+    }
+
+    pub fn record_query(&self, elapsed: Duration, failed: bool) {
+        let mut queries = self.queries.lock().unwrap();
+
+        queries.latency.observe(elapsed);
+        if failed {
+            queries.failed += 1;
+        }
+    }
+
+    pub fn render(&self, inventory: &Inventory) -> String {
+        let mut out = String::new();
+
+        {
+            let requests = self.requests.lock().unwrap();
+
+            out.push_str("# HELP iron_guard_http_requests_total HTTP requests handled.\n");
+            out.push_str("# TYPE iron_guard_http_requests_total counter\n");
+            for ((method, route, status), count) in &requests.counts {
+                let _ = writeln!(
+                    out,
+                    "iron_guard_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                    method,
+                    escape(route),
+                    status,
+                    count
+                );
+            }
+
+            out.push_str("# HELP iron_guard_http_request_duration_seconds HTTP request latency.\n");
+            out.push_str("# TYPE iron_guard_http_request_duration_seconds histogram\n");
+            for ((method, route), latency) in &requests.latencies {
+                latency.render(
+                    &mut out,
+                    "iron_guard_http_request_duration_seconds",
+                    &[
+                        format!("method=\"{}\"", method),
+                        format!("route=\"{}\"", escape(route)),
+                    ],
+                );
+            }
+        }
+
+        {
+            let queries = self.queries.lock().unwrap();
+
+            out.push_str("# HELP iron_guard_db_query_duration_seconds Database query latency.\n");
+            out.push_str("# TYPE iron_guard_db_query_duration_seconds histogram\n");
+            queries
+                .latency
+                .render(&mut out, "iron_guard_db_query_duration_seconds", &[]);
+
+            write_metric(
+                &mut out,
+                "iron_guard_db_query_failures_total",
+                "counter",
+                "Database queries that failed.",
+                queries.failed,
+            );
+        }
+
+        write_metric(
+            &mut out,
+            "iron_guard_db_pool_max_connections",
+            "gauge",
+            "Most connections the database pool opens.",
+            self.pool_max,
+        );
+        write_metric(
+            &mut out,
+            "iron_guard_db_pool_min_connections",
+            "gauge",
+            "Connections the database pool keeps open.",
+            self.pool_min,
+        );
+
+        write_metric(
+            &mut out,
+            "iron_guard_items",
+            "gauge",
+            "Items in every organization.",
+            inventory.items,
+        );
+        write_metric(
+            &mut out,
+            "iron_guard_items_quantity",
+            "gauge",
+            "Total quantity of every item, regardless of unit.",
+            &inventory.quantity,
+        );
+        write_metric(
+            &mut out,
+            "iron_guard_items_low_stock",
+            "gauge",
+            "Bulk items at or below IRON_GUARD_LOW_STOCK_THRESHOLD.",
+            inventory.low_stock,
+        );
+
+        out
+    }
+}
+
+fn write_metric(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    value: impl std::fmt::Display,
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+/// Escapes a label value.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[rocket::async_trait]
+impl Fairing for Metrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Record request metrics",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| Started(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Started(started) = request.local_cache(|| Started(Instant::now()));
+
+        let route = match request.route() {
+            Some(route) => route.uri.path().to_string(),
+            None => "unmatched".to_string(),
+        };
+
+        self.record_request(
+            request.method().as_str(),
+            &route,
+            response.status().code,
+            started.elapsed(),
+        );
+    }
+}
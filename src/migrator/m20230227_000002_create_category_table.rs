@@ -73,7 +73,7 @@ impl MigrationTrait for Migration {
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         manager
-            .drop_table(Table::drop().table(Category::Table).to_owned())
+            .drop_table(Table::drop().table(Category::Table).if_exists().to_owned())
             .await
     }
 }
@@ -77,7 +77,7 @@ impl MigrationTrait for Migration {
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         manager
-            .drop_table(Table::drop().table(Item::Table).to_owned())
+            .drop_table(Table::drop().table(Item::Table).if_exists().to_owned())
             .await
     }
 }
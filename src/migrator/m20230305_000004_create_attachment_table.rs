@@ -0,0 +1,105 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{m20230227_000001_create_user_table::User, m20230227_000003_create_item_table::Item};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230305_000004_create_attachment_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(Attachment::Table)
+                    .col(
+                        ColumnDef::new(Attachment::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Attachment::Uuid)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Attachment::ItemId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachment-item_id")
+                            .from(Attachment::Table, Attachment::ItemId)
+                            .to(Item::Table, Item::Id),
+                    )
+                    .col(ColumnDef::new(Attachment::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachment-user_id")
+                            .from(Attachment::Table, Attachment::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(ColumnDef::new(Attachment::Name).string().not_null())
+                    .col(ColumnDef::new(Attachment::ContentType).string().not_null())
+                    .col(
+                        ColumnDef::new(Attachment::Size)
+                            .big_integer()
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Attachment::StorageKey).string().not_null())
+                    .col(ColumnDef::new(Attachment::ThumbnailKey).string().null())
+                    .col(ColumnDef::new(Attachment::Meta).text())
+                    .col(
+                        ColumnDef::new(Attachment::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(Attachment::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(ColumnDef::new(Attachment::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Attachment::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Attachment {
+    Table,
+    Id,
+    Uuid,
+    ItemId,
+    UserId,
+    Name,
+    ContentType,
+    Size,
+    StorageKey,
+    ThumbnailKey,
+    Meta,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+}
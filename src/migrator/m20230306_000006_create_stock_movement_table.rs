@@ -0,0 +1,100 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{m20230227_000001_create_user_table::User, m20230227_000003_create_item_table::Item};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230306_000006_create_stock_movement_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(StockMovement::Table)
+                    .col(
+                        ColumnDef::new(StockMovement::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StockMovement::Uuid)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(StockMovement::ItemId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-stock_movement-item_id")
+                            .from(StockMovement::Table, StockMovement::ItemId)
+                            .to(Item::Table, Item::Id),
+                    )
+                    .col(ColumnDef::new(StockMovement::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-stock_movement-user_id")
+                            .from(StockMovement::Table, StockMovement::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(ColumnDef::new(StockMovement::Quantity).integer().not_null())
+                    .col(
+                        ColumnDef::new(StockMovement::UnitCost)
+                            .decimal_len(12, 4)
+                            .null(),
+                    )
+                    .col(ColumnDef::new(StockMovement::Reason).string().not_null())
+                    .col(ColumnDef::new(StockMovement::Meta).text())
+                    .col(
+                        ColumnDef::new(StockMovement::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(StockMovement::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(ColumnDef::new(StockMovement::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StockMovement::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum StockMovement {
+    Table,
+    Id,
+    Uuid,
+    ItemId,
+    UserId,
+    Quantity,
+    UnitCost,
+    Reason,
+    Meta,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+}
@@ -0,0 +1,90 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::m20230227_000001_create_user_table::User;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230307_000007_create_supplier_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(Supplier::Table)
+                    .col(
+                        ColumnDef::new(Supplier::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Supplier::Uuid)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Supplier::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-supplier-user_id")
+                            .from(Supplier::Table, Supplier::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(ColumnDef::new(Supplier::Name).string().not_null())
+                    .col(ColumnDef::new(Supplier::Email).string().null())
+                    .col(ColumnDef::new(Supplier::Phone).string().null())
+                    .col(ColumnDef::new(Supplier::Address).text())
+                    .col(ColumnDef::new(Supplier::Meta).text())
+                    .col(
+                        ColumnDef::new(Supplier::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(Supplier::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(ColumnDef::new(Supplier::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Supplier::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Supplier {
+    Table,
+    Id,
+    Uuid,
+    UserId,
+    Name,
+    Email,
+    Phone,
+    Address,
+    Meta,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+}
@@ -0,0 +1,102 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20230227_000003_create_item_table::Item, m20230307_000007_create_supplier_table::Supplier,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230307_000008_create_supplier_item_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(SupplierItem::Table)
+                    .col(
+                        ColumnDef::new(SupplierItem::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SupplierItem::SupplierId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-supplier_item-supplier_id")
+                            .from(SupplierItem::Table, SupplierItem::SupplierId)
+                            .to(Supplier::Table, Supplier::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(SupplierItem::ItemId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-supplier_item-item_id")
+                            .from(SupplierItem::Table, SupplierItem::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(SupplierItem::Sku).string().null())
+                    .col(
+                        ColumnDef::new(SupplierItem::LeadTimeDays)
+                            .integer()
+                            .unsigned()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(SupplierItem::UnitCost)
+                            .decimal_len(12, 4)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(SupplierItem::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(SupplierItem::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SupplierItem::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum SupplierItem {
+    Table,
+    Id,
+    SupplierId,
+    ItemId,
+    Sku,
+    LeadTimeDays,
+    UnitCost,
+    CreatedAt,
+    UpdatedAt,
+}
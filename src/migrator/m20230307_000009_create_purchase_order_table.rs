@@ -0,0 +1,105 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20230227_000001_create_user_table::User, m20230307_000007_create_supplier_table::Supplier,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230307_000009_create_purchase_order_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(PurchaseOrder::Table)
+                    .col(
+                        ColumnDef::new(PurchaseOrder::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrder::Uuid)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrder::SupplierId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-purchase_order-supplier_id")
+                            .from(PurchaseOrder::Table, PurchaseOrder::SupplierId)
+                            .to(Supplier::Table, Supplier::Id),
+                    )
+                    .col(ColumnDef::new(PurchaseOrder::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-purchase_order-user_id")
+                            .from(PurchaseOrder::Table, PurchaseOrder::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrder::Status)
+                            .string()
+                            .not_null()
+                            .default("draft"),
+                    )
+                    .col(ColumnDef::new(PurchaseOrder::Notes).text())
+                    .col(ColumnDef::new(PurchaseOrder::Meta).text())
+                    .col(
+                        ColumnDef::new(PurchaseOrder::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrder::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(ColumnDef::new(PurchaseOrder::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PurchaseOrder::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum PurchaseOrder {
+    Table,
+    Id,
+    Uuid,
+    SupplierId,
+    UserId,
+    Status,
+    Notes,
+    Meta,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+}
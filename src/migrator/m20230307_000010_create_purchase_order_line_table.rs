@@ -0,0 +1,119 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20230227_000003_create_item_table::Item,
+    m20230307_000009_create_purchase_order_table::PurchaseOrder,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230307_000010_create_purchase_order_line_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(PurchaseOrderLine::Table)
+                    .col(
+                        ColumnDef::new(PurchaseOrderLine::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderLine::Uuid)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderLine::PurchaseOrderId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-purchase_order_line-purchase_order_id")
+                            .from(PurchaseOrderLine::Table, PurchaseOrderLine::PurchaseOrderId)
+                            .to(PurchaseOrder::Table, PurchaseOrder::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderLine::ItemId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-purchase_order_line-item_id")
+                            .from(PurchaseOrderLine::Table, PurchaseOrderLine::ItemId)
+                            .to(Item::Table, Item::Id),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderLine::QuantityOrdered)
+                            .integer()
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderLine::QuantityReceived)
+                            .integer()
+                            .unsigned()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderLine::UnitCost)
+                            .decimal_len(12, 4)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderLine::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderLine::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PurchaseOrderLine::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum PurchaseOrderLine {
+    Table,
+    Id,
+    Uuid,
+    PurchaseOrderId,
+    ItemId,
+    QuantityOrdered,
+    QuantityReceived,
+    UnitCost,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,111 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20230227_000001_create_user_table::User, m20230227_000002_create_category_table::Category,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230308_000011_create_stocktake_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(Stocktake::Table)
+                    .col(
+                        ColumnDef::new(Stocktake::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Stocktake::Uuid)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Stocktake::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-stocktake-user_id")
+                            .from(Stocktake::Table, Stocktake::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(ColumnDef::new(Stocktake::CategoryId).integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-stocktake-category_id")
+                            .from(Stocktake::Table, Stocktake::CategoryId)
+                            .to(Category::Table, Category::Id),
+                    )
+                    .col(
+                        ColumnDef::new(Stocktake::Status)
+                            .string()
+                            .not_null()
+                            .default("open"),
+                    )
+                    .col(ColumnDef::new(Stocktake::ApprovedBy).integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-stocktake-approved_by")
+                            .from(Stocktake::Table, Stocktake::ApprovedBy)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(ColumnDef::new(Stocktake::ApprovedAt).timestamp().null())
+                    .col(ColumnDef::new(Stocktake::Notes).text())
+                    .col(ColumnDef::new(Stocktake::Meta).text())
+                    .col(
+                        ColumnDef::new(Stocktake::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(Stocktake::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(ColumnDef::new(Stocktake::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Stocktake::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Stocktake {
+    Table,
+    Id,
+    Uuid,
+    UserId,
+    CategoryId,
+    Status,
+    ApprovedBy,
+    ApprovedAt,
+    Notes,
+    Meta,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+}
@@ -0,0 +1,117 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20230227_000001_create_user_table::User, m20230227_000003_create_item_table::Item,
+    m20230308_000011_create_stocktake_table::Stocktake,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230308_000012_create_stocktake_count_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(StocktakeCount::Table)
+                    .col(
+                        ColumnDef::new(StocktakeCount::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StocktakeCount::StocktakeId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-stocktake_count-stocktake_id")
+                            .from(StocktakeCount::Table, StocktakeCount::StocktakeId)
+                            .to(Stocktake::Table, Stocktake::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(StocktakeCount::ItemId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-stocktake_count-item_id")
+                            .from(StocktakeCount::Table, StocktakeCount::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(StocktakeCount::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-stocktake_count-user_id")
+                            .from(StocktakeCount::Table, StocktakeCount::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(
+                        ColumnDef::new(StocktakeCount::CountedQuantity)
+                            .integer()
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StocktakeCount::ExpectedQuantity)
+                            .integer()
+                            .unsigned()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(StocktakeCount::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(StocktakeCount::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .name("idx-stocktake_count-stocktake_id-item_id")
+                            .col(StocktakeCount::StocktakeId)
+                            .col(StocktakeCount::ItemId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StocktakeCount::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum StocktakeCount {
+    Table,
+    Id,
+    StocktakeId,
+    ItemId,
+    UserId,
+    CountedQuantity,
+    ExpectedQuantity,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,90 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::m20230227_000003_create_item_table::Item;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230309_000013_create_lot_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(Lot::Table)
+                    .col(
+                        ColumnDef::new(Lot::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Lot::Uuid).string().unique_key().not_null())
+                    .col(ColumnDef::new(Lot::ItemId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-lot-item_id")
+                            .from(Lot::Table, Lot::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Lot::BatchNumber).string().not_null())
+                    .col(
+                        ColumnDef::new(Lot::Quantity)
+                            .integer()
+                            .unsigned()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(Lot::ExpiryDate).date().null())
+                    .col(ColumnDef::new(Lot::Meta).text())
+                    .col(
+                        ColumnDef::new(Lot::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(Lot::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(ColumnDef::new(Lot::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Lot::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Lot {
+    Table,
+    Id,
+    Uuid,
+    ItemId,
+    BatchNumber,
+    Quantity,
+    ExpiryDate,
+    Meta,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+}
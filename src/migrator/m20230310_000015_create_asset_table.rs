@@ -0,0 +1,104 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{m20230227_000001_create_user_table::User, m20230227_000003_create_item_table::Item};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230310_000015_create_asset_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(Asset::Table)
+                    .col(
+                        ColumnDef::new(Asset::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Asset::Uuid).string().unique_key().not_null())
+                    .col(ColumnDef::new(Asset::ItemId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-asset-item_id")
+                            .from(Asset::Table, Asset::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(Asset::SerialNumber)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Asset::Status)
+                            .string()
+                            .not_null()
+                            .default("in_stock"),
+                    )
+                    .col(ColumnDef::new(Asset::AssigneeId).integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-asset-assignee_id")
+                            .from(Asset::Table, Asset::AssigneeId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(ColumnDef::new(Asset::PurchaseDate).date().null())
+                    .col(ColumnDef::new(Asset::Notes).text())
+                    .col(ColumnDef::new(Asset::Meta).text())
+                    .col(
+                        ColumnDef::new(Asset::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(Asset::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(ColumnDef::new(Asset::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Asset::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Asset {
+    Table,
+    Id,
+    Uuid,
+    ItemId,
+    SerialNumber,
+    Status,
+    AssigneeId,
+    PurchaseDate,
+    Notes,
+    Meta,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+}
@@ -0,0 +1,86 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{m20230227_000001_create_user_table::User, m20230310_000015_create_asset_table::Asset};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230310_000016_create_asset_event_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(AssetEvent::Table)
+                    .col(
+                        ColumnDef::new(AssetEvent::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AssetEvent::AssetId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-asset_event-asset_id")
+                            .from(AssetEvent::Table, AssetEvent::AssetId)
+                            .to(Asset::Table, Asset::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(AssetEvent::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-asset_event-user_id")
+                            .from(AssetEvent::Table, AssetEvent::UserId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(ColumnDef::new(AssetEvent::Status).string().not_null())
+                    .col(ColumnDef::new(AssetEvent::AssigneeId).integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-asset_event-assignee_id")
+                            .from(AssetEvent::Table, AssetEvent::AssigneeId)
+                            .to(User::Table, User::Id),
+                    )
+                    .col(ColumnDef::new(AssetEvent::Notes).text())
+                    .col(
+                        ColumnDef::new(AssetEvent::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AssetEvent::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum AssetEvent {
+    Table,
+    Id,
+    AssetId,
+    UserId,
+    Status,
+    AssigneeId,
+    Notes,
+    CreatedAt,
+}
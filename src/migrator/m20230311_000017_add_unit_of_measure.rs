@@ -0,0 +1,186 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230311_000017_add_unit_of_measure"
+    }
+}
+
+/// Quantities become `DECIMAL(16, 4)` so that items measured in metres,
+/// kilograms or litres can hold fractions.
+fn quantity<T: Iden + 'static>(column: T, nullable: bool) -> ColumnDef {
+    let mut def = ColumnDef::new(column);
+    def.decimal_len(16, 4);
+
+    if nullable {
+        def.null();
+    } else {
+        def.not_null().default(0);
+    }
+
+    def
+}
+
+/// The `down` direction restores the original unsigned integer columns.
+fn count<T: Iden + 'static>(column: T, nullable: bool) -> ColumnDef {
+    let mut def = ColumnDef::new(column);
+    def.integer().unsigned();
+
+    if nullable {
+        def.null();
+    } else {
+        def.not_null().default(0);
+    }
+
+    def
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .modify_column(&mut quantity(Item::Quantity, false))
+                    .add_column(
+                        ColumnDef::new(Item::Unit)
+                            .string()
+                            .not_null()
+                            .default("pcs"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(StockMovement::Table)
+                    .modify_column(&mut quantity(StockMovement::Quantity, false))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Lot::Table)
+                    .modify_column(&mut quantity(Lot::Quantity, false))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PurchaseOrderLine::Table)
+                    .modify_column(&mut quantity(PurchaseOrderLine::QuantityOrdered, false))
+                    .modify_column(&mut quantity(PurchaseOrderLine::QuantityReceived, false))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(StocktakeCount::Table)
+                    .modify_column(&mut quantity(StocktakeCount::CountedQuantity, false))
+                    .modify_column(&mut quantity(StocktakeCount::ExpectedQuantity, true))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(StocktakeCount::Table)
+                    .modify_column(&mut count(StocktakeCount::CountedQuantity, false))
+                    .modify_column(&mut count(StocktakeCount::ExpectedQuantity, true))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PurchaseOrderLine::Table)
+                    .modify_column(&mut count(PurchaseOrderLine::QuantityOrdered, false))
+                    .modify_column(&mut count(PurchaseOrderLine::QuantityReceived, false))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Lot::Table)
+                    .modify_column(&mut count(Lot::Quantity, false))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(StockMovement::Table)
+                    .modify_column(ColumnDef::new(StockMovement::Quantity).integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .modify_column(&mut count(Item::Quantity, false))
+                    .drop_column(Item::Unit)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Item {
+    Table,
+    Quantity,
+    Unit,
+}
+
+#[derive(Iden)]
+pub enum StockMovement {
+    Table,
+    Quantity,
+}
+
+#[derive(Iden)]
+pub enum Lot {
+    Table,
+    Quantity,
+}
+
+#[derive(Iden)]
+pub enum PurchaseOrderLine {
+    Table,
+    QuantityOrdered,
+    QuantityReceived,
+}
+
+#[derive(Iden)]
+pub enum StocktakeCount {
+    Table,
+    CountedQuantity,
+    ExpectedQuantity,
+}
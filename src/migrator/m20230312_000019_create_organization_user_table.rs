@@ -0,0 +1,106 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20230227_000001_create_user_table::User,
+    m20230312_000018_create_organization_table::Organization,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230312_000019_create_organization_user_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(OrganizationUser::Table)
+                    .col(
+                        ColumnDef::new(OrganizationUser::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationUser::OrganizationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-organization_user-organization_id")
+                            .from(OrganizationUser::Table, OrganizationUser::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationUser::UserId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-organization_user-user_id")
+                            .from(OrganizationUser::Table, OrganizationUser::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationUser::Role)
+                            .string()
+                            .not_null()
+                            .default("user"),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationUser::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationUser::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .name("idx-organization_user-organization_id-user_id")
+                            .col(OrganizationUser::OrganizationId)
+                            .col(OrganizationUser::UserId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrganizationUser::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum OrganizationUser {
+    Table,
+    Id,
+    OrganizationId,
+    UserId,
+    Role,
+    CreatedAt,
+    UpdatedAt,
+}
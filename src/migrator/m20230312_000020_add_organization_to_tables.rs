@@ -0,0 +1,162 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm::ConnectionTrait;
+use sea_orm_migration::prelude::*;
+use uuid::Uuid;
+
+use super::{
+    m20230227_000001_create_user_table::User,
+    m20230312_000018_create_organization_table::Organization,
+    m20230312_000019_create_organization_user_table::OrganizationUser,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230312_000020_add_organization_to_tables"
+    }
+}
+
+const TABLES: [Scoped; 5] = [
+    Scoped::Category,
+    Scoped::Item,
+    Scoped::Supplier,
+    Scoped::PurchaseOrder,
+    Scoped::Stocktake,
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Existing data and users move into a default organization, with
+        // each user keeping their current role there.
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Organization::Table)
+                    .columns([Organization::Uuid, Organization::Name])
+                    .values_panic([Uuid::new_v4().to_string().into(), "Default".into()])
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        let organization_id: i32 = db
+            .query_one(
+                manager.get_database_backend().build(
+                    Query::select()
+                        .column(Organization::Id)
+                        .from(Organization::Table)
+                        .order_by(Organization::Id, Order::Desc)
+                        .limit(1),
+                ),
+            )
+            .await?
+            .unwrap()
+            .try_get("", "id")?;
+
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(OrganizationUser::Table)
+                    .columns([
+                        OrganizationUser::OrganizationId,
+                        OrganizationUser::UserId,
+                        OrganizationUser::Role,
+                    ])
+                    .select_from(
+                        Query::select()
+                            .expr(Expr::val(organization_id))
+                            .column(User::Id)
+                            .column(User::Role)
+                            .from(User::Table)
+                            .to_owned(),
+                    )
+                    .unwrap()
+                    .to_owned(),
+            )
+            .await?;
+
+        for table in TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(ColumnDef::new(Scoped::OrganizationId).integer().null())
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(table)
+                        .value(Scoped::OrganizationId, organization_id.into())
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .modify_column(ColumnDef::new(Scoped::OrganizationId).integer().not_null())
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name(&format!("fk-{}-organization_id", table.to_string()))
+                                .from_tbl(table)
+                                .from_col(Scoped::OrganizationId)
+                                .to_tbl(Organization::Table)
+                                .to_col(Organization::Id),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_foreign_key(Alias::new(&format!(
+                            "fk-{}-organization_id",
+                            table.to_string()
+                        )))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Scoped::OrganizationId)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Iden, Clone, Copy)]
+enum Scoped {
+    Category,
+    Item,
+    Supplier,
+    PurchaseOrder,
+    Stocktake,
+    OrganizationId,
+}
@@ -0,0 +1,82 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::m20230312_000018_create_organization_table::Organization;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230313_000021_create_group_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(Group::Table)
+                    .col(
+                        ColumnDef::new(Group::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Group::Uuid).string().unique_key().not_null())
+                    .col(ColumnDef::new(Group::OrganizationId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-group-organization_id")
+                            .from(Group::Table, Group::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Group::Name).string().not_null())
+                    .col(ColumnDef::new(Group::Description).text())
+                    .col(ColumnDef::new(Group::Meta).text())
+                    .col(
+                        ColumnDef::new(Group::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(Group::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(ColumnDef::new(Group::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Group::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Group {
+    Table,
+    Id,
+    Uuid,
+    OrganizationId,
+    Name,
+    Description,
+    Meta,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+}
@@ -0,0 +1,88 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{m20230227_000001_create_user_table::User, m20230313_000021_create_group_table::Group};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230313_000022_create_group_user_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(GroupUser::Table)
+                    .col(
+                        ColumnDef::new(GroupUser::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GroupUser::GroupId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-group_user-group_id")
+                            .from(GroupUser::Table, GroupUser::GroupId)
+                            .to(Group::Table, Group::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(GroupUser::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-group_user-user_id")
+                            .from(GroupUser::Table, GroupUser::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(GroupUser::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(GroupUser::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .name("idx-group_user-group_id-user_id")
+                            .col(GroupUser::GroupId)
+                            .col(GroupUser::UserId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GroupUser::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum GroupUser {
+    Table,
+    Id,
+    GroupId,
+    UserId,
+    CreatedAt,
+    UpdatedAt,
+}
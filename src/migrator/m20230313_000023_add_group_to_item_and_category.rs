@@ -0,0 +1,78 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::m20230313_000021_create_group_table::Group;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230313_000023_add_group_to_item_and_category"
+    }
+}
+
+const TABLES: [Owned; 2] = [Owned::Category, Owned::Item];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(ColumnDef::new(Owned::GroupId).integer().null())
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name(&format!("fk-{}-group_id", table.to_string()))
+                                .from_tbl(table)
+                                .from_col(Owned::GroupId)
+                                .to_tbl(Group::Table)
+                                .to_col(Group::Id)
+                                .on_delete(ForeignKeyAction::SetNull),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_foreign_key(Alias::new(&format!("fk-{}-group_id", table.to_string())))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Owned::GroupId)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Iden, Clone, Copy)]
+enum Owned {
+    Category,
+    Item,
+    GroupId,
+}
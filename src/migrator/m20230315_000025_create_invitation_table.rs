@@ -0,0 +1,109 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20230227_000001_create_user_table::User,
+    m20230312_000018_create_organization_table::Organization,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230315_000025_create_invitation_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(Invitation::Table)
+                    .col(
+                        ColumnDef::new(Invitation::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::Uuid)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::OrganizationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invitation-organization_id")
+                            .from(Invitation::Table, Invitation::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Invitation::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invitation-user_id")
+                            .from(Invitation::Table, Invitation::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Invitation::Email).string().not_null())
+                    .col(
+                        ColumnDef::new(Invitation::Role)
+                            .string()
+                            .not_null()
+                            .default("user"),
+                    )
+                    .col(
+                        ColumnDef::new(Invitation::TokenHash)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Invitation::ExpiresAt).timestamp().not_null())
+                    .col(ColumnDef::new(Invitation::AcceptedAt).timestamp().null())
+                    .col(
+                        ColumnDef::new(Invitation::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Invitation::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Invitation {
+    Table,
+    Id,
+    Uuid,
+    OrganizationId,
+    UserId,
+    Email,
+    Role,
+    TokenHash,
+    ExpiresAt,
+    AcceptedAt,
+    CreatedAt,
+}
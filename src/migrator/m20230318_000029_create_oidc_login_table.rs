@@ -0,0 +1,68 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230318_000029_create_oidc_login_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(OidcLogin::Table)
+                    .col(
+                        ColumnDef::new(OidcLogin::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OidcLogin::StateHash)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OidcLogin::CodeVerifier).string().not_null())
+                    .col(ColumnDef::new(OidcLogin::Nonce).string().not_null())
+                    .col(ColumnDef::new(OidcLogin::ExpiresAt).timestamp().not_null())
+                    .col(
+                        ColumnDef::new(OidcLogin::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OidcLogin::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum OidcLogin {
+    Table,
+    Id,
+    StateHash,
+    CodeVerifier,
+    Nonce,
+    ExpiresAt,
+    CreatedAt,
+}
@@ -0,0 +1,206 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm::{ConnectionTrait, Statement};
+use sea_orm_migration::prelude::*;
+
+use super::m20230312_000018_create_organization_table::Organization;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230322_000034_create_location_table"
+    }
+}
+
+const TABLES: [Located; 2] = [Located::Item, Located::Stocktake];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .if_not_exists()
+                    .table(Location::Table)
+                    .col(
+                        ColumnDef::new(Location::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Location::Uuid)
+                            .string()
+                            .unique_key()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Location::OrganizationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-location-organization_id")
+                            .from(Location::Table, Location::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Location::Name).string().not_null())
+                    .col(ColumnDef::new(Location::Description).text())
+                    .col(ColumnDef::new(Location::Meta).text())
+                    .col(
+                        ColumnDef::new(Location::CreatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(
+                        ColumnDef::new(Location::UpdatedAt)
+                            .timestamp()
+                            .extra("DEFAULT CURRENT_TIMESTAMP".to_owned()),
+                    )
+                    .col(ColumnDef::new(Location::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Items point at a location instead of naming it. Stocktakes scoped to
+        // a location keep it, so locations with stocktakes cannot be deleted.
+        for table in TABLES {
+            let on_delete = match table {
+                Located::Item => ForeignKeyAction::SetNull,
+                _ => ForeignKeyAction::Restrict,
+            };
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(ColumnDef::new(Located::LocationId).integer().null())
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name(&format!("fk-{}-location_id", table.to_string()))
+                                .from_tbl(table)
+                                .from_col(Located::LocationId)
+                                .to_tbl(Location::Table)
+                                .to_col(Location::Id)
+                                .on_delete(on_delete),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        // Each distinct location named on an organization's items becomes one
+        // of its locations.
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        db.execute(Statement::from_string(
+            backend,
+            "INSERT INTO `location` (`uuid`, `organization_id`, `name`) \
+             SELECT UUID(), `organization_id`, `location` FROM `item` \
+             WHERE `location` IS NOT NULL AND `location` <> '' \
+             GROUP BY `organization_id`, `location`"
+                .to_owned(),
+        ))
+        .await?;
+
+        db.execute(Statement::from_string(
+            backend,
+            "UPDATE `item` SET `location_id` = (\
+             SELECT `location`.`id` FROM `location` \
+             WHERE `location`.`organization_id` = `item`.`organization_id` \
+             AND `location`.`name` = `item`.`location`)"
+                .to_owned(),
+        ))
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Located::Item)
+                    .drop_column(Located::Location)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Located::Item)
+                    .add_column(ColumnDef::new(Located::Location).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                manager.get_database_backend(),
+                "UPDATE `item` SET `location` = (\
+                 SELECT `location`.`name` FROM `location` \
+                 WHERE `location`.`id` = `item`.`location_id`)"
+                    .to_owned(),
+            ))
+            .await?;
+
+        for table in TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_foreign_key(Alias::new(&format!(
+                            "fk-{}-location_id",
+                            table.to_string()
+                        )))
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Located::LocationId)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(Location::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Location {
+    Table,
+    Id,
+    Uuid,
+    OrganizationId,
+    Name,
+    Description,
+    Meta,
+    CreatedAt,
+    UpdatedAt,
+    DeletedAt,
+}
+
+#[derive(Iden, Clone, Copy)]
+enum Located {
+    Item,
+    Stocktake,
+    Location,
+    LocationId,
+}
@@ -5,11 +5,48 @@
  * @link   https://afaan.dev
  * @link   https://github.com/AfaanBilal/iron-guard-server
  */
+use std::{
+    collections::HashMap,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use sea_orm::{prelude::DateTimeUtc, DatabaseConnection};
 use sea_orm_migration::prelude::*;
 
 mod m20230227_000001_create_user_table;
 mod m20230227_000002_create_category_table;
 mod m20230227_000003_create_item_table;
+mod m20230305_000004_create_attachment_table;
+mod m20230306_000005_add_valuation_to_item_table;
+mod m20230306_000006_create_stock_movement_table;
+mod m20230307_000007_create_supplier_table;
+mod m20230307_000008_create_supplier_item_table;
+mod m20230307_000009_create_purchase_order_table;
+mod m20230307_000010_create_purchase_order_line_table;
+mod m20230308_000011_create_stocktake_table;
+mod m20230308_000012_create_stocktake_count_table;
+mod m20230309_000013_create_lot_table;
+mod m20230310_000014_add_mode_to_item_table;
+mod m20230310_000015_create_asset_table;
+mod m20230310_000016_create_asset_event_table;
+mod m20230311_000017_add_unit_of_measure;
+mod m20230312_000018_create_organization_table;
+mod m20230312_000019_create_organization_user_table;
+mod m20230312_000020_add_organization_to_tables;
+mod m20230313_000021_create_group_table;
+mod m20230313_000022_create_group_user_table;
+mod m20230313_000023_add_group_to_item_and_category;
+mod m20230314_000024_create_password_reset_table;
+mod m20230315_000025_create_invitation_table;
+mod m20230316_000026_add_two_factor;
+mod m20230316_000027_create_recovery_code_table;
+mod m20230317_000028_create_api_key_table;
+mod m20230318_000029_create_oidc_login_table;
+mod m20230318_000030_add_oidc_subject_to_user;
+mod m20230319_000031_create_session_table;
+mod m20230320_000032_add_version_to_tables;
+mod m20230321_000033_add_location_to_item_table;
+mod m20230322_000034_create_location_table;
 
 pub struct Migrator;
 
@@ -20,6 +57,96 @@ impl MigratorTrait for Migrator {
             Box::new(m20230227_000001_create_user_table::Migration),
             Box::new(m20230227_000002_create_category_table::Migration),
             Box::new(m20230227_000003_create_item_table::Migration),
+            Box::new(m20230305_000004_create_attachment_table::Migration),
+            Box::new(m20230306_000005_add_valuation_to_item_table::Migration),
+            Box::new(m20230306_000006_create_stock_movement_table::Migration),
+            Box::new(m20230307_000007_create_supplier_table::Migration),
+            Box::new(m20230307_000008_create_supplier_item_table::Migration),
+            Box::new(m20230307_000009_create_purchase_order_table::Migration),
+            Box::new(m20230307_000010_create_purchase_order_line_table::Migration),
+            Box::new(m20230308_000011_create_stocktake_table::Migration),
+            Box::new(m20230308_000012_create_stocktake_count_table::Migration),
+            Box::new(m20230309_000013_create_lot_table::Migration),
+            Box::new(m20230310_000014_add_mode_to_item_table::Migration),
+            Box::new(m20230310_000015_create_asset_table::Migration),
+            Box::new(m20230310_000016_create_asset_event_table::Migration),
+            Box::new(m20230311_000017_add_unit_of_measure::Migration),
+            Box::new(m20230312_000018_create_organization_table::Migration),
+            Box::new(m20230312_000019_create_organization_user_table::Migration),
+            Box::new(m20230312_000020_add_organization_to_tables::Migration),
+            Box::new(m20230313_000021_create_group_table::Migration),
+            Box::new(m20230313_000022_create_group_user_table::Migration),
+            Box::new(m20230313_000023_add_group_to_item_and_category::Migration),
+            Box::new(m20230314_000024_create_password_reset_table::Migration),
+            Box::new(m20230315_000025_create_invitation_table::Migration),
+            Box::new(m20230316_000026_add_two_factor::Migration),
+            Box::new(m20230316_000027_create_recovery_code_table::Migration),
+            Box::new(m20230317_000028_create_api_key_table::Migration),
+            Box::new(m20230318_000029_create_oidc_login_table::Migration),
+            Box::new(m20230318_000030_add_oidc_subject_to_user::Migration),
+            Box::new(m20230319_000031_create_session_table::Migration),
+            Box::new(m20230320_000032_add_version_to_tables::Migration),
+            Box::new(m20230321_000033_add_location_to_item_table::Migration),
+            Box::new(m20230322_000034_create_location_table::Migration),
         ]
     }
 }
+
+/// A migration and when it was applied, if it was.
+pub struct MigrationState {
+    pub name: String,
+    pub applied_at: Option<DateTimeUtc>,
+}
+
+impl Migrator {
+    /// Every migration in order, and the applied ones the binary does not
+    /// know, which a newer version of the server has run.
+    pub async fn state(
+        db: &DatabaseConnection,
+    ) -> Result<(Vec<MigrationState>, Vec<String>), DbErr> {
+        let mut applied = Migrator::get_migration_models(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.version, m.applied_at))
+            .collect::<HashMap<_, _>>();
+
+        let migrations = Migrator::migrations()
+            .iter()
+            .map(|m| MigrationState {
+                name: m.name().to_string(),
+                applied_at: applied.remove(m.name()).map(|secs| {
+                    DateTimeUtc::from(UNIX_EPOCH + Duration::from_secs(secs as u64))
+                }),
+            })
+            .collect();
+
+        let mut unknown = applied.into_keys().collect::<Vec<_>>();
+        unknown.sort();
+
+        Ok((migrations, unknown))
+    }
+
+    /// Refuses a schema that is behind or ahead of the binary, so replicas
+    /// never serve against a database they do not match.
+    pub async fn check(db: &DatabaseConnection) -> Result<(), String> {
+        let (migrations, unknown) = Migrator::state(db).await.map_err(|e| e.to_string())?;
+
+        if !unknown.is_empty() {
+            return Err(format!(
+                "the database has migrations this version does not know: {}",
+                unknown.join(", ")
+            ));
+        }
+
+        let pending = migrations.iter().filter(|m| m.applied_at.is_none()).count();
+        if pending > 0 {
+            return Err(format!(
+                "the database schema is behind by {} migrations. \
+                 Run `iron-guard-server migrate up` or set IRON_GUARD_AUTO_MIGRATE=true",
+                pending
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::fmt;
+
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use rocket::serde::{json::Value, Deserialize};
+use sha2::{Digest, Sha256};
+
+use crate::Config;
+
+/// An OpenID Connect provider used for single sign-on with the authorization
+/// code flow and PKCE.
+pub struct Provider {
+    issuer: String,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    scopes: String,
+    http: reqwest::Client,
+}
+
+/// The endpoints published at `/.well-known/openid-configuration`.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Discovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// The verified ID token claims. `claims` holds all of them, for role mapping.
+#[derive(Debug)]
+pub struct Identity {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub name: Option<String>,
+    pub claims: Value,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The provider could not be reached or answered unexpectedly.
+    Provider(String),
+    /// The ID token failed verification.
+    InvalidToken(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Provider(message) => write!(f, "Identity provider error: {}", message),
+            Error::InvalidToken(message) => write!(f, "Invalid ID token: {}", message),
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Provider(err.to_string())
+    }
+}
+
+impl Provider {
+    /// The configured provider, or `None` when single sign-on is off.
+    pub fn make(config: &Config) -> Option<Provider> {
+        if config.oidc_issuer.is_empty() {
+            return None;
+        }
+
+        Some(Provider {
+            issuer: config.oidc_issuer.trim_end_matches('/').to_string(),
+            client_id: config.oidc_client_id.to_owned(),
+            client_secret: config.oidc_client_secret.to_owned(),
+            redirect_url: config.oidc_redirect_url.to_owned(),
+            scopes: config.oidc_scopes.to_owned(),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    pub async fn discover(&self) -> Result<Discovery, Error> {
+        Ok(self
+            .http
+            .get(format!("{}/.well-known/openid-configuration", self.issuer))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Discovery>()
+            .await?)
+    }
+
+    /// Where to send the user to sign in at the provider.
+    pub fn authorize_url(
+        &self,
+        discovery: &Discovery,
+        state: &str,
+        nonce: &str,
+        code_verifier: &str,
+    ) -> String {
+        let separator = if discovery.authorization_endpoint.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+
+        format!(
+            "{}{}response_type=code&client_id={}&redirect_uri={}&scope={}&state={}&nonce={}\
+             &code_challenge={}&code_challenge_method=S256",
+            discovery.authorization_endpoint,
+            separator,
+            encode(&self.client_id),
+            encode(&self.redirect_url),
+            encode(&self.scopes),
+            encode(state),
+            encode(nonce),
+            code_challenge(code_verifier)
+        )
+    }
+
+    /// Redeems an authorization code and verifies the returned ID token.
+    pub async fn exchange(
+        &self,
+        discovery: &Discovery,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<Identity, Error> {
+        let response = self
+            .http
+            .post(&discovery.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &self.redirect_url),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(Error::Provider(format!(
+                "token endpoint answered {}",
+                response.status()
+            )));
+        }
+
+        let token = response.json::<TokenResponse>().await?;
+
+        self.verify(discovery, &token.id_token, nonce).await
+    }
+
+    /// Checks the ID token's signature against the provider's published keys,
+    /// and its issuer, audience, expiry and nonce.
+    async fn verify(
+        &self,
+        discovery: &Discovery,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<Identity, Error> {
+        let header = decode_header(id_token).map_err(|e| Error::InvalidToken(e.to_string()))?;
+
+        // Shared-secret algorithms would let anyone knowing the client secret sign tokens.
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(Error::InvalidToken("unsupported algorithm".to_string()));
+        }
+
+        let jwks = self
+            .http
+            .get(&discovery.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        }
+        .ok_or_else(|| Error::InvalidToken("unknown signing key".to_string()))?;
+
+        let key = DecodingKey::from_jwk(jwk).map_err(|e| Error::InvalidToken(e.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&self.client_id]);
+        validation.set_issuer(&[&self.issuer]);
+
+        let claims = decode::<Value>(id_token, &key, &validation)
+            .map_err(|e| Error::InvalidToken(e.to_string()))?
+            .claims;
+
+        if claims["nonce"].as_str() != Some(nonce) {
+            return Err(Error::InvalidToken("nonce mismatch".to_string()));
+        }
+
+        let string = |name: &str| claims[name].as_str().map(|s| s.to_string());
+
+        Ok(Identity {
+            subject: string("sub")
+                .ok_or_else(|| Error::InvalidToken("missing subject".to_string()))?,
+            email: string("email"),
+            email_verified: claims["email_verified"].as_bool().unwrap_or(false),
+            given_name: string("given_name"),
+            family_name: string("family_name"),
+            name: string("name"),
+            claims,
+        })
+    }
+}
+
+/// The S256 PKCE challenge for a verifier (RFC 7636).
+pub fn code_challenge(code_verifier: &str) -> String {
+    base64::encode_config(
+        Sha256::digest(code_verifier.as_bytes()),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+/// Whether the claim is `role`, or a list containing it.
+pub fn has_role(claims: &Value, claim: &str, role: &str) -> bool {
+    match &claims[claim] {
+        Value::String(s) => s == role,
+        Value::Array(values) => values.iter().any(|v| v.as_str() == Some(role)),
+        _ => false,
+    }
+}
+
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,88 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use rocket::tokio::fs;
+
+use crate::Config;
+
+/// An object store for uploaded files.
+///
+/// Objects are addressed by a flat key (`items/<uuid>/<uuid>.png`), the same way
+/// an S3-compatible bucket addresses them, so a remote backend can be added
+/// later without touching the controllers.
+#[rocket::async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, data: &[u8]) -> io::Result<()>;
+    async fn get(&self, key: &str) -> io::Result<Vec<u8>>;
+    async fn delete(&self, key: &str) -> io::Result<()>;
+}
+
+pub type StorageBackend = Box<dyn Storage>;
+
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new<P: AsRef<Path>>(root: P) -> LocalStorage {
+        LocalStorage {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        let key = Path::new(key);
+
+        if key
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid storage key",
+            ));
+        }
+
+        Ok(self.root.join(key))
+    }
+}
+
+#[rocket::async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::write(path, data).await
+    }
+
+    async fn get(&self, key: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.path(key)?).await
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)?).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn make(config: &Config) -> Result<StorageBackend, String> {
+    match config.storage_type.as_str() {
+        "local" => Ok(Box::new(LocalStorage::new(&config.storage_path))),
+        other => Err(format!("Unsupported storage type: {}", other)),
+    }
+}
@@ -0,0 +1,219 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rand::Rng;
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    Orbit, Rocket,
+};
+use serde_json::{json, Value};
+
+use crate::Config;
+
+/// Spans beyond this many are dropped while the collector is unreachable.
+const MAX_QUEUED: usize = 10_000;
+
+/// How often queued spans are sent.
+const EXPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy)]
+pub enum SpanKind {
+    Server = 2,
+    Client = 3,
+}
+
+pub struct Span {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub kind: SpanKind,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub attributes: Vec<(&'static str, Value)>,
+    pub error: bool,
+}
+
+/// Exports spans to an OpenTelemetry collector with OTLP over HTTP, as JSON.
+#[derive(Clone)]
+pub struct Tracer {
+    endpoint: String,
+    service_name: String,
+    http: reqwest::Client,
+    queue: Arc<Mutex<Vec<Value>>>,
+}
+
+impl Tracer {
+    /// A tracer sending to `IRON_GUARD_OTEL_ENDPOINT`, or none when it is blank.
+    pub fn make(config: &Config) -> Option<Tracer> {
+        if config.otel_endpoint.is_empty() {
+            return None;
+        }
+
+        Some(Tracer {
+            endpoint: format!("{}/v1/traces", config.otel_endpoint.trim_end_matches('/')),
+            service_name: config.otel_service_name.to_owned(),
+            http: reqwest::Client::new(),
+            queue: Arc::default(),
+        })
+    }
+
+    pub fn record(&self, span: Span) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.len() < MAX_QUEUED {
+            queue.push(span.to_otlp());
+        }
+    }
+
+    /// Records a database query as a span of its own, since queries do not
+    /// know the request they run for.
+    pub fn record_query(&self, sql: &str, elapsed: Duration, failed: bool) {
+        let end = SystemTime::now();
+
+        self.record(Span {
+            trace_id: random_id(16),
+            span_id: random_id(8),
+            parent_span_id: None,
+            name: "db.query".to_string(),
+            kind: SpanKind::Client,
+            start: end - elapsed,
+            end,
+            attributes: vec![("db.system", json!("mysql")), ("db.statement", json!(sql))],
+            error: failed,
+        });
+    }
+
+    /// Sends the queued spans. They are dropped if the collector refuses them.
+    pub async fn export(&self) -> Result<(), reqwest::Error> {
+        let spans = std::mem::take(&mut *self.queue.lock().unwrap());
+        if spans.is_empty() {
+            return Ok(());
+        }
+
+        let body = json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [attribute("service.name", &json!(self.service_name))],
+                },
+                "scopeSpans": [{
+                    "scope": { "name": "iron-guard-server" },
+                    "spans": spans,
+                }],
+            }],
+        });
+
+        self.http
+            .post(&self.endpoint)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+impl Span {
+    fn to_otlp(&self) -> Value {
+        let mut span = json!({
+            "traceId": self.trace_id,
+            "spanId": self.span_id,
+            "name": self.name,
+            "kind": self.kind as u8,
+            "startTimeUnixNano": unix_nanos(self.start),
+            "endTimeUnixNano": unix_nanos(self.end),
+            "attributes": self
+                .attributes
+                .iter()
+                .map(|(key, value)| attribute(key, value))
+                .collect::<Vec<_>>(),
+            "status": { "code": if self.error { 2 } else { 1 } },
+        });
+
+        if let Some(parent) = &self.parent_span_id {
+            span["parentSpanId"] = json!(parent);
+        }
+
+        span
+    }
+}
+
+fn attribute(key: &str, value: &Value) -> Value {
+    let value = match value {
+        Value::Bool(b) => json!({ "boolValue": b }),
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({ "intValue": n.to_string() }),
+        Value::Number(n) => json!({ "doubleValue": n }),
+        Value::String(s) => json!({ "stringValue": s }),
+        other => json!({ "stringValue": other.to_string() }),
+    };
+
+    json!({ "key": key, "value": value })
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+/// A random hex id of `bytes` bytes: 16 for traces, 8 for spans.
+pub fn random_id(bytes: usize) -> String {
+    let mut rng = rand::thread_rng();
+    hex::encode((0..bytes).map(|_| rng.gen::<u8>()).collect::<Vec<_>>())
+}
+
+/// The trace id and parent span id of a W3C `traceparent` header.
+pub fn parse_traceparent(header: &str) -> Option<(String, String)> {
+    let parts = header.trim().split('-').collect::<Vec<_>>();
+
+    let is_id = |id: &str, len: usize| {
+        id.len() == len
+            && id
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+            && id.chars().any(|c| c != '0')
+    };
+
+    match parts[..] {
+        ["00", trace_id, parent_id, flags]
+            if is_id(trace_id, 32) && is_id(parent_id, 16) && flags.len() == 2 =>
+        {
+            Some((trace_id.to_string(), parent_id.to_string()))
+        }
+        _ => None,
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Tracer {
+    fn info(&self) -> Info {
+        Info {
+            name: "Export traces",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, _: &Rocket<Orbit>) {
+        let tracer = self.clone();
+
+        rocket::tokio::spawn(async move {
+            loop {
+                rocket::tokio::time::sleep(EXPORT_INTERVAL).await;
+
+                if let Err(err) = tracer.export().await {
+                    log::warn!("Could not export traces: {}", err);
+                }
+            }
+        });
+    }
+}
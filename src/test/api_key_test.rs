@@ -0,0 +1,187 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::controllers::{api_keys::ResponseNewApiKey, auth::ResponseSignIn};
+use crate::entities::{organization, prelude::*};
+use crate::test::utils::{get_auth_header, get_client, get_db};
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+use sea_orm::*;
+use serde_json::json;
+
+#[async_test]
+async fn should_reject_unknown_key() {
+    let client = get_client().await;
+
+    let response = client
+        .get("/items")
+        .header(Header::new("X-API-Key", "igk_unknown"))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+#[async_test]
+async fn should_limit_key_expiry() {
+    let client = get_client().await;
+
+    for days in [366, u64::MAX] {
+        let response = client
+            .post("/me/api-keys")
+            .header(get_auth_header(false).await)
+            .body(json!({ "name": "Forever", "expires_in_days": days }).to_string())
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+}
+
+#[async_test]
+async fn should_use_and_revoke_key() {
+    let client = get_client().await;
+    let auth = get_auth_header(false).await;
+
+    let response = client
+        .post("/me/api-keys")
+        .header(auth.clone())
+        .body(json!({ "name": "Reporting", "read_only": true }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let new_key = response.into_json::<ResponseNewApiKey>().await.unwrap();
+    let api_key = Header::new("X-API-Key", new_key.key.to_owned());
+
+    let response = client
+        .get("/items")
+        .header(api_key.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    // Read-only keys cannot change anything, including minting more keys.
+    let response = client
+        .post("/me/api-keys")
+        .header(api_key.clone())
+        .body(json!({ "name": "Another" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let response = client
+        .get("/me/api-keys")
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.into_string().await.unwrap().contains("Reporting"));
+
+    let response = client
+        .delete(format!("/me/api-keys/{}", new_key.uuid))
+        .header(auth)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.get("/items").header(api_key).dispatch().await;
+
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+async fn switch(
+    client: &Client,
+    auth: Header<'static>,
+    organization_uuid: &str,
+) -> Header<'static> {
+    let response = client
+        .post("/auth/switch")
+        .header(auth)
+        .body(json!({ "organization_uuid": organization_uuid }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let r = response.into_json::<ResponseSignIn>().await.unwrap();
+
+    Header::new("token", r.token)
+}
+
+#[async_test]
+async fn should_withhold_admin_role_without_two_factor() {
+    let client = get_client().await;
+    let db = get_db().await;
+
+    let response = client
+        .post("/organizations")
+        .header(get_auth_header(true).await)
+        .body(json!({ "name": "api-key-two-factor" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    let org = Organization::find()
+        .filter(organization::Column::Name.eq("api-key-two-factor"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let auth = switch(&client, get_auth_header(true).await, &org.uuid).await;
+
+    let response = client
+        .post("/me/api-keys")
+        .header(auth.clone())
+        .body(json!({ "name": "Admin" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let new_key = response.into_json::<ResponseNewApiKey>().await.unwrap();
+    let api_key = Header::new("X-API-Key", new_key.key);
+
+    let response = client
+        .get("/migrations")
+        .header(api_key.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let mut requiring: organization::ActiveModel = org.clone().into();
+    requiring.require_admin_two_factor = Set(true);
+    requiring.update(&db).await.unwrap();
+
+    // The admin has no TOTP set up, so the key acts as a user, and a token
+    // without the admin role cannot create keys that would regain it.
+    let response = client.get("/migrations").header(api_key).dispatch().await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let auth = switch(&client, auth, &org.uuid).await;
+
+    let response = client
+        .post("/me/api-keys")
+        .header(auth)
+        .body(json!({ "name": "Another" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+
+    org.delete(&db).await.unwrap();
+}
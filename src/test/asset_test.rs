@@ -0,0 +1,152 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::entities::{prelude::*, user};
+use crate::test::utils::{
+    delete_test_item, get_auth_header, get_client, get_db, get_test_item_uuid,
+    get_test_organization_id,
+};
+use rocket::http::Status;
+use sea_orm::{prelude::Decimal, *};
+use serde_json::{json, Value};
+
+#[async_test]
+async fn should_not_add_asset_to_bulk_item() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    client
+        .post("/items")
+        .header(auth.clone())
+        .body(json!({ "name": "test", "quantity": 1 }).to_string())
+        .dispatch()
+        .await;
+
+    let uuid = get_test_item_uuid().await;
+
+    let response = client
+        .post(format!("/items/{}/assets", uuid))
+        .header(auth.clone())
+        .body(json!({ "serial_number": "SN-TEST-0" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    delete_test_item().await;
+}
+
+#[async_test]
+async fn should_track_serialized_assets() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    client
+        .post("/items")
+        .header(auth.clone())
+        .body(json!({ "name": "test", "quantity": 9, "mode": "serialized" }).to_string())
+        .dispatch()
+        .await;
+
+    let uuid = get_test_item_uuid().await;
+    let db = get_db().await;
+
+    let response = client
+        .post(format!("/items/{}/assets", uuid))
+        .header(auth.clone())
+        .body(json!({ "serial_number": "SN-TEST-1", "purchase_date": "2023-01-15" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    let item = Item::from_uuid(&db, get_test_organization_id().await, &uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.quantity, Decimal::from(1));
+
+    let assignee = User::find()
+        .filter(user::Column::Email.eq("user@example.com"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let response = client
+        .get(format!("/items/{}/assets", uuid))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    let r: Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let asset_uuid = r["results"][0]["uuid"].as_str().unwrap().to_string();
+
+    let response = client
+        .put(format!("/items/{}/assets/{}", uuid, asset_uuid))
+        .header(auth.clone())
+        .body(
+            json!({
+                "serial_number": "SN-TEST-1",
+                "status": "assigned",
+                "assignee_uuid": assignee.uuid
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .put(format!("/items/{}/assets/{}", uuid, asset_uuid))
+        .header(auth.clone())
+        .body(json!({ "serial_number": "SN-TEST-1", "status": "retired" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .get(format!("/items/{}/assets/{}", uuid, asset_uuid))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    let r: Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    assert_eq!(r["status"], "retired");
+    assert_eq!(r["history"].as_array().unwrap().len(), 3);
+
+    let item = Item::from_uuid(&db, get_test_organization_id().await, &uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.quantity, Decimal::from(0));
+
+    // Serialized items are neither received in lots nor turned back into bulk
+    // items while they have assets.
+    let response = client
+        .post(format!("/items/{}/lots", uuid))
+        .header(auth.clone())
+        .body(json!({ "batch_number": "B-TEST", "quantity": 2 }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    let response = client
+        .put(format!("/items/{}", uuid))
+        .header(auth.clone())
+        .body(json!({ "name": "test", "quantity": 5, "mode": "bulk" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Conflict);
+
+    delete_test_item().await;
+}
@@ -0,0 +1,111 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::test::utils::{get_auth_header, get_client, get_test_item_uuid};
+use rocket::http::{ContentType, Status};
+use serde_json::json;
+
+const BOUNDARY: &str = "iron-guard-boundary";
+
+fn multipart_body(filename: &str, content_type: &str, content: &str) -> String {
+    format!(
+        "--{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{f}\"\r\nContent-Type: {t}\r\n\r\n{c}\r\n--{b}--\r\n",
+        b = BOUNDARY,
+        f = filename,
+        t = content_type,
+        c = content
+    )
+}
+
+fn multipart_content_type() -> ContentType {
+    ContentType::new("multipart", "form-data").with_params(("boundary", BOUNDARY))
+}
+
+#[async_test]
+async fn should_404_attachments_for_missing_item() {
+    let client = get_client().await;
+
+    let response = client
+        .get("/items/does-not-exist/attachments")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[async_test]
+async fn should_reject_disallowed_attachment_type() {
+    let client = get_client().await;
+
+    client
+        .post("/items")
+        .header(get_auth_header(true).await)
+        .body(json!({ "name": "test", "quantity": 1 }).to_string())
+        .dispatch()
+        .await;
+
+    let uuid = get_test_item_uuid().await;
+
+    let response = client
+        .post(format!("/items/{}/attachments", uuid))
+        .header(get_auth_header(true).await)
+        .header(multipart_content_type())
+        .body(multipart_body("run.sh", "application/x-sh", "echo hi"))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnsupportedMediaType);
+
+    client
+        .delete(format!("/items/{}", uuid))
+        .header(get_auth_header(true).await)
+        .dispatch()
+        .await;
+}
+
+#[async_test]
+async fn should_add_attachment() {
+    let client = get_client().await;
+
+    client
+        .post("/items")
+        .header(get_auth_header(true).await)
+        .body(json!({ "name": "test", "quantity": 1 }).to_string())
+        .dispatch()
+        .await;
+
+    let uuid = get_test_item_uuid().await;
+
+    let response = client
+        .post(format!("/items/{}/attachments", uuid))
+        .header(get_auth_header(true).await)
+        .header(multipart_content_type())
+        .body(multipart_body("manual.txt", "text/plain", "Read me first."))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    let response = client
+        .get(format!("/items/{}", uuid))
+        .header(get_auth_header(true).await)
+        .dispatch()
+        .await;
+
+    let r = response.into_string().await.unwrap();
+    assert!(r.contains("manual.txt"));
+
+    let response = client
+        .delete(format!("/items/{}", uuid))
+        .header(get_auth_header(true).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+}
@@ -0,0 +1,159 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{env, fs};
+
+use clap::Parser;
+use rocket::http::Status;
+use sea_orm::*;
+use serde_json::{json, Value};
+
+use crate::cli::{self, Cli, Command, MigrateAction};
+use crate::entities::{item, organization, prelude::*, user};
+use crate::test::utils::{get_client, get_db, get_test_organization_id};
+
+fn parse(args: &[&str]) -> Option<Command> {
+    Cli::try_parse_from([&["iron-guard-server"], args].concat())
+        .unwrap()
+        .command
+}
+
+#[test]
+fn should_parse_commands() {
+    assert!(parse(&[]).is_none());
+    assert!(matches!(parse(&["serve"]), Some(Command::Serve)));
+
+    match parse(&["migrate", "down"]) {
+        Some(Command::Migrate {
+            action: MigrateAction::Down { steps },
+        }) => assert_eq!(steps, 1),
+        _ => panic!("expected migrate down"),
+    }
+
+    match parse(&["create-admin", "--email", "root@example.com"]) {
+        Some(Command::CreateAdmin {
+            email,
+            firstname,
+            password,
+            organization,
+            ..
+        }) => {
+            assert_eq!(email, "root@example.com");
+            assert_eq!(firstname, "Admin");
+            assert!(password.is_none());
+            assert!(organization.is_none());
+        }
+        _ => panic!("expected create-admin"),
+    }
+}
+
+#[test]
+fn should_reject_missing_arguments() {
+    assert!(Cli::try_parse_from(["iron-guard-server", "create-admin"]).is_err());
+    assert!(Cli::try_parse_from(["iron-guard-server", "migrate"]).is_err());
+    assert!(Cli::try_parse_from(["iron-guard-server", "import", "--admin", "a@b.c"]).is_err());
+}
+
+#[async_test]
+async fn should_create_admin_and_reset_password() {
+    let client = get_client().await;
+    let db = get_db().await;
+
+    let organization = Organization::find_by_id(get_test_organization_id().await)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    cli::run(Command::CreateAdmin {
+        email: "cli@example.com".to_string(),
+        firstname: "Cli".to_string(),
+        lastname: "Admin".to_string(),
+        password: Some("cli12345".to_string()),
+        organization: Some(organization.uuid),
+    })
+    .await
+    .unwrap();
+
+    assert!(cli::run(Command::CreateAdmin {
+        email: "cli@example.com".to_string(),
+        firstname: "Cli".to_string(),
+        lastname: "Admin".to_string(),
+        password: None,
+        organization: None,
+    })
+    .await
+    .is_err());
+
+    cli::run(Command::ResetPassword {
+        email: "cli@example.com".to_string(),
+        password: Some("cli67890".to_string()),
+    })
+    .await
+    .unwrap();
+
+    let response = client
+        .post("/auth/sign-in")
+        .body(json!({"email": "cli@example.com", "password": "cli67890"}).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    User::delete_many()
+        .filter(user::Column::Email.eq("cli@example.com"))
+        .exec(&db)
+        .await
+        .unwrap();
+}
+
+#[async_test]
+async fn should_export_and_import_organization() {
+    let db = get_db().await;
+
+    let organization = Organization::find_by_id(get_test_organization_id().await)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let path = env::temp_dir().join(format!("iron-guard-export-{}.json", organization.uuid));
+
+    cli::run(Command::Export {
+        organization: organization.uuid,
+        output: Some(path.clone()),
+    })
+    .await
+    .unwrap();
+
+    let export: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+    cli::run(Command::Import {
+        admin: "admin@example.com".to_string(),
+        file: path.clone(),
+    })
+    .await
+    .unwrap();
+
+    fs::remove_file(path).unwrap();
+
+    let imported = Organization::find()
+        .filter(organization::Column::Name.eq(organization.name))
+        .order_by_desc(organization::Column::Id)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let items = Item::find()
+        .filter(item::Column::OrganizationId.eq(imported.id))
+        .count(&db)
+        .await
+        .unwrap();
+
+    assert_eq!(items as usize, export["items"].as_array().unwrap().len());
+}
@@ -0,0 +1,130 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::collections::HashMap;
+
+use crate::Config;
+
+const FILE: &str = r#"
+db_host = "db"
+token_ttl = 30
+upload_allowed_types = ["image/PNG", "application/pdf"]
+rate_limit_api = "off"
+
+[release]
+db_host = "prod-db"
+cors_origins = ["https://app.example.com"]
+"#;
+
+fn parse(profile: &str, file: Option<&str>, env: &[(&str, &str)]) -> Result<Config, Vec<String>> {
+    let env = env
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<HashMap<_, _>>();
+
+    Config::parse(
+        profile,
+        file.map(|text| ("iron-guard.toml", text)),
+        &|name| env.get(name).cloned(),
+    )
+    .map_err(|err| err.0)
+}
+
+#[test]
+fn should_use_defaults() {
+    let config = parse("debug", None, &[]).unwrap();
+
+    assert_eq!(config.db_host, "localhost");
+    assert_eq!(config.token_ttl, 4 * 60);
+    assert_eq!(config.cors_origins, ["*"]);
+    assert_eq!(config.auth_providers, ["local"]);
+    assert!(config.rate_limit_api.is_some());
+    assert!(config.api_keys);
+}
+
+#[test]
+fn should_apply_profile_overrides() {
+    let debug = parse("debug", Some(FILE), &[]).unwrap();
+
+    assert_eq!(debug.db_host, "db");
+    assert_eq!(debug.token_ttl, 30);
+    assert_eq!(debug.upload_allowed_types, ["image/png", "application/pdf"]);
+    assert!(debug.rate_limit_api.is_none());
+    assert_eq!(debug.cors_origins, ["*"]);
+
+    let release = parse("release", Some(FILE), &[]).unwrap();
+
+    assert_eq!(release.db_host, "prod-db");
+    assert_eq!(release.token_ttl, 30);
+    assert_eq!(release.cors_origins, ["https://app.example.com"]);
+}
+
+#[test]
+fn should_prefer_environment() {
+    let config = parse(
+        "release",
+        Some(FILE),
+        &[
+            ("IRON_GUARD_DB_HOST", "env-db"),
+            ("IRON_GUARD_SECRET", "12345"),
+            ("IRON_GUARD_LDAP_ADMIN_GROUPS", "cn=a,dc=org; cn=b,dc=org"),
+            ("IRON_GUARD_API_KEYS", "false"),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(config.db_host, "env-db");
+    assert_eq!(config.secret, "12345");
+    assert_eq!(config.ldap_admin_groups, ["cn=a,dc=org", "cn=b,dc=org"]);
+    assert!(!config.api_keys);
+}
+
+#[test]
+fn should_report_every_problem() {
+    let problems = match parse(
+        "debug",
+        Some("db_hots = \"db\"\napp_url = \"localhost:3000\"\n"),
+        &[
+            ("IRON_GUARD_TOKEN_TTL", "4h"),
+            ("IRON_GUARD_PASSWORD_SIGN_IN", "maybe"),
+            ("IRON_GUARD_RATE_LIMIT_API", "fast"),
+            ("IRON_GUARD_DB_MIN_CONNECTIONS", "20"),
+            ("IRON_GUARD_CORS_CREDENTIALS", "true"),
+        ],
+    ) {
+        Ok(_) => panic!("expected problems"),
+        Err(problems) => problems,
+    };
+
+    let expected = [
+        "IRON_GUARD_TOKEN_TTL (token_ttl): expected a number",
+        "IRON_GUARD_PASSWORD_SIGN_IN (password_sign_in): expected true or false",
+        "IRON_GUARD_RATE_LIMIT_API (rate_limit_api): expected <requests>/<seconds> or off",
+        "db_hots: unknown setting",
+        "IRON_GUARD_DB_MIN_CONNECTIONS: must not exceed",
+        "IRON_GUARD_APP_URL: must be an http:// or https:// URL",
+        "IRON_GUARD_CORS_CREDENTIALS: browsers refuse credentials",
+    ];
+
+    assert_eq!(problems.len(), expected.len(), "{:?}", problems);
+    for message in expected {
+        assert!(
+            problems.iter().any(|p| p.starts_with(message)),
+            "missing {:?} in {:?}",
+            message,
+            problems
+        );
+    }
+}
+
+#[test]
+fn should_reject_malformed_file() {
+    let problems = parse("debug", Some("db_host = "), &[]).err().unwrap();
+
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].starts_with("iron-guard.toml: "));
+}
@@ -0,0 +1,162 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::collections::HashMap;
+
+use rocket::http::{Header, Status};
+use rocket::local::blocking::Client;
+
+use crate::cors::CORS;
+use crate::Config;
+
+#[get("/")]
+fn index() -> &'static str {
+    "Iron Guard"
+}
+
+fn client(env: &[(&str, &str)]) -> Client {
+    let env = env
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<HashMap<_, _>>();
+
+    let config = Config::parse("debug", None, &|name| env.get(name).cloned()).unwrap();
+
+    Client::tracked(
+        rocket::build()
+            .attach(CORS::new(&config))
+            .mount("/", routes![index]),
+    )
+    .unwrap()
+}
+
+const ORIGINS: (&str, &str) = (
+    "IRON_GUARD_CORS_ORIGINS",
+    "https://app.example.com,https://admin.example.com",
+);
+
+#[test]
+fn should_echo_allowed_origin() {
+    let client = client(&[ORIGINS, ("IRON_GUARD_CORS_CREDENTIALS", "true")]);
+
+    let response = client
+        .get("/")
+        .header(Header::new("Origin", "https://admin.example.com"))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let headers = response.headers();
+    assert_eq!(
+        headers.get_one("Access-Control-Allow-Origin"),
+        Some("https://admin.example.com")
+    );
+    assert_eq!(
+        headers.get_one("Access-Control-Allow-Credentials"),
+        Some("true")
+    );
+    assert_eq!(headers.get_one("Vary"), Some("Origin"));
+}
+
+#[test]
+fn should_not_allow_other_origin() {
+    let client = client(&[ORIGINS]);
+
+    let response = client
+        .get("/")
+        .header(Header::new("Origin", "https://evil.example.com"))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .is_none());
+}
+
+#[test]
+fn should_allow_any_origin_without_credentials() {
+    let client = client(&[]);
+
+    let response = client
+        .get("/")
+        .header(Header::new("Origin", "https://any.example.com"))
+        .dispatch();
+
+    let headers = response.headers();
+    assert_eq!(headers.get_one("Access-Control-Allow-Origin"), Some("*"));
+    assert!(headers
+        .get_one("Access-Control-Allow-Credentials")
+        .is_none());
+    assert!(headers.get_one("Vary").is_none());
+}
+
+#[test]
+fn should_answer_preflight() {
+    let client = client(&[ORIGINS, ("IRON_GUARD_CORS_MAX_AGE", "600")]);
+
+    let response = client
+        .options("/items/1")
+        .header(Header::new("Origin", "https://app.example.com"))
+        .header(Header::new("Access-Control-Request-Method", "PUT"))
+        .header(Header::new(
+            "Access-Control-Request-Headers",
+            "content-type, token",
+        ))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::NoContent);
+
+    let headers = response.headers();
+    assert_eq!(
+        headers.get_one("Access-Control-Allow-Origin"),
+        Some("https://app.example.com")
+    );
+    assert_eq!(
+        headers.get_one("Access-Control-Allow-Methods"),
+        Some("GET, POST, PUT, PATCH, DELETE")
+    );
+    assert_eq!(headers.get_one("Access-Control-Max-Age"), Some("600"));
+}
+
+#[test]
+fn should_reject_disallowed_preflight() {
+    let client = client(&[ORIGINS, ("IRON_GUARD_CORS_METHODS", "get,post")]);
+
+    let preflight = |origin: &str, method: &str, headers: &str| {
+        client
+            .options("/")
+            .header(Header::new("Origin", origin.to_string()))
+            .header(Header::new(
+                "Access-Control-Request-Method",
+                method.to_string(),
+            ))
+            .header(Header::new(
+                "Access-Control-Request-Headers",
+                headers.to_string(),
+            ))
+            .dispatch()
+            .status()
+    };
+
+    assert_eq!(
+        preflight("https://app.example.com", "POST", "token"),
+        Status::NoContent
+    );
+    assert_eq!(
+        preflight("https://evil.example.com", "POST", "token"),
+        Status::Forbidden
+    );
+    assert_eq!(
+        preflight("https://app.example.com", "DELETE", "token"),
+        Status::Forbidden
+    );
+    assert_eq!(
+        preflight("https://app.example.com", "POST", "token, X-Custom"),
+        Status::Forbidden
+    );
+}
@@ -0,0 +1,120 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use rocket::http::{Header, Status};
+use sea_orm::*;
+use serde_json::json;
+
+use crate::entities::{item, prelude::*, stock_movement};
+use crate::etag::Preconditions;
+use crate::test::utils::{get_auth_header, get_client, get_db};
+
+#[async_test]
+async fn should_update_items_only_while_unchanged() {
+    let client = get_client().await;
+    let db = get_db().await;
+
+    let response = client
+        .post("/items")
+        .header(get_auth_header(false).await)
+        .body(json!({ "name": "etag", "quantity": 5 }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Created);
+
+    let item = Item::find()
+        .filter(item::Column::Name.eq("etag"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    let path = format!("/items/{}", item.uuid);
+
+    let response = client
+        .get(path.clone())
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let etag = response.headers().get_one("ETag").unwrap().to_string();
+
+    let response = client
+        .get(path.clone())
+        .header(get_auth_header(false).await)
+        .header(Header::new("If-None-Match", etag.clone()))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::NotModified);
+
+    let update = json!({ "name": "etag", "quantity": 4 }).to_string();
+
+    let response = client
+        .put(path.clone())
+        .header(get_auth_header(false).await)
+        .header(Header::new("If-Match", etag.clone()))
+        .body(update.clone())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    // The first update changed the item, so a second one based on the same
+    // copy is refused.
+    let response = client
+        .put(path.clone())
+        .header(get_auth_header(false).await)
+        .header(Header::new("If-Match", etag.clone()))
+        .body(update)
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::PreconditionFailed);
+
+    let response = client
+        .delete(path.clone())
+        .header(get_auth_header(false).await)
+        .header(Header::new("If-Match", etag.clone()))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::PreconditionFailed);
+
+    let response = client
+        .get(path)
+        .header(get_auth_header(false).await)
+        .header(Header::new("If-None-Match", etag.clone()))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    assert_ne!(response.headers().get_one("ETag"), Some(etag.as_str()));
+
+    StockMovement::delete_many()
+        .filter(stock_movement::Column::ItemId.eq(item.id))
+        .exec(&db)
+        .await
+        .unwrap();
+    item.delete(&db).await.unwrap();
+}
+
+#[test]
+fn should_match_entity_tags() {
+    let none = Preconditions::new(None, None);
+    assert!(none.allows("\"3\""));
+    assert!(!none.not_modified("\"3\""));
+
+    let current = Preconditions::new(Some("\"2\", \"3\""), Some("\"1\", W/\"3\""));
+    assert!(current.allows("\"3\""));
+    assert!(current.not_modified("\"3\""));
+
+    let stale = Preconditions::new(Some("\"2\""), Some("\"2\""));
+    assert!(!stale.allows("\"3\""));
+    assert!(!stale.not_modified("\"3\""));
+
+    // Weak tags never satisfy If-Match.
+    assert!(!Preconditions::new(Some("W/\"3\""), None).allows("\"3\""));
+
+    let any = Preconditions::new(Some("*"), Some("*"));
+    assert!(any.allows("\"3\""));
+    assert!(any.not_modified("\"3\""));
+}
@@ -0,0 +1,90 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::entities::{group, prelude::*, user};
+use crate::test::utils::{get_auth_header, get_client, get_db};
+use rocket::http::Status;
+use sea_orm::*;
+use serde_json::json;
+
+#[async_test]
+async fn should_not_create_group_as_user() {
+    let client = get_client().await;
+
+    let response = client
+        .post("/groups")
+        .header(get_auth_header(false).await)
+        .body(json!({ "name": "test" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[async_test]
+async fn should_add_group_members() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    let response = client
+        .post("/groups")
+        .header(auth.clone())
+        .body(json!({ "name": "test-group", "description": "Test" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    let db = get_db().await;
+    let group = Group::find()
+        .filter(group::Column::Name.eq("test-group"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    let admin = User::find()
+        .filter(user::Column::Email.eq("admin@example.com"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let response = client
+        .put(format!("/groups/{}/members", group.uuid))
+        .header(auth.clone())
+        .body(json!({ "user_uuid": admin.uuid }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.get("/me").header(auth.clone()).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response
+        .into_string()
+        .await
+        .unwrap()
+        .contains("test-group"));
+
+    let response = client
+        .delete(format!("/groups/{}/members/{}", group.uuid, admin.uuid))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .delete(format!("/groups/{}", group.uuid))
+        .header(auth)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+}
@@ -0,0 +1,109 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::Duration;
+
+use rocket::http::Status;
+use serde_json::Value;
+
+use crate::metrics::{Inventory, Metrics};
+use crate::test::utils::{get_auth_header, get_client};
+
+#[async_test]
+async fn should_be_live_and_ready() {
+    let client = get_client().await;
+
+    let response = client.get("/healthz").dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.get("/readyz").dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let ready = response.into_json::<Value>().await.unwrap();
+    assert_eq!(ready["status"], "ready");
+    assert_eq!(ready["migrations"], "ok");
+}
+
+#[async_test]
+async fn should_serve_metrics() {
+    let client = get_client().await;
+
+    let response = client
+        .get("/dashboard")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.get("/metrics").dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let metrics = response.into_string().await.unwrap();
+    assert!(
+        metrics
+            .lines()
+            .any(|l| l
+                .starts_with("iron_guard_http_requests_total{method=\"GET\",route=\"/dashboard"))
+    );
+    assert!(metrics.contains("iron_guard_db_query_duration_seconds_count"));
+    assert!(metrics.contains("\niron_guard_items "));
+    assert!(metrics.contains("\niron_guard_items_low_stock "));
+}
+
+#[test]
+fn should_render_histograms() {
+    let metrics = Metrics::new(10, 1);
+
+    metrics.record_request("GET", "/items/<uuid>", 200, Duration::from_millis(3));
+    metrics.record_request("GET", "/items/<uuid>", 404, Duration::from_millis(30));
+    metrics.record_query(Duration::from_millis(2), true);
+
+    let out = metrics.render(&Inventory {
+        items: 3,
+        quantity: "12.5".to_string(),
+        low_stock: 1,
+    });
+
+    let route = "method=\"GET\",route=\"/items/<uuid>\"";
+    for line in [
+        format!(
+            "iron_guard_http_requests_total{{{},status=\"200\"}} 1",
+            route
+        ),
+        format!(
+            "iron_guard_http_requests_total{{{},status=\"404\"}} 1",
+            route
+        ),
+        format!(
+            "iron_guard_http_request_duration_seconds_bucket{{{},le=\"0.005\"}} 1",
+            route
+        ),
+        format!(
+            "iron_guard_http_request_duration_seconds_bucket{{{},le=\"0.05\"}} 2",
+            route
+        ),
+        format!(
+            "iron_guard_http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} 2",
+            route
+        ),
+        format!(
+            "iron_guard_http_request_duration_seconds_count{{{}}} 2",
+            route
+        ),
+        "iron_guard_db_query_duration_seconds_count 1".to_string(),
+        "iron_guard_db_query_failures_total 1".to_string(),
+        "iron_guard_db_pool_max_connections 10".to_string(),
+        "iron_guard_items_quantity 12.5".to_string(),
+    ] {
+        assert!(
+            out.lines().any(|l| l == line),
+            "missing {} in\n{}",
+            line,
+            out
+        );
+    }
+}
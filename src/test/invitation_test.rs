@@ -0,0 +1,106 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::controllers::{auth::ResponseSignIn, passwords::hash_token};
+use crate::entities::{invitation, prelude::*, user};
+use crate::test::utils::{get_auth_header, get_client, get_db};
+use rocket::http::{Header, Status};
+use sea_orm::*;
+use serde_json::json;
+
+#[async_test]
+async fn should_not_invite_as_user() {
+    let client = get_client().await;
+
+    let response = client
+        .post("/invitations")
+        .header(get_auth_header(false).await)
+        .body(json!({ "email": "invitee@example.com", "role": "user" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[async_test]
+async fn should_accept_invitation() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    let response = client
+        .post("/invitations")
+        .header(auth.clone())
+        .body(json!({ "email": "invitee@example.com", "role": "user" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    let response = client.get("/invitations").header(auth).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response
+        .into_string()
+        .await
+        .unwrap()
+        .contains("invitee@example.com"));
+
+    // The mailed token is not readable here, so swap in a known one.
+    let db = get_db().await;
+    let invitation = Invitation::find()
+        .filter(invitation::Column::Email.eq("invitee@example.com"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut active: invitation::ActiveModel = invitation.into();
+    active.token_hash = Set(hash_token("known-invite"));
+    let invitation = active.update(&db).await.unwrap();
+
+    let body = json!({
+        "token": "known-invite",
+        "firstname": "Invited",
+        "lastname": "User",
+        "password": "invite1234"
+    })
+    .to_string();
+
+    let response = client
+        .post("/auth/accept-invite")
+        .body(body.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let r: ResponseSignIn = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+
+    let response = client
+        .get("/me")
+        .header(Header::new("token", r.token))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .post("/auth/accept-invite")
+        .body(body)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    invitation.delete(&db).await.unwrap();
+    User::delete_many()
+        .filter(user::Column::Email.eq("invitee@example.com"))
+        .exec(&db)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,133 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::collections::HashSet;
+
+use crate::auth_provider::{map_role, user_filter, AuthProvider, Ldap, Verified};
+use crate::controllers::auth::Role;
+use crate::test::utils::get_config;
+use ldap3::LdapConnAsync;
+use sea_orm::DatabaseConnection;
+
+const ADMINS: &str = "cn=iron-guard-admins,dc=example,dc=org";
+const STAFF: &str = "cn=iron-guard-staff,dc=example,dc=org";
+const PERSON: &str = "uid=ldaptest,dc=example,dc=org";
+
+#[test]
+fn should_escape_user_filter() {
+    assert_eq!(
+        user_filter("(mail={email})", "user@example.com"),
+        "(mail=user@example.com)"
+    );
+    assert_eq!(
+        user_filter("(mail={email})", "*)(uid=*"),
+        "(mail=\\2a\\29\\28uid=\\2a)"
+    );
+}
+
+#[test]
+fn should_map_groups_to_roles() {
+    let admins = vec![ADMINS.to_string()];
+    let staff = vec![STAFF.to_string()];
+
+    assert_eq!(
+        map_role(&[ADMINS.to_uppercase()], &admins, &staff),
+        Some(Role::Admin)
+    );
+    assert_eq!(map_role(&staff, &admins, &staff), Some(Role::User));
+    assert_eq!(map_role(&[], &admins, &staff), None);
+    assert_eq!(map_role(&[], &admins, &[]), Some(Role::User));
+}
+
+/// Runs against an OpenLDAP server with the `osixia/openldap` defaults when
+/// `IRON_GUARD_TEST_LDAP_URL` is set, e.g. `ldap://localhost:389`.
+#[async_test]
+async fn should_bind_against_directory() {
+    let url = match std::env::var("IRON_GUARD_TEST_LDAP_URL") {
+        Ok(url) => url,
+        Err(_) => return,
+    };
+
+    let (conn, mut ldap) = LdapConnAsync::new(&url).await.unwrap();
+    ldap3::drive!(conn);
+    ldap.simple_bind("cn=admin,dc=example,dc=org", "admin")
+        .await
+        .unwrap()
+        .success()
+        .unwrap();
+
+    let set = |values: &[&'static str]| values.iter().copied().collect::<HashSet<_>>();
+
+    let _ = ldap.delete(ADMINS).await;
+    let _ = ldap.delete(PERSON).await;
+    ldap.add(
+        PERSON,
+        vec![
+            ("objectClass", set(&["inetOrgPerson"])),
+            ("uid", set(&["ldaptest"])),
+            ("cn", set(&["Lab Tech"])),
+            ("givenName", set(&["Lab"])),
+            ("sn", set(&["Tech"])),
+            ("mail", set(&["ldaptest@example.com"])),
+            ("userPassword", set(&["ldap1234"])),
+        ],
+    )
+    .await
+    .unwrap()
+    .success()
+    .unwrap();
+    ldap.add(
+        ADMINS,
+        vec![
+            ("objectClass", set(&["groupOfNames"])),
+            ("cn", set(&["iron-guard-admins"])),
+            ("member", set(&[PERSON])),
+        ],
+    )
+    .await
+    .unwrap()
+    .success()
+    .unwrap();
+
+    let provider = Ldap::new(&get_config(&[
+        ("IRON_GUARD_LDAP_URL", &url),
+        ("IRON_GUARD_LDAP_BIND_DN", "cn=admin,dc=example,dc=org"),
+        ("IRON_GUARD_LDAP_BIND_PASSWORD", "admin"),
+        ("IRON_GUARD_LDAP_BASE_DN", "dc=example,dc=org"),
+        ("IRON_GUARD_LDAP_GROUP_BASE_DN", "dc=example,dc=org"),
+        ("IRON_GUARD_LDAP_ADMIN_GROUPS", ADMINS),
+    ]));
+    let db = DatabaseConnection::Disconnected;
+
+    match provider
+        .authenticate(&db, "ldaptest@example.com", "ldap1234")
+        .await
+        .unwrap()
+    {
+        Some(Verified::Directory(user)) => {
+            assert_eq!(user.firstname, "Lab");
+            assert_eq!(user.lastname, "Tech");
+            assert_eq!(user.role, Role::Admin);
+        }
+        _ => panic!("directory user was not verified"),
+    }
+
+    assert!(provider
+        .authenticate(&db, "ldaptest@example.com", "wrong")
+        .await
+        .unwrap()
+        .is_none());
+    assert!(provider
+        .authenticate(&db, "ldaptest@example.com", "")
+        .await
+        .unwrap()
+        .is_none());
+
+    ldap.delete(ADMINS).await.unwrap();
+    ldap.delete(PERSON).await.unwrap();
+    ldap.unbind().await.unwrap();
+}
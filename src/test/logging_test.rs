@@ -0,0 +1,91 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use rocket::http::Header;
+use rocket::local::blocking::Client;
+
+use crate::logging::{LogFormat, RequestLog, REQUEST_ID_HEADER};
+use crate::telemetry::parse_traceparent;
+
+#[get("/")]
+fn index() -> &'static str {
+    "Iron Guard"
+}
+
+fn client() -> Client {
+    Client::tracked(
+        rocket::build()
+            .attach(RequestLog::new(LogFormat::Off, None))
+            .mount("/", routes![index]),
+    )
+    .unwrap()
+}
+
+#[test]
+fn should_echo_request_id() {
+    let client = client();
+
+    let response = client
+        .get("/")
+        .header(Header::new(REQUEST_ID_HEADER, "abc-123"))
+        .dispatch();
+
+    assert_eq!(
+        response.headers().get_one(REQUEST_ID_HEADER),
+        Some("abc-123")
+    );
+}
+
+#[test]
+fn should_assign_request_id() {
+    let client = client();
+
+    let response = client.get("/").dispatch();
+    let first = response
+        .headers()
+        .get_one(REQUEST_ID_HEADER)
+        .unwrap()
+        .to_string();
+    assert_eq!(first.len(), 36);
+
+    // Ids that are unsafe to log are replaced.
+    let response = client
+        .get("/")
+        .header(Header::new(REQUEST_ID_HEADER, "bad id\"}"))
+        .dispatch();
+    let second = response.headers().get_one(REQUEST_ID_HEADER).unwrap();
+    assert_eq!(second.len(), 36);
+    assert_ne!(second, first);
+}
+
+#[test]
+fn should_parse_traceparent() {
+    assert_eq!(
+        parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+        Some((
+            "4bf92f3577b34da6a3ce929d0e0e4736".to_string(),
+            "00f067aa0ba902b7".to_string()
+        ))
+    );
+
+    for header in [
+        "",
+        "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+        "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+    ] {
+        assert!(parse_traceparent(header).is_none(), "{}", header);
+    }
+}
+
+#[test]
+fn should_parse_log_format() {
+    assert!(LogFormat::from_str("json") == Some(LogFormat::Json));
+    assert!(LogFormat::from_str("off") == Some(LogFormat::Off));
+    assert!(LogFormat::from_str("JSON").is_none());
+}
@@ -0,0 +1,127 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::entities::{lot, prelude::*};
+use crate::test::utils::{
+    delete_test_item, get_auth_header, get_client, get_db, get_test_item_uuid,
+    get_test_organization_id,
+};
+use rocket::http::Status;
+use sea_orm::{prelude::Decimal, *};
+use serde_json::json;
+
+#[async_test]
+async fn should_list_expiring_lots() {
+    let client = get_client().await;
+
+    let response = client
+        .get("/lots/expiring?days=7")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.into_string().await.unwrap().contains("results"));
+
+    // Windows past the last representable date still list the lots.
+    let response = client
+        .get(format!("/lots/expiring?days={}", u32::MAX))
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+}
+
+#[async_test]
+async fn should_reject_invalid_expiry_date() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    client
+        .post("/items")
+        .header(auth.clone())
+        .body(json!({ "name": "test", "quantity": 0 }).to_string())
+        .dispatch()
+        .await;
+
+    let uuid = get_test_item_uuid().await;
+
+    let response = client
+        .post(format!("/items/{}/lots", uuid))
+        .header(auth.clone())
+        .body(json!({ "batch_number": "B1", "quantity": 1, "expiry_date": "31/12/2030" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    delete_test_item().await;
+}
+
+#[async_test]
+async fn should_check_out_first_expiring_lot() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    client
+        .post("/items")
+        .header(auth.clone())
+        .body(json!({ "name": "test", "quantity": 0 }).to_string())
+        .dispatch()
+        .await;
+
+    let uuid = get_test_item_uuid().await;
+
+    for (batch, expiry) in [("LATE", "2031-01-01"), ("EARLY", "2030-01-01")] {
+        let response = client
+            .post(format!("/items/{}/lots", uuid))
+            .header(auth.clone())
+            .body(json!({ "batch_number": batch, "quantity": 5, "expiry_date": expiry }).to_string())
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Created);
+    }
+
+    let response = client
+        .post(format!("/items/{}/check-out", uuid))
+        .header(auth.clone())
+        .body(json!({ "quantity": 7 }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let db = get_db().await;
+    let item = Item::from_uuid(&db, get_test_organization_id().await, &uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.quantity, Decimal::from(3));
+
+    let early = item
+        .find_related(Lot)
+        .filter(lot::Column::BatchNumber.eq("EARLY"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(early.quantity, Decimal::from(0));
+
+    let response = client
+        .post(format!("/items/{}/check-out", uuid))
+        .header(auth.clone())
+        .body(json!({ "quantity": 4 }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    delete_test_item().await;
+}
@@ -0,0 +1,102 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use rocket::http::Status;
+use sea_orm::*;
+use sea_orm_migration::prelude::*;
+use serde_json::Value;
+
+use crate::migrator::Migrator;
+use crate::test::utils::{get_auth_header, get_client, get_db};
+use crate::{db, Config};
+
+#[async_test]
+async fn should_list_migrations() {
+    let client = get_client().await;
+    let response = client
+        .get("/migrations")
+        .header(get_auth_header(true).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let migrations = response.into_json::<Value>().await.unwrap();
+    let results = migrations["results"].as_array().unwrap();
+
+    assert_eq!(results.len(), Migrator::migrations().len());
+    assert_eq!(results[0]["name"], "m20230227_000001_create_user_table");
+    assert!(results.iter().all(|m| m["applied"] == true));
+}
+
+#[async_test]
+async fn should_not_list_migrations_for_user() {
+    let client = get_client().await;
+    let response = client
+        .get("/migrations")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+async fn tables(db: &DatabaseConnection) -> Vec<String> {
+    db.query_all(Statement::from_string(
+        db.get_database_backend(),
+        "SELECT table_name AS name FROM information_schema.tables \
+         WHERE table_schema = DATABASE() ORDER BY table_name"
+            .to_owned(),
+    ))
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| row.try_get::<String>("", "name").unwrap())
+    .collect()
+}
+
+/// Runs every migration down and up again in a database of its own, so the
+/// other tests keep their schema.
+#[async_test]
+async fn should_reverse_every_migration() {
+    let mut config = Config::load("debug").unwrap();
+    let name = format!("{}_migrations", config.db_database);
+    config.database_url = String::new();
+
+    let server = get_db().await;
+    let backend = server.get_database_backend();
+    server
+        .execute(Statement::from_string(
+            backend,
+            format!("CREATE DATABASE IF NOT EXISTS `{}`", name),
+        ))
+        .await
+        .unwrap();
+
+    config.db_database = name.to_owned();
+    let db = db::connect(&config).await.unwrap();
+
+    Migrator::up(&db, None).await.unwrap();
+    Migrator::check(&db).await.unwrap();
+    let schema = tables(&db).await;
+
+    Migrator::down(&db, None).await.unwrap();
+    assert!(Migrator::check(&db).await.is_err());
+    assert_eq!(tables(&db).await, ["seaql_migrations"]);
+
+    Migrator::up(&db, None).await.unwrap();
+    Migrator::check(&db).await.unwrap();
+    assert_eq!(tables(&db).await, schema);
+
+    server
+        .execute(Statement::from_string(
+            backend,
+            format!("DROP DATABASE `{}`", name),
+        ))
+        .await
+        .unwrap();
+}
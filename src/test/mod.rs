@@ -10,10 +10,36 @@ use super::rocket;
 use crate::test::utils::get_client;
 use rocket::http::Status;
 
+pub mod api_key_test;
+pub mod asset_test;
+pub mod attachment_test;
 pub mod auth_test;
 pub mod category_test;
+pub mod cli_test;
+pub mod config_test;
+pub mod cors_test;
+pub mod etag_test;
+pub mod group_test;
+pub mod health_test;
 pub mod inventory_test;
+pub mod invitation_test;
 pub mod item_test;
+pub mod jwt_test;
+pub mod ldap_test;
+pub mod logging_test;
+pub mod lot_test;
+pub mod migration_test;
+pub mod oidc_test;
+pub mod organization_test;
+pub mod password_test;
+pub mod purchase_order_test;
+pub mod report_test;
+pub mod session_test;
+pub mod stocktake_test;
+pub mod supplier_test;
+pub mod throttle_test;
+pub mod two_factor_test;
+pub mod unit_test;
 pub mod user_test;
 
 pub mod utils;
@@ -0,0 +1,163 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::controllers::auth::ResponseSignIn;
+use crate::entities::{organization, prelude::*};
+use crate::test::utils::{
+    delete_test_item, get_auth_header, get_client, get_db, get_test_item_uuid,
+};
+use rocket::http::{Header, Status};
+use sea_orm::*;
+use serde_json::json;
+
+#[async_test]
+async fn should_list_organizations() {
+    let client = get_client().await;
+
+    let response = client
+        .get("/organizations")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response
+        .into_string()
+        .await
+        .unwrap()
+        .contains("\"active\":true"));
+}
+
+#[async_test]
+async fn should_not_switch_to_foreign_organization() {
+    let client = get_client().await;
+
+    let response = client
+        .post("/auth/switch")
+        .header(get_auth_header(false).await)
+        .body(json!({ "organization_uuid": "not-a-member" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[async_test]
+async fn should_scope_items_to_active_organization() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    client
+        .post("/items")
+        .header(auth.clone())
+        .body(json!({ "name": "test", "quantity": 1 }).to_string())
+        .dispatch()
+        .await;
+
+    let item_uuid = get_test_item_uuid().await;
+
+    let response = client
+        .post("/organizations")
+        .header(auth.clone())
+        .body(json!({ "name": "test" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    let db = get_db().await;
+    let org = Organization::find()
+        .filter(organization::Column::Name.eq("test"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let response = client
+        .post("/auth/switch")
+        .header(auth.clone())
+        .body(json!({ "organization_uuid": org.uuid }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let r: ResponseSignIn = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let switched = Header::new("token", r.token);
+
+    let response = client
+        .get(format!("/items/{}", item_uuid))
+        .header(switched.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::NotFound);
+
+    let response = client.get("/users").header(switched).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response
+        .into_string()
+        .await
+        .unwrap()
+        .contains("\"total\":1"));
+
+    org.delete(&db).await.unwrap();
+    delete_test_item().await;
+}
+
+#[async_test]
+async fn should_require_two_factor_to_update_organization() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    let response = client
+        .post("/organizations")
+        .header(auth.clone())
+        .body(json!({ "name": "two-factor", "require_admin_two_factor": true }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    let db = get_db().await;
+    let org = Organization::find()
+        .filter(organization::Column::Name.eq("two-factor"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let response = client
+        .post("/auth/switch")
+        .header(auth.clone())
+        .body(json!({ "organization_uuid": org.uuid }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let r: ResponseSignIn = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    assert_eq!(r.status, "2fa_setup_required");
+    let switched = Header::new("token", r.token);
+
+    // The admin has no TOTP set up, so neither their token for the
+    // organization nor one for another organization may change it.
+    for token in [switched, auth] {
+        let response = client
+            .put(format!("/organizations/{}", org.uuid))
+            .header(token)
+            .body(json!({ "name": "two-factor", "require_admin_two_factor": false }).to_string())
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    org.delete(&db).await.unwrap();
+}
@@ -0,0 +1,147 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::{Duration, SystemTime};
+
+use super::super::rocket;
+use crate::controllers::passwords::hash_token;
+use crate::entities::{password_reset, prelude::*, user};
+use crate::test::utils::{get_auth_header, get_client, get_db};
+use bcrypt::{hash, DEFAULT_COST};
+use rocket::http::Status;
+use sea_orm::{prelude::DateTimeUtc, *};
+use serde_json::json;
+use uuid::Uuid;
+
+#[async_test]
+async fn should_accept_forgot_password_for_unknown_email() {
+    let client = get_client().await;
+
+    let response = client
+        .post("/auth/forgot-password")
+        .body(json!({ "email": "nobody@example.com" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+}
+
+#[async_test]
+async fn should_require_current_password_to_change_password() {
+    let client = get_client().await;
+
+    let response = client
+        .put("/me")
+        .header(get_auth_header(false).await)
+        .body(
+            json!({
+                "firstname": "Test",
+                "lastname": "User",
+                "email": "user@example.com",
+                "password": "changed1234",
+                "current_password": "wrong"
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[async_test]
+async fn should_require_current_password_to_change_email() {
+    let client = get_client().await;
+
+    let response = client
+        .put("/me")
+        .header(get_auth_header(false).await)
+        .body(
+            json!({
+                "firstname": "Test",
+                "lastname": "User",
+                "email": "attacker@example.net",
+                "password": ""
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[async_test]
+async fn should_reset_password_once() {
+    let client = get_client().await;
+    let db = get_db().await;
+
+    let user_id = User::insert(user::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        role: Set("user".to_string()),
+        firstname: Set("Reset".to_string()),
+        lastname: Set("User".to_string()),
+        email: Set("reset@example.com".to_string()),
+        password: Set(hash("old1234", DEFAULT_COST).unwrap()),
+        ..Default::default()
+    })
+    .exec(&db)
+    .await
+    .unwrap()
+    .last_insert_id;
+
+    let response = client
+        .post("/auth/forgot-password")
+        .body(json!({ "email": "reset@example.com" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    // The mailed token is not readable here, so issue a known one.
+    PasswordReset::insert(password_reset::ActiveModel {
+        user_id: Set(user_id),
+        token_hash: Set(hash_token("known-token")),
+        expires_at: Set(DateTimeUtc::from(
+            SystemTime::now() + Duration::from_secs(60),
+        )),
+        created_at: Set(DateTimeUtc::from(SystemTime::now())),
+        ..Default::default()
+    })
+    .exec(&db)
+    .await
+    .unwrap();
+
+    let body = json!({ "token": "known-token", "password": "new1234" }).to_string();
+
+    let response = client
+        .post("/auth/reset-password")
+        .body(body.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .post("/auth/sign-in")
+        .body(json!({ "email": "reset@example.com", "password": "new1234" }).to_string())
+        .dispatch()
+        .await;
+
+    // Signing in needs an organization membership, which this user lacks.
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let response = client
+        .post("/auth/reset-password")
+        .body(body)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    User::delete_by_id(user_id).exec(&db).await.unwrap();
+}
@@ -0,0 +1,157 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::test::utils::{
+    delete_test_item, delete_test_supplier, get_auth_header, get_client, get_db,
+    get_test_item_uuid, get_test_organization_id, get_test_supplier_uuid,
+};
+use crate::entities::{prelude::*, purchase_order, purchase_order_line};
+use rocket::http::Status;
+use sea_orm::{prelude::Decimal, *};
+use serde_json::{json, Value};
+
+#[async_test]
+async fn should_not_add_purchase_order_for_unknown_supplier() {
+    let client = get_client().await;
+
+    let response = client
+        .post("/purchase-orders")
+        .header(get_auth_header(true).await)
+        .body(json!({ "supplier_uuid": "does-not-exist", "lines": [] }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+}
+
+#[async_test]
+async fn should_receive_purchase_order() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    client
+        .post("/suppliers")
+        .header(auth.clone())
+        .body(json!({ "name": "test" }).to_string())
+        .dispatch()
+        .await;
+    client
+        .post("/items")
+        .header(auth.clone())
+        .body(json!({ "name": "test", "quantity": 2 }).to_string())
+        .dispatch()
+        .await;
+
+    let supplier_uuid = get_test_supplier_uuid().await;
+    let item_uuid = get_test_item_uuid().await;
+
+    let response = client
+        .post("/purchase-orders")
+        .header(auth.clone())
+        .body(
+            json!({
+                "supplier_uuid": supplier_uuid,
+                "lines": [{ "item_uuid": item_uuid, "quantity": 5 }]
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    let db = get_db().await;
+    let supplier = Supplier::from_uuid(&db, get_test_organization_id().await, &supplier_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    let order = supplier
+        .find_related(PurchaseOrder)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let response = client
+        .post(format!("/purchase-orders/{}/receive", order.uuid))
+        .header(auth.clone())
+        .body(json!({ "lines": [] }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Conflict);
+
+    let response = client
+        .post(format!("/purchase-orders/{}/send", order.uuid))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .get(format!("/purchase-orders/{}", order.uuid))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    let r: Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let line_uuid = r["lines"][0]["uuid"].as_str().unwrap().to_string();
+
+    let response = client
+        .post(format!("/purchase-orders/{}/receive", order.uuid))
+        .header(auth.clone())
+        .body(json!({ "lines": [{ "line_uuid": line_uuid, "quantity": 6 }] }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    let response = client
+        .post(format!("/purchase-orders/{}/receive", order.uuid))
+        .header(auth.clone())
+        .body(json!({ "lines": [{ "line_uuid": line_uuid, "quantity": 3 }] }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let order = PurchaseOrder::from_uuid(&db, get_test_organization_id().await, &order.uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(order.status, "partially_received");
+
+    let item = Item::from_uuid(&db, get_test_organization_id().await, &item_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.quantity, Decimal::from(5));
+
+    let response = client
+        .delete(format!("/items/{}", item_uuid))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Conflict);
+
+    PurchaseOrderLine::delete_many()
+        .filter(purchase_order_line::Column::PurchaseOrderId.eq(order.id))
+        .exec(&db)
+        .await
+        .unwrap();
+    PurchaseOrder::delete_many()
+        .filter(purchase_order::Column::Id.eq(order.id))
+        .exec(&db)
+        .await
+        .unwrap();
+
+    delete_test_supplier().await;
+    delete_test_item().await;
+}
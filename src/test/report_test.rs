@@ -0,0 +1,216 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::SystemTime;
+
+use super::super::rocket;
+use crate::{
+    controllers::reports::{item_value, CostMethod},
+    entities::{item, location, prelude::*, stock_movement},
+    test::utils::{get_auth_header, get_client, get_db},
+};
+use rocket::http::Status;
+use sea_orm::{
+    prelude::{DateTimeUtc, Decimal},
+    *,
+};
+use serde_json::{json, Value};
+
+fn test_item(unit_cost: i64) -> item::Model {
+    item::Model {
+        id: 1,
+        uuid: "item".to_string(),
+        organization_id: 1,
+        category_id: None,
+        user_id: 1,
+        group_id: None,
+        name: "test".to_string(),
+        description: None,
+        quantity: Decimal::ZERO,
+        unit: "pcs".to_string(),
+        unit_cost: Some(Decimal::from(unit_cost)),
+        currency: None,
+        mode: "bulk".to_string(),
+        location_id: None,
+        version: 1,
+        meta: None,
+        created_at: DateTimeUtc::from(SystemTime::now()),
+        updated_at: DateTimeUtc::from(SystemTime::now()),
+        deleted_at: None,
+    }
+}
+
+fn receipt(quantity: i64, unit_cost: i64) -> stock_movement::Model {
+    stock_movement::Model {
+        id: 1,
+        uuid: "movement".to_string(),
+        item_id: 1,
+        user_id: 1,
+        quantity: Decimal::from(quantity),
+        unit_cost: Some(Decimal::from(unit_cost)),
+        reason: "receipt".to_string(),
+        meta: None,
+        created_at: DateTimeUtc::from(SystemTime::now()),
+        updated_at: DateTimeUtc::from(SystemTime::now()),
+        deleted_at: None,
+    }
+}
+
+#[test]
+fn should_value_inventory_by_method() {
+    let item = test_item(7);
+    let movements = [receipt(10, 2), receipt(10, 4)];
+    let movements = movements.iter().collect::<Vec<_>>();
+
+    assert_eq!(
+        item_value(&item, Decimal::from(5), &movements, CostMethod::Unit),
+        Decimal::from(35)
+    );
+    assert_eq!(
+        item_value(&item, Decimal::from(5), &movements, CostMethod::Average),
+        Decimal::from(15)
+    );
+    assert_eq!(
+        item_value(&item, Decimal::from(15), &movements, CostMethod::Fifo),
+        Decimal::from(50)
+    );
+    assert_eq!(
+        item_value(&item, Decimal::from(25), &movements, CostMethod::Fifo),
+        Decimal::from(95)
+    );
+    assert_eq!(
+        item_value(&item, Decimal::new(25, 1), &movements, CostMethod::Unit),
+        Decimal::new(175, 1)
+    );
+}
+
+#[async_test]
+async fn should_return_valuation() {
+    let client = get_client().await;
+
+    let response = client
+        .get("/reports/valuation?method=fifo")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let r = response.into_string().await.unwrap();
+    assert!(r.contains("fifo"));
+    assert!(r.contains("categories"));
+    assert!(r.contains("locations"));
+}
+
+#[async_test]
+async fn should_return_valuation_history() {
+    let client = get_client().await;
+
+    let response = client
+        .get("/reports/valuation/history?points=3")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let r = response.into_string().await.unwrap();
+    assert!(r.contains("results"));
+}
+
+/// A decimal from a JSON number or string.
+fn amount(value: &Value) -> Decimal {
+    match value {
+        Value::String(s) => s.parse().unwrap(),
+        other => other.to_string().parse().unwrap(),
+    }
+}
+
+#[async_test]
+async fn should_value_inventory_per_location() {
+    let client = get_client().await;
+    let db = get_db().await;
+
+    for (name, quantity, unit_cost) in [("report-a", 2, 3), ("report-b", 5, 2)] {
+        let response = client
+            .post("/locations")
+            .header(get_auth_header(false).await)
+            .body(json!({ "name": name }).to_string())
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+
+        let location = Location::find()
+            .filter(location::Column::Name.eq(name))
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let response = client
+            .post("/items")
+            .header(get_auth_header(false).await)
+            .body(
+                json!({
+                    "name": "report-location",
+                    "quantity": quantity,
+                    "unit_cost": unit_cost,
+                    "currency": "EUR",
+                    "location_uuid": location.uuid
+                })
+                .to_string(),
+            )
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+    }
+
+    let response = client
+        .get("/reports/valuation?method=unit")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let valuation = response.into_json::<Value>().await.unwrap();
+    let locations = valuation["locations"].as_array().unwrap();
+    let bucket = |name: &str| {
+        locations
+            .iter()
+            .find(|l| l["name"] == name)
+            .unwrap_or_else(|| panic!("no {} in {:?}", name, locations))
+            .clone()
+    };
+
+    let a = bucket("report-a");
+    assert_eq!(amount(&a["quantity"]), Decimal::from(2));
+    assert_eq!(amount(&a["value"]["EUR"]), Decimal::from(6));
+
+    let b = bucket("report-b");
+    assert_eq!(amount(&b["quantity"]), Decimal::from(5));
+    assert_eq!(amount(&b["value"]["EUR"]), Decimal::from(10));
+
+    for item in Item::find()
+        .filter(item::Column::Name.eq("report-location"))
+        .all(&db)
+        .await
+        .unwrap()
+    {
+        StockMovement::delete_many()
+            .filter(stock_movement::Column::ItemId.eq(item.id))
+            .exec(&db)
+            .await
+            .unwrap();
+        item.delete(&db).await.unwrap();
+    }
+
+    Location::delete_many()
+        .filter(location::Column::Name.is_in(["report-a", "report-b"]))
+        .exec(&db)
+        .await
+        .unwrap();
+}
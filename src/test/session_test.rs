@@ -0,0 +1,207 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::controllers::{api_keys::ResponseNewApiKey, auth::ResponseSignIn};
+use crate::entities::{prelude::*, user};
+use crate::test::utils::{add_test_membership, get_auth_header, get_client, get_db};
+use bcrypt::{hash, DEFAULT_COST};
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+use sea_orm::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+async fn sign_in(client: &Client, email: &str, password: &str) -> Header<'static> {
+    let response = client
+        .post("/auth/sign-in")
+        .header(Header::new("User-Agent", "Session Test"))
+        .body(json!({ "email": email, "password": password }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let r = response.into_json::<ResponseSignIn>().await.unwrap();
+
+    Header::new("token", r.token)
+}
+
+#[async_test]
+async fn should_list_and_revoke_session() {
+    let client = get_client().await;
+    let auth = sign_in(&client, "user@example.com", "test1234").await;
+
+    let response = client
+        .get("/me/sessions")
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let sessions = response.into_json::<Value>().await.unwrap();
+    let current = sessions["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["current"] == json!(true))
+        .unwrap()
+        .to_owned();
+
+    assert_eq!(current["user_agent"], "Session Test");
+
+    let response = client
+        .delete(format!(
+            "/me/sessions/{}",
+            current["uuid"].as_str().unwrap()
+        ))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.get("/me").header(auth).dispatch().await;
+
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+#[async_test]
+async fn should_not_revoke_others_sessions() {
+    let client = get_client().await;
+    let auth = get_auth_header(false).await;
+
+    let response = client
+        .delete(format!("/me/sessions/{}", Uuid::new_v4()))
+        .header(auth)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[async_test]
+async fn should_terminate_user_sessions() {
+    let client = get_client().await;
+    let db = get_db().await;
+
+    // A user of its own, so signing it out does not affect other tests.
+    let member = user::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        role: Set("user".to_string()),
+        firstname: Set("Session".to_string()),
+        lastname: Set("Test".to_string()),
+        email: Set("sessions@example.com".to_string()),
+        password: Set(hash("sessions1234", DEFAULT_COST).unwrap()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    add_test_membership(&db, member.id, "user").await;
+
+    let laptop = sign_in(&client, "sessions@example.com", "sessions1234").await;
+    let phone = sign_in(&client, "sessions@example.com", "sessions1234").await;
+
+    let response = client
+        .delete(format!("/users/{}/sessions", member.uuid))
+        .header(laptop.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let response = client
+        .delete(format!("/users/{}/sessions", member.uuid))
+        .header(get_auth_header(true).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    for auth in [laptop, phone] {
+        let response = client.get("/me").header(auth).dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    User::delete_many()
+        .filter(user::Column::Email.eq("sessions@example.com"))
+        .exec(&db)
+        .await
+        .unwrap();
+}
+
+#[async_test]
+async fn should_sign_out_elsewhere_on_password_change() {
+    let client = get_client().await;
+    let db = get_db().await;
+
+    let member = user::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        role: Set("user".to_string()),
+        firstname: Set("Password".to_string()),
+        lastname: Set("Change".to_string()),
+        email: Set("password-change@example.com".to_string()),
+        password: Set(hash("before1234", DEFAULT_COST).unwrap()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    add_test_membership(&db, member.id, "user").await;
+
+    let laptop = sign_in(&client, "password-change@example.com", "before1234").await;
+    let phone = sign_in(&client, "password-change@example.com", "before1234").await;
+
+    let response = client
+        .post("/me/api-keys")
+        .header(laptop.clone())
+        .body(json!({ "name": "Script" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let new_key = response.into_json::<ResponseNewApiKey>().await.unwrap();
+    let api_key = Header::new("X-API-Key", new_key.key);
+
+    let response = client
+        .put("/me")
+        .header(laptop.clone())
+        .body(
+            json!({
+                "firstname": "Password",
+                "lastname": "Change",
+                "email": "password-change@example.com",
+                "password": "after1234",
+                "current_password": "before1234"
+            })
+            .to_string(),
+        )
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    // The session that changed the password stays signed in.
+    let response = client.get("/me").header(laptop).dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+
+    for auth in [phone, api_key] {
+        let response = client.get("/me").header(auth).dispatch().await;
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    User::delete_many()
+        .filter(user::Column::Email.eq("password-change@example.com"))
+        .exec(&db)
+        .await
+        .unwrap();
+}
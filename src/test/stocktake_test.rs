@@ -0,0 +1,227 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::entities::{item, location, prelude::*, stock_movement, stocktake};
+use crate::test::utils::{
+    delete_test_item, get_auth_header, get_client, get_db, get_test_item_uuid,
+    get_test_organization_id,
+};
+use rocket::http::Status;
+use sea_orm::{prelude::Decimal, *};
+use serde_json::{json, Value};
+
+#[async_test]
+async fn should_list_stocktakes() {
+    let client = get_client().await;
+
+    let response = client
+        .get("/stocktakes")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.into_string().await.unwrap().contains("results"));
+}
+
+#[async_test]
+async fn should_apply_approved_stocktake() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    client
+        .post("/items")
+        .header(auth.clone())
+        .body(json!({ "name": "test", "quantity": 10 }).to_string())
+        .dispatch()
+        .await;
+
+    let item_uuid = get_test_item_uuid().await;
+
+    let response = client
+        .post("/stocktakes")
+        .header(auth.clone())
+        .body(json!({ "notes": "test" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    let db = get_db().await;
+    let session = Stocktake::find()
+        .filter(stocktake::Column::Notes.eq("test"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let response = client
+        .put(format!("/stocktakes/{}/counts", session.uuid))
+        .header(auth.clone())
+        .body(json!({ "counts": [{ "item_uuid": item_uuid, "quantity": 7 }] }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .post(format!("/stocktakes/{}/approve", session.uuid))
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let response = client
+        .post(format!("/stocktakes/{}/approve", session.uuid))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .get(format!("/stocktakes/{}", session.uuid))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+
+    let r: Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let line = r["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|l| l["item"]["uuid"] == item_uuid.as_str())
+        .unwrap();
+
+    assert_eq!(line["expected"], 10);
+    assert_eq!(line["variance"], -3);
+
+    let item = Item::from_uuid(&db, get_test_organization_id().await, &item_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.quantity, Decimal::from(7));
+
+    session.delete(&db).await.unwrap();
+    delete_test_item().await;
+}
+
+#[async_test]
+async fn should_scope_stocktake_to_location() {
+    let client = get_client().await;
+    let auth = get_auth_header(false).await;
+    let db = get_db().await;
+
+    let response = client
+        .post("/locations")
+        .header(auth.clone())
+        .body(json!({ "name": "stocktake-shelf" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Created);
+
+    let location = Location::find()
+        .filter(location::Column::Name.eq("stocktake-shelf"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    for (name, location_uuid) in [
+        ("stocktake-here", Some(location.uuid.to_owned())),
+        ("stocktake-elsewhere", None),
+    ] {
+        let response = client
+            .post("/items")
+            .header(auth.clone())
+            .body(
+                json!({ "name": name, "quantity": 3, "location_uuid": location_uuid }).to_string(),
+            )
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Created);
+    }
+
+    let response = client
+        .post("/stocktakes")
+        .header(auth.clone())
+        .body(json!({ "location_uuid": "missing" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    let response = client
+        .post("/stocktakes")
+        .header(auth.clone())
+        .body(json!({ "location_uuid": location.uuid, "notes": "stocktake-location" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Created);
+
+    let session = Stocktake::find()
+        .filter(stocktake::Column::Notes.eq("stocktake-location"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let response = client
+        .get(format!("/stocktakes/{}", session.uuid))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let r = response.into_json::<Value>().await.unwrap();
+    assert_eq!(r["location"]["uuid"], location.uuid.as_str());
+
+    let lines = r["lines"].as_array().unwrap();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["item"]["name"], "stocktake-here");
+
+    let items = Item::find()
+        .filter(item::Column::Name.is_in(["stocktake-here", "stocktake-elsewhere"]))
+        .all(&db)
+        .await
+        .unwrap();
+    let elsewhere = items
+        .iter()
+        .find(|i| i.name == "stocktake-elsewhere")
+        .unwrap();
+
+    // Items at other locations cannot be counted in the session.
+    let response = client
+        .put(format!("/stocktakes/{}/counts", session.uuid))
+        .header(auth.clone())
+        .body(json!({ "counts": [{ "item_uuid": elsewhere.uuid, "quantity": 1 }] }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    // The session keeps its location.
+    let response = client
+        .delete(format!("/locations/{}", location.uuid))
+        .header(auth.clone())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Conflict);
+
+    session.delete(&db).await.unwrap();
+
+    for item in items {
+        StockMovement::delete_many()
+            .filter(stock_movement::Column::ItemId.eq(item.id))
+            .exec(&db)
+            .await
+            .unwrap();
+        item.delete(&db).await.unwrap();
+    }
+
+    location.delete(&db).await.unwrap();
+}
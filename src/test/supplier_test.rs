@@ -0,0 +1,58 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::test::utils::{delete_test_supplier, get_auth_header, get_client};
+use rocket::http::Status;
+use serde_json::json;
+
+#[async_test]
+async fn should_list_suppliers() {
+    let client = get_client().await;
+
+    let response = client
+        .get("/suppliers")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let r = response.into_string().await;
+    assert!(r.is_some());
+    assert!(r.unwrap().contains("results"));
+}
+
+#[async_test]
+async fn should_not_add_supplier() {
+    let client = get_client().await;
+
+    let response = client
+        .post("/suppliers")
+        .header(get_auth_header(true).await)
+        .body(json!({}).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+}
+
+#[async_test]
+async fn should_add_supplier() {
+    let client = get_client().await;
+
+    let response = client
+        .post("/suppliers")
+        .header(get_auth_header(true).await)
+        .body(json!({ "name": "test", "email": "orders@example.com" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    delete_test_supplier().await;
+}
@@ -0,0 +1,118 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::Duration;
+
+use super::super::rocket;
+use crate::test::utils::get_client;
+use crate::throttle::{rate_limited, Lockout, RateLimit, Window};
+use rocket::http::{Header, Status};
+use rocket::local::blocking::Client;
+use serde_json::json;
+
+#[test]
+fn should_lock_out_progressively() {
+    let lockout = Lockout::new(3, 10, Duration::from_secs(30), Duration::from_secs(3600));
+    let ip = Some("10.0.0.1".parse().unwrap());
+
+    lockout.fail("user@example.com", ip);
+    lockout.fail("user@example.com", ip);
+    assert_eq!(lockout.check("user@example.com", ip), Ok(()));
+
+    lockout.fail("User@Example.com", ip);
+    assert!(matches!(lockout.check("user@example.com", None), Err(s) if s > 25 && s <= 30));
+
+    lockout.fail("user@example.com", ip);
+    assert!(matches!(lockout.check("user@example.com", None), Err(s) if s > 55 && s <= 60));
+
+    // The IP has not reached its own threshold.
+    assert_eq!(lockout.check("other@example.com", ip), Ok(()));
+
+    lockout.succeed("user@example.com");
+    assert_eq!(lockout.check("user@example.com", ip), Ok(()));
+}
+
+#[test]
+fn should_limit_requests_per_route_group() {
+    assert_eq!(
+        Window::parse("2/60"),
+        Some(Window {
+            requests: 2,
+            seconds: 60
+        })
+    );
+    assert_eq!(Window::parse("off"), None);
+    assert_eq!(Window::parse("0/60"), None);
+
+    let limit = RateLimit::new(Window::parse("2/60"), None, false);
+
+    assert_eq!(limit.hit("/auth/sign-in", "a"), Ok(()));
+    assert_eq!(limit.hit("/auth/sign-in", "a"), Ok(()));
+    assert!(limit.hit("/auth/sign-in", "a").is_err());
+    assert_eq!(limit.hit("/auth/sign-in", "b"), Ok(()));
+    assert_eq!(limit.hit("/items", "a"), Ok(()));
+}
+
+#[get("/")]
+fn index() -> &'static str {
+    "Iron Guard"
+}
+
+/// The status of two requests from the same address claiming to come from
+/// different clients.
+fn spoofed_statuses(trust_proxy: bool) -> Vec<Status> {
+    let client = Client::tracked(
+        rocket::build()
+            .attach(RateLimit::new(None, Window::parse("1/60"), trust_proxy))
+            .mount("/", routes![index, rate_limited]),
+    )
+    .unwrap();
+
+    ["10.0.0.1", "10.0.0.2"]
+        .iter()
+        .map(|ip| {
+            client
+                .get("/")
+                .remote("192.0.2.1:4000".parse().unwrap())
+                .header(Header::new("X-Real-IP", *ip))
+                .dispatch()
+                .status()
+        })
+        .collect()
+}
+
+#[test]
+fn should_not_trust_real_ip_header_by_default() {
+    assert_eq!(
+        spoofed_statuses(false),
+        [Status::Ok, Status::TooManyRequests]
+    );
+
+    // Behind a trusted proxy the header names the client.
+    assert_eq!(spoofed_statuses(true), [Status::Ok, Status::Ok]);
+}
+
+#[async_test]
+async fn should_429_after_repeated_failed_sign_ins() {
+    let client = get_client().await;
+    let body = json!({ "email": "locked@example.com", "password": "wrong" }).to_string();
+
+    for _ in 0..5 {
+        let response = client
+            .post("/auth/sign-in")
+            .body(body.clone())
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    let response = client.post("/auth/sign-in").body(body).dispatch().await;
+
+    assert_eq!(response.status(), Status::TooManyRequests);
+    assert!(response.headers().get_one("Retry-After").is_some());
+}
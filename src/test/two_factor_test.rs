@@ -0,0 +1,116 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::controllers::auth::ResponseSignIn;
+use crate::entities::{prelude::*, user};
+use crate::test::utils::{add_test_membership, get_client, get_db};
+use crate::totp::{code_at, current_step, verify};
+use bcrypt::{hash, DEFAULT_COST};
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+use sea_orm::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+#[test]
+fn should_match_rfc_6238_codes() {
+    assert_eq!(code_at(SECRET, 1).as_deref(), Some("287082"));
+    assert_eq!(code_at(SECRET, 37037036).as_deref(), Some("081804"));
+    assert_eq!(code_at(SECRET, 41152263).as_deref(), Some("005924"));
+    assert_eq!(code_at("not base32!", 1), None);
+
+    assert_eq!(verify(SECRET, "287082", 2, None), Some(1));
+    assert_eq!(verify(SECRET, "287082", 1, Some(1)), None);
+    assert_eq!(verify(SECRET, "287082", 3, None), None);
+}
+
+async fn sign_in(client: &Client) -> ResponseSignIn {
+    let response = client
+        .post("/auth/sign-in")
+        .body(json!({ "email": "2fa@example.com", "password": "test1234" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    serde_json::from_str(&response.into_string().await.unwrap()).unwrap()
+}
+
+#[async_test]
+async fn should_challenge_sign_in_with_two_factor() {
+    let client = get_client().await;
+    let db = get_db().await;
+
+    let user_id = User::insert(user::ActiveModel {
+        uuid: Set(Uuid::new_v4().to_string()),
+        role: Set("user".to_string()),
+        firstname: Set("Two".to_string()),
+        lastname: Set("Factor".to_string()),
+        email: Set("2fa@example.com".to_string()),
+        password: Set(hash("test1234", DEFAULT_COST).unwrap()),
+        ..Default::default()
+    })
+    .exec(&db)
+    .await
+    .unwrap()
+    .last_insert_id;
+
+    add_test_membership(&db, user_id, "user").await;
+
+    let r = sign_in(&client).await;
+    assert_eq!(r.status, "success");
+    let auth = Header::new("token", r.token);
+
+    let response = client.post("/me/2fa").header(auth.clone()).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let enrollment: Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let secret = enrollment["secret"].as_str().unwrap().to_string();
+    assert!(enrollment["uri"]
+        .as_str()
+        .unwrap()
+        .starts_with("otpauth://totp/"));
+
+    let response = client
+        .post("/me/2fa/verify")
+        .header(auth)
+        .body(json!({ "code": code_at(&secret, current_step()).unwrap() }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let codes: Value = serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let recovery_code = codes["recovery_codes"][0].as_str().unwrap().to_string();
+
+    let r = sign_in(&client).await;
+    assert_eq!(r.status, "2fa_required");
+
+    let response = client
+        .get("/me")
+        .header(Header::new("token", r.token.to_owned()))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let body = json!({ "token": r.token, "code": recovery_code }).to_string();
+
+    let response = client.post("/auth/2fa").body(body.clone()).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.post("/auth/2fa").body(body).dispatch().await;
+
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    User::delete_by_id(user_id).exec(&db).await.unwrap();
+}
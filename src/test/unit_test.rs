@@ -0,0 +1,110 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use super::super::rocket;
+use crate::entities::prelude::*;
+use crate::test::utils::{
+    delete_test_item, get_auth_header, get_client, get_db, get_test_item_uuid,
+    get_test_organization_id,
+};
+use crate::units::{convert, is_valid_quantity};
+use rocket::http::Status;
+use sea_orm::prelude::Decimal;
+use serde_json::json;
+
+#[test]
+fn should_convert_compatible_units() {
+    assert_eq!(
+        convert(Decimal::new(25, 1), "m", "cm"),
+        Some(Decimal::from(250))
+    );
+    assert_eq!(
+        convert(Decimal::from(750), "g", "kg"),
+        Some(Decimal::new(75, 2))
+    );
+    assert_eq!(
+        convert(Decimal::from(2), "dozen", "pcs"),
+        Some(Decimal::from(24))
+    );
+    assert_eq!(convert(Decimal::ONE, "kg", "l"), None);
+    assert_eq!(convert(Decimal::ONE, "kg", "stone"), None);
+
+    assert!(is_valid_quantity(Decimal::new(75, 2), "kg"));
+    assert!(!is_valid_quantity(Decimal::new(15, 1), "pcs"));
+    assert!(!is_valid_quantity(Decimal::from(-1), "m"));
+}
+
+#[async_test]
+async fn should_list_units() {
+    let client = get_client().await;
+
+    let response = client
+        .get("/units")
+        .header(get_auth_header(false).await)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.into_string().await.unwrap().contains("kg"));
+}
+
+#[async_test]
+async fn should_reject_fractional_pieces() {
+    let client = get_client().await;
+
+    let response = client
+        .post("/items")
+        .header(get_auth_header(true).await)
+        .body(json!({ "name": "test", "quantity": 2.5 }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+}
+
+#[async_test]
+async fn should_check_out_in_compatible_unit() {
+    let client = get_client().await;
+    let auth = get_auth_header(true).await;
+
+    let response = client
+        .post("/items")
+        .header(auth.clone())
+        .body(json!({ "name": "test", "quantity": 2.5, "unit": "kg" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Created);
+
+    let uuid = get_test_item_uuid().await;
+
+    let response = client
+        .post(format!("/items/{}/check-out", uuid))
+        .header(auth.clone())
+        .body(json!({ "quantity": 750, "unit": "g" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .post(format!("/items/{}/check-out", uuid))
+        .header(auth.clone())
+        .body(json!({ "quantity": 1, "unit": "l" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    let item = Item::from_uuid(&get_db().await, get_test_organization_id().await, &uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.quantity, Decimal::new(175, 2));
+
+    delete_test_item().await;
+}
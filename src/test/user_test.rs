@@ -7,8 +7,10 @@
  */
 use super::super::rocket;
 #[allow(unused_imports)]
-use crate::test::utils::{create_test_user, create_test_admin, delete_test_user, get_auth_header, get_client};
+use crate::test::utils::{create_test_user, create_test_admin, delete_test_user, get_auth_header, get_client, get_db};
+use crate::entities::{prelude::*, user};
 use rocket::http::Status;
+use sea_orm::*;
 use serde_json::json;
 
 #[async_test]
@@ -76,3 +78,48 @@ async fn should_add_user() {
 
     delete_test_user().await;
 }
+
+#[async_test]
+async fn should_not_add_existing_account() {
+    let client = get_client().await;
+
+    let response = client
+        .post("/users")
+        .header(get_auth_header(true).await)
+        .body(json!({ "email": "user@example.com", "password": "test-password", "firstname": "Test", "lastname": "User", "role": "admin" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Conflict);
+}
+
+#[async_test]
+async fn should_not_change_password_of_member() {
+    let client = get_client().await;
+    let db = get_db().await;
+
+    let member = User::find()
+        .filter(user::Column::Email.eq("user@example.com"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let response = client
+        .put(format!("/users/{}", member.uuid))
+        .header(get_auth_header(true).await)
+        .body(json!({ "email": "user@example.com", "password": "taken-over", "firstname": "Test", "lastname": "User", "role": "user" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let response = client
+        .put(format!("/users/{}", member.uuid))
+        .header(get_auth_header(true).await)
+        .body(json!({ "email": "attacker@example.net", "firstname": "Test", "lastname": "User", "role": "user" }).to_string())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
@@ -5,11 +5,16 @@
  * @link   https://afaan.dev
  * @link   https://github.com/AfaanBilal/iron-guard-server
  */
+use std::collections::HashMap;
+
 use super::rocket;
 use crate::{
     controllers::auth::ResponseSignIn,
     db,
-    entities::{category, item, prelude::*, user},
+    entities::{
+        category, item, organization, organization_user, prelude::*, stock_movement, supplier,
+        user,
+    },
     Config,
 };
 use bcrypt::{hash, DEFAULT_COST};
@@ -22,7 +27,7 @@ use serde_json::json;
 use uuid::Uuid;
 
 pub async fn get_db() -> DatabaseConnection {
-    let db = match db::connect(&Config::make()).await {
+    let db = match db::connect(&Config::load("debug").unwrap()).await {
         Ok(db) => db,
         Err(err) => panic!("{}", err),
     };
@@ -44,10 +49,12 @@ pub async fn create_test_user() {
         ..Default::default()
     };
 
-    match User::insert(new_user).exec(&db).await {
+    let user_id = match User::insert(new_user).exec(&db).await {
         Err(err) => panic!("{}", err.to_string()),
-        _ => 0,
+        Ok(r) => r.last_insert_id,
     };
+
+    add_test_membership(&db, user_id, "user").await;
 }
 
 #[allow(dead_code)]
@@ -64,10 +71,39 @@ pub async fn create_test_admin() {
         ..Default::default()
     };
 
-    match User::insert(new_user).exec(&db).await {
+    let user_id = match User::insert(new_user).exec(&db).await {
         Err(err) => panic!("{}", err.to_string()),
-        _ => 0,
+        Ok(r) => r.last_insert_id,
     };
+
+    add_test_membership(&db, user_id, "admin").await;
+}
+
+/// The organization test data lives in: the first one, which holds every
+/// user from before organizations were introduced.
+pub async fn get_test_organization_id() -> i32 {
+    let db = get_db().await;
+
+    Organization::find()
+        .order_by_asc(organization::Column::Id)
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap()
+        .id
+}
+
+#[allow(dead_code)]
+pub async fn add_test_membership(db: &DatabaseConnection, user_id: i32, role: &str) {
+    OrganizationUser::insert(organization_user::ActiveModel {
+        organization_id: Set(get_test_organization_id().await),
+        user_id: Set(user_id),
+        role: Set(role.to_string()),
+        ..Default::default()
+    })
+    .exec(db)
+    .await
+    .unwrap();
 }
 
 pub async fn delete_test_user() {
@@ -106,11 +142,87 @@ pub async fn delete_test_item() {
         .unwrap()
         .unwrap();
 
+    StockMovement::delete_many()
+        .filter(stock_movement::Column::ItemId.eq(item.id))
+        .exec(&db)
+        .await
+        .unwrap();
+
     item.delete(&db).await.unwrap();
 }
 
+pub async fn get_test_supplier_uuid() -> String {
+    let db = get_db().await;
+
+    Supplier::find()
+        .filter(supplier::Column::Name.eq("test"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap()
+        .uuid
+}
+
+pub async fn delete_test_supplier() {
+    let db = get_db().await;
+
+    let supplier = Supplier::find()
+        .filter(supplier::Column::Name.eq("test"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    supplier.delete(&db).await.unwrap();
+}
+
+pub async fn get_test_item_uuid() -> String {
+    let db = get_db().await;
+
+    Item::find()
+        .filter(item::Column::Name.eq("test"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap()
+        .uuid
+}
+
 pub async fn get_client() -> Client {
-    Client::tracked(rocket().await).await.unwrap()
+    let rocket = match rocket().await {
+        Ok(rocket) => rocket,
+        Err(err) => panic!("{}", err),
+    };
+
+    Client::tracked(rocket).await.unwrap()
+}
+
+/// The debug configuration with `overrides` in place of the environment's
+/// settings, so tests need not change the environment other tests read.
+#[allow(dead_code)]
+pub fn get_config(overrides: &[(&str, &str)]) -> Config {
+    let overrides = overrides
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>();
+
+    Config::load_with("debug", &|name| {
+        overrides
+            .get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+    })
+    .unwrap()
+}
+
+#[allow(dead_code)]
+pub async fn get_client_with(overrides: &[(&str, &str)]) -> Client {
+    let rocket = match crate::build(get_config(overrides)).await {
+        Ok(rocket) => rocket,
+        Err(err) => panic!("{}", err),
+    };
+
+    Client::tracked(rocket).await.unwrap()
 }
 
 pub async fn get_token(admin: bool) -> String {
@@ -0,0 +1,270 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    http::{uri::Origin, Header, Method, Status},
+    Data, Request, Response,
+};
+
+/// Entries are pruned once a map grows past this many keys.
+const PRUNE_AT: usize = 10_000;
+
+/// Failed sign-in tracking with progressive lockout, per account and per
+/// client IP. State is kept in memory, so each server instance counts on
+/// its own.
+pub struct Lockout {
+    threshold: u32,
+    ip_threshold: u32,
+    base: Duration,
+    max: Duration,
+    failures: Mutex<HashMap<String, Failures>>,
+}
+
+struct Failures {
+    count: u32,
+    last: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl Lockout {
+    pub fn new(threshold: u32, ip_threshold: u32, base: Duration, max: Duration) -> Lockout {
+        Lockout {
+            threshold,
+            ip_threshold,
+            base,
+            max,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn keys(account: &str, ip: Option<IpAddr>) -> Vec<(String, bool)> {
+        let mut keys = vec![(format!("account:{}", account.to_lowercase()), false)];
+        if let Some(ip) = ip {
+            keys.push((format!("ip:{}", ip), true));
+        }
+
+        keys
+    }
+
+    /// The seconds until the account or IP may try again, if either is locked.
+    pub fn check(&self, account: &str, ip: Option<IpAddr>) -> Result<(), u64> {
+        let failures = self.failures.lock().unwrap();
+        let now = Instant::now();
+
+        let wait = Lockout::keys(account, ip)
+            .iter()
+            .filter_map(|(key, _)| failures.get(key)?.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+            .max();
+
+        match wait {
+            Some(w) => Err(w.as_secs() + 1),
+            None => Ok(()),
+        }
+    }
+
+    /// Counts a failure. Reaching the threshold locks for the base duration,
+    /// doubling with every further failure up to the maximum.
+    pub fn fail(&self, account: &str, ip: Option<IpAddr>) {
+        let mut failures = self.failures.lock().unwrap();
+        let now = Instant::now();
+
+        if failures.len() > PRUNE_AT {
+            failures.retain(|_, f| now - f.last < self.max);
+        }
+
+        for (key, is_ip) in Lockout::keys(account, ip) {
+            let threshold = if is_ip {
+                self.ip_threshold
+            } else {
+                self.threshold
+            };
+
+            let entry = failures.entry(key).or_insert(Failures {
+                count: 0,
+                last: now,
+                locked_until: None,
+            });
+
+            // Failures are forgotten after a quiet period.
+            if now - entry.last >= self.max {
+                entry.count = 0;
+            }
+
+            entry.count += 1;
+            entry.last = now;
+
+            if threshold > 0 && entry.count >= threshold {
+                let doublings = (entry.count - threshold).min(16);
+                entry.locked_until = Some(now + (self.base * 2u32.pow(doublings)).min(self.max));
+            }
+        }
+    }
+
+    /// Clears the account's failures after a successful sign-in.
+    pub fn succeed(&self, account: &str) {
+        self.failures
+            .lock()
+            .unwrap()
+            .remove(&format!("account:{}", account.to_lowercase()));
+    }
+}
+
+/// A request budget: at most `requests` per `seconds`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Window {
+    pub requests: u32,
+    pub seconds: u64,
+}
+
+impl Window {
+    /// Parses `<requests>/<seconds>`, e.g. `20/60`. Anything else, such as
+    /// `off`, disables the limit.
+    pub fn parse(value: &str) -> Option<Window> {
+        let (requests, seconds) = value.trim().split_once('/')?;
+
+        let window = Window {
+            requests: requests.trim().parse().ok()?,
+            seconds: seconds.trim().parse().ok()?,
+        };
+
+        if window.requests == 0 || window.seconds == 0 {
+            return None;
+        }
+
+        Some(window)
+    }
+}
+
+/// The client's IP address. Clients can set `X-Real-IP` themselves, so it is
+/// only believed behind a proxy trusted to set it.
+pub fn client_ip(request: &Request<'_>, trust_proxy: bool) -> Option<IpAddr> {
+    if trust_proxy {
+        request.client_ip()
+    } else {
+        request.remote().map(|remote| remote.ip())
+    }
+}
+
+/// Fixed-window request limits per client IP, with a separate budget for
+/// each route group: `/auth` and everything else.
+pub struct RateLimit {
+    auth: Option<Window>,
+    api: Option<Window>,
+    trust_proxy: bool,
+    counters: Mutex<Counters>,
+}
+
+/// Window start and request count per route group and client.
+type Counters = HashMap<(&'static str, String), (Instant, u32)>;
+
+/// Set on requests over the limit, with the seconds until the window resets.
+struct Limited(Option<u64>);
+
+pub const LIMITED_PATH: &str = "/rate-limited";
+
+impl RateLimit {
+    pub fn new(auth: Option<Window>, api: Option<Window>, trust_proxy: bool) -> RateLimit {
+        RateLimit {
+            auth,
+            api,
+            trust_proxy,
+            counters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn group(path: &str) -> &'static str {
+        if path == "/auth" || path.starts_with("/auth/") {
+            "auth"
+        } else {
+            "api"
+        }
+    }
+
+    /// Counts a request, returning the seconds to wait if it is over the limit.
+    pub fn hit(&self, path: &str, client: &str) -> Result<(), u64> {
+        let group = RateLimit::group(path);
+        let window = match if group == "auth" { self.auth } else { self.api } {
+            Some(w) => w,
+            None => return Ok(()),
+        };
+
+        let mut counters = self.counters.lock().unwrap();
+        let now = Instant::now();
+        let length = Duration::from_secs(window.seconds);
+
+        if counters.len() > PRUNE_AT {
+            counters.retain(|_, (start, _)| now - *start < length);
+        }
+
+        let (start, count) = counters
+            .entry((group, client.to_string()))
+            .or_insert((now, 0));
+
+        if now - *start >= length {
+            *start = now;
+            *count = 0;
+        }
+
+        if *count >= window.requests {
+            return Err((length - (now - *start)).as_secs() + 1);
+        }
+
+        *count += 1;
+
+        Ok(())
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for RateLimit {
+    fn info(&self) -> Info {
+        Info {
+            name: "Rate limit requests",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    /// Fairings cannot answer requests, so requests over the limit are
+    /// rerouted to the `rate_limited` handler instead.
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        if request.method() == Method::Options {
+            return;
+        }
+
+        let client = match client_ip(request, self.trust_proxy) {
+            Some(ip) => ip.to_string(),
+            None => "unknown".to_string(),
+        };
+
+        if let Err(retry_after) = self.hit(request.uri().path().as_str(), &client) {
+            request.local_cache(|| Limited(Some(retry_after)));
+            request.set_method(Method::Get);
+            request.set_uri(Origin::parse(LIMITED_PATH).unwrap());
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if let Limited(Some(retry_after)) = request.local_cache(|| Limited(None)) {
+            response.set_header(Header::new("Retry-After", retry_after.to_string()));
+        }
+    }
+}
+
+#[get("/rate-limited")]
+pub fn rate_limited() -> Status {
+    Status::TooManyRequests
+}
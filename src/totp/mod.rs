@@ -0,0 +1,98 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use std::time::SystemTime;
+
+use base32::Alphabet;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+
+/// Time-based one-time passwords (RFC 6238) with the parameters authenticator
+/// apps assume: HMAC-SHA1, 6 digits and 30 second steps.
+pub const DIGITS: u32 = 6;
+pub const STEP: u64 = 30;
+
+/// Codes from this many steps before or after the current one are accepted,
+/// to allow for clock drift.
+const SKEW: i64 = 1;
+
+const ALPHABET: Alphabet = Alphabet::RFC4648 { padding: false };
+
+/// A new random 160-bit secret, base32 encoded.
+pub fn generate_secret() -> String {
+    base32::encode(ALPHABET, &rand::thread_rng().gen::<[u8; 20]>())
+}
+
+/// The `otpauth://` URI authenticator apps enroll from, usually shown as a QR code.
+pub fn provisioning_uri(secret: &str, issuer: &str, account: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        encode(issuer),
+        encode(account),
+        secret,
+        encode(issuer),
+        DIGITS,
+        STEP
+    )
+}
+
+pub fn current_step() -> i64 {
+    (SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        / STEP) as i64
+}
+
+/// The code for a time step, or `None` if the secret is not valid base32.
+pub fn code_at(secret: &str, step: i64) -> Option<String> {
+    let key = base32::decode(ALPHABET, secret.trim_end_matches('='))?;
+
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).ok()?;
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    Some(format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    ))
+}
+
+/// Checks a code against the steps around `step` and returns the step it
+/// matched. Steps up to `after` are rejected so a code cannot be replayed.
+pub fn verify(secret: &str, code: &str, step: i64, after: Option<i64>) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize {
+        return None;
+    }
+
+    (step - SKEW..=step + SKEW)
+        .filter(|s| !matches!(after, Some(a) if *s <= a))
+        .find(|s| code_at(secret, *s).as_deref() == Some(code))
+}
+
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
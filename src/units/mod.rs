@@ -0,0 +1,131 @@
+/**
+ * Iron Guard Server
+ *
+ * @author Afaan Bilal
+ * @link   https://afaan.dev
+ * @link   https://github.com/AfaanBilal/iron-guard-server
+ */
+use sea_orm::prelude::Decimal;
+
+pub const DEFAULT_UNIT: &str = "pcs";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dimension {
+    Count,
+    Length,
+    Mass,
+    Volume,
+}
+
+impl Dimension {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dimension::Count => "count",
+            Dimension::Length => "length",
+            Dimension::Mass => "mass",
+            Dimension::Volume => "volume",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Unit {
+    pub code: &'static str,
+    pub dimension: Dimension,
+    /// Multiplier to the dimension's base unit (pcs, m, kg or l), as a
+    /// mantissa and scale.
+    factor: (i64, u32),
+}
+
+impl Unit {
+    pub fn factor(&self) -> Decimal {
+        Decimal::new(self.factor.0, self.factor.1)
+    }
+}
+
+pub const UNITS: &[Unit] = &[
+    Unit {
+        code: "pcs",
+        dimension: Dimension::Count,
+        factor: (1, 0),
+    },
+    Unit {
+        code: "dozen",
+        dimension: Dimension::Count,
+        factor: (12, 0),
+    },
+    Unit {
+        code: "mm",
+        dimension: Dimension::Length,
+        factor: (1, 3),
+    },
+    Unit {
+        code: "cm",
+        dimension: Dimension::Length,
+        factor: (1, 2),
+    },
+    Unit {
+        code: "m",
+        dimension: Dimension::Length,
+        factor: (1, 0),
+    },
+    Unit {
+        code: "km",
+        dimension: Dimension::Length,
+        factor: (1000, 0),
+    },
+    Unit {
+        code: "mg",
+        dimension: Dimension::Mass,
+        factor: (1, 6),
+    },
+    Unit {
+        code: "g",
+        dimension: Dimension::Mass,
+        factor: (1, 3),
+    },
+    Unit {
+        code: "kg",
+        dimension: Dimension::Mass,
+        factor: (1, 0),
+    },
+    Unit {
+        code: "ml",
+        dimension: Dimension::Volume,
+        factor: (1, 3),
+    },
+    Unit {
+        code: "l",
+        dimension: Dimension::Volume,
+        factor: (1, 0),
+    },
+];
+
+pub fn find(code: &str) -> Option<&'static Unit> {
+    UNITS.iter().find(|u| u.code == code)
+}
+
+/// Converts `quantity` between units of the same dimension. Returns `None`
+/// when either unit is unknown or the dimensions differ.
+pub fn convert(quantity: Decimal, from: &str, to: &str) -> Option<Decimal> {
+    let from = find(from)?;
+    let to = find(to)?;
+
+    if from.dimension != to.dimension {
+        return None;
+    }
+
+    Some((quantity * from.factor() / to.factor()).normalize())
+}
+
+/// Whether `quantity` is a valid amount of `unit`: not negative, and a whole
+/// number for counted units.
+pub fn is_valid_quantity(quantity: Decimal, unit: &str) -> bool {
+    match find(unit) {
+        Some(u) => {
+            quantity >= Decimal::ZERO
+                && (u.dimension != Dimension::Count || quantity.fract().is_zero())
+        }
+        None => false,
+    }
+}